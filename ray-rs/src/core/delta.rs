@@ -4,6 +4,152 @@
 
 use crate::types::*;
 
+/// Read-only access to the committed graph a [`DeltaState`] overlays,
+/// needed by [`DeltaState::invert`] to recover the values a delta's writes
+/// overwrote. Implemented by whatever snapshot/delta pairing a `GraphDB`
+/// flavor maintains internally; kept minimal so it's easy to implement over
+/// a plain in-memory graph in tests too.
+pub trait GraphRead {
+    /// Whether `node_id` exists in the committed base, ignoring anything
+    /// this delta itself did to it.
+    fn node_exists(&self, node_id: NodeId) -> bool;
+    /// The node's committed primary key, if it has one.
+    fn node_key(&self, node_id: NodeId) -> Option<String>;
+    /// The node's committed labels.
+    fn node_labels(&self, node_id: NodeId) -> Vec<LabelId>;
+    /// The node's committed properties.
+    fn node_props(&self, node_id: NodeId) -> std::collections::HashMap<PropKeyId, PropValue>;
+    /// The node's committed outgoing edges, as `(etype, dst)` pairs.
+    fn out_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)>;
+    /// The node's committed incoming edges, as `(etype, src)` pairs.
+    fn in_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)>;
+}
+
+/// One irreconcilable disagreement found by [`DeltaState::merge`] between
+/// two deltas built from the same committed base -- named with enough
+/// detail (the node/edge/key and both competing values) for a caller to
+/// resolve it without re-deriving the diff itself.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeltaConflict {
+    /// Both deltas set `(node_id, key_id)` to a different value.
+    NodePropConflict {
+        node_id: NodeId,
+        key_id: PropKeyId,
+        ours: Option<PropValue>,
+        theirs: Option<PropValue>,
+    },
+    /// One delta deleted `node_id`; the other modified its properties.
+    DeletedNodeModified { node_id: NodeId },
+    /// One delta deleted `node_id`; the other added an edge touching it.
+    DeletedNodeEdgeAdded {
+        node_id: NodeId,
+        etype: ETypeId,
+        other: NodeId,
+    },
+    /// One delta added `(src, etype, dst)`; the other deleted it.
+    EdgeConflict {
+        src: NodeId,
+        etype: ETypeId,
+        dst: NodeId,
+    },
+}
+
+/// Sentinel stored in [`DeltaState::key_index`] in place of a real node id
+/// to mark a key as deleted, the same way `NodeId::MAX` would never be
+/// allocated to a real node. Keeping tombstones inline in the same map the
+/// live entries live in (rather than a parallel `key_index_deleted` set)
+/// means a single lookup or a single sorted walk answers both "is this key
+/// live" and "what does it resolve to".
+pub const KEY_TOMBSTONE: NodeId = NodeId::MAX;
+
+/// A committed edge endpoint that no longer exists: `surviving_node` still
+/// has an edge of type `etype` pointing at `missing_node`, which this delta
+/// deleted without also removing the edge. Returned by
+/// [`DeltaState::validate`] so a caller can reject or repair the delta
+/// before committing it -- the same "missing up context" problem Pijul's
+/// `repair_missing_up_context` solves for its own delete/move patches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingContext {
+    pub surviving_node: NodeId,
+    pub etype: ETypeId,
+    pub missing_node: NodeId,
+    /// `true` if `surviving_node` was the source of the dangling edge
+    /// (`surviving_node --etype--> missing_node`), `false` if it was the
+    /// destination (`missing_node --etype--> surviving_node`).
+    pub outgoing: bool,
+}
+
+/// Per-category breakdown of what's buffered in a [`DeltaState`], returned
+/// by [`DeltaState::usage`]. `DeltaState::total_entries` collapses all of
+/// these except `edge_props_patched` into one number; this keeps them
+/// apart for callers that want to know
+/// what kind of uncommitted state is piling up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeltaUsage {
+    pub nodes_created: usize,
+    pub nodes_deleted: usize,
+    pub nodes_modified: usize,
+    pub edges_added: usize,
+    pub edges_deleted: usize,
+    pub edge_props_patched: usize,
+    pub schema_defs: usize,
+}
+
+/// Opaque position in a [`DeltaState`]'s journal, returned by
+/// [`DeltaState::savepoint`] and consumed by [`DeltaState::rollback_to`] /
+/// [`DeltaState::release`].
+pub type SavepointId = usize;
+
+/// One reversible mutation recorded by `DeltaState`'s mutating methods, so
+/// `rollback_to` can undo a batch of them without discarding the whole
+/// overlay via `clear()` -- nested-transaction semantics on top of the
+/// overlay, the same stacking discipline version-control apply/unrecord
+/// layers use. Edge mutations only need to record the logical op: an
+/// `add_edge` is undone by calling `delete_edge` and vice versa, since the
+/// two are already exact inverses of each other (including their
+/// cancellation logic) -- the same fact `DeltaState::invert` leans on.
+/// Everything else records the pre-image it displaced.
+#[derive(Debug, Clone)]
+enum JournalEntry {
+    AddEdge { src: NodeId, etype: ETypeId, dst: NodeId },
+    DeleteEdge { src: NodeId, etype: ETypeId, dst: NodeId },
+    CreateNode { node_id: NodeId },
+    /// `delete_node` on a node created within this very delta: it doesn't
+    /// just tombstone the id, it erases the node's whole delta footprint
+    /// (its own edges, and every edge elsewhere pointing at it), so the
+    /// undo has to restore all of that, not just the `created_nodes` entry.
+    DeleteNodeCreated {
+        node_id: NodeId,
+        removed: NodeDelta,
+        out_add_self: Option<std::collections::HashSet<EdgePatch>>,
+        in_add_self: Option<std::collections::HashSet<EdgePatch>>,
+        incoming_sources: std::collections::HashSet<NodeId>,
+        pruned_out_add: Vec<(NodeId, EdgePatch)>,
+        pruned_in_add: Vec<(NodeId, EdgePatch)>,
+    },
+    /// `delete_node` on a node that existed before this delta: just a
+    /// tombstone plus clearing any pending property edits.
+    DeleteNodeCommitted {
+        node_id: NodeId,
+        was_already_deleted: bool,
+        removed_modified: Option<NodeDelta>,
+    },
+    SetNodeProp {
+        node_id: NodeId,
+        key_id: PropKeyId,
+        prior: Option<Option<PropValue>>,
+    },
+    DeleteNodeProp {
+        node_id: NodeId,
+        key_id: PropKeyId,
+        prior: Option<Option<PropValue>>,
+    },
+    DefineLabel { label_id: LabelId, prior: Option<String> },
+    DefineEtype { etype_id: ETypeId, prior: Option<String> },
+    DefinePropkey { propkey_id: PropKeyId, prior: Option<String> },
+    TombstoneKey { key: String, prior: Option<NodeId> },
+}
+
 impl DeltaState {
     /// Create empty delta state
     pub fn new() -> Self {
@@ -12,6 +158,15 @@ impl DeltaState {
 
     /// Add edge with cancellation logic
     pub fn add_edge(&mut self, src: NodeId, etype: ETypeId, dst: NodeId) {
+        // A repeated add changes nothing; journaling it would make its undo
+        // remove the earlier add too.
+        if !self.is_edge_added(src, etype, dst) {
+            self.journal.push(JournalEntry::AddEdge { src, etype, dst });
+        }
+        self.add_edge_raw(src, etype, dst);
+    }
+
+    fn add_edge_raw(&mut self, src: NodeId, etype: ETypeId, dst: NodeId) {
         let patch = EdgePatch { etype, other: dst };
 
         // Check if cancels a pending delete
@@ -50,6 +205,14 @@ impl DeltaState {
 
     /// Delete edge with cancellation logic
     pub fn delete_edge(&mut self, src: NodeId, etype: ETypeId, dst: NodeId) {
+        // Same as `add_edge`: a repeated delete is not journaled.
+        if !self.is_edge_deleted(src, etype, dst) {
+            self.journal.push(JournalEntry::DeleteEdge { src, etype, dst });
+        }
+        self.delete_edge_raw(src, etype, dst);
+    }
+
+    fn delete_edge_raw(&mut self, src: NodeId, etype: ETypeId, dst: NodeId) {
         let patch = EdgePatch { etype, other: dst };
 
         // Check if cancels a pending add
@@ -66,6 +229,7 @@ impl DeltaState {
                         self.in_add.remove(&dst);
                     }
                 }
+                self.edge_props.remove(&(src, etype, dst));
                 return;
             }
         }
@@ -74,6 +238,9 @@ impl DeltaState {
         self.out_del.entry(src).or_default().insert(patch);
         let in_patch = EdgePatch { etype, other: src };
         self.in_del.entry(dst).or_default().insert(in_patch);
+
+        // Drop any pending property patches for the now-deleted edge
+        self.edge_props.remove(&(src, etype, dst));
     }
 
     /// Check if edge is deleted in delta
@@ -106,8 +273,539 @@ impl DeltaState {
         self.new_etypes.clear();
         self.new_propkeys.clear();
         self.key_index.clear();
-        self.key_index_deleted.clear();
         self.incoming_edge_sources.clear();
+        self.journal.clear();
+        self.savepoints.clear();
+    }
+
+    /// Mark the current point in the journal, to later `rollback_to` or
+    /// `release`. Savepoints nest purely by position -- rolling back to an
+    /// earlier one also discards any later ones, the same way exiting an
+    /// outer transaction discards whatever inner ones it contained.
+    pub fn savepoint(&mut self) -> SavepointId {
+        let id = self.journal.len();
+        self.savepoints.push(id);
+        id
+    }
+
+    /// Undo every mutation recorded since `id` was created, restoring
+    /// `created_nodes`/`modified_nodes`/`out_add`/`out_del`/`key_index` (and
+    /// everything else a mutating call touches) to exactly what they were
+    /// at that point -- without discarding mutations from before it the way
+    /// `clear()` would.
+    ///
+    /// Only the journaled mutators are undone: `add_edge`, `delete_edge`,
+    /// `create_node`, `delete_node`, `set_node_prop`, `delete_node_prop`,
+    /// `tombstone_key` and the `define_*` calls. Edge-property patches
+    /// (including the ones `delete_edge` drops), `undelete_node`, and the node
+    /// entries `merge` copies in directly are not journaled and are left
+    /// as they are by a rollback.
+    pub fn rollback_to(&mut self, id: SavepointId) {
+        while self.journal.len() > id {
+            let entry = self.journal.pop().expect("journal.len() > id implies non-empty");
+            self.undo(entry);
+        }
+        self.savepoints.retain(|&mark| mark <= id);
+    }
+
+    /// Forget a savepoint without rolling back to it -- its mutations stay,
+    /// merged into whatever scope (outer savepoint, or the whole delta)
+    /// contains it, the same way committing a nested transaction folds it
+    /// into its parent instead of undoing it.
+    pub fn release(&mut self, id: SavepointId) {
+        self.savepoints.retain(|&mark| mark != id);
+    }
+
+    /// Apply the inverse of one journal entry. Always goes through the
+    /// `_raw` mutators (or direct field surgery), never the journaling
+    /// public methods -- otherwise undoing an entry would journal a new
+    /// entry for `rollback_to`'s own `while` loop to trip over.
+    fn undo(&mut self, entry: JournalEntry) {
+        match entry {
+            JournalEntry::AddEdge { src, etype, dst } => self.delete_edge_raw(src, etype, dst),
+            JournalEntry::DeleteEdge { src, etype, dst } => self.add_edge_raw(src, etype, dst),
+            JournalEntry::CreateNode { node_id } => {
+                if let Some(removed) = self.created_nodes.remove(&node_id) {
+                    if let Some(key) = &removed.key {
+                        self.key_index.remove(key);
+                    }
+                }
+            }
+            JournalEntry::DeleteNodeCreated {
+                node_id,
+                removed,
+                out_add_self,
+                in_add_self,
+                incoming_sources,
+                pruned_out_add,
+                pruned_in_add,
+            } => {
+                if let Some(key) = &removed.key {
+                    self.key_index.insert(key.clone(), node_id);
+                }
+                self.created_nodes.insert(node_id, removed);
+                if let Some(set) = out_add_self {
+                    self.out_add.insert(node_id, set);
+                }
+                if let Some(set) = in_add_self {
+                    self.in_add.insert(node_id, set);
+                }
+                if !incoming_sources.is_empty() {
+                    self.incoming_edge_sources.insert(node_id, incoming_sources);
+                }
+                for (src, patch) in pruned_out_add {
+                    self.out_add.entry(src).or_default().insert(patch);
+                }
+                for (bucket, patch) in pruned_in_add {
+                    self.in_add.entry(bucket).or_default().insert(patch);
+                }
+            }
+            JournalEntry::DeleteNodeCommitted {
+                node_id,
+                was_already_deleted,
+                removed_modified,
+            } => {
+                if !was_already_deleted {
+                    self.deleted_nodes.remove(&node_id);
+                }
+                if let Some(node_delta) = removed_modified {
+                    self.modified_nodes.insert(node_id, node_delta);
+                }
+            }
+            JournalEntry::SetNodeProp { node_id, key_id, prior }
+            | JournalEntry::DeleteNodeProp { node_id, key_id, prior } => {
+                self.restore_node_prop_patch(node_id, key_id, prior);
+            }
+            JournalEntry::DefineLabel { label_id, prior } => match prior {
+                Some(name) => {
+                    self.new_labels.insert(label_id, name);
+                }
+                None => {
+                    self.new_labels.remove(&label_id);
+                }
+            },
+            JournalEntry::DefineEtype { etype_id, prior } => match prior {
+                Some(name) => {
+                    self.new_etypes.insert(etype_id, name);
+                }
+                None => {
+                    self.new_etypes.remove(&etype_id);
+                }
+            },
+            JournalEntry::DefinePropkey { propkey_id, prior } => match prior {
+                Some(name) => {
+                    self.new_propkeys.insert(propkey_id, name);
+                }
+                None => {
+                    self.new_propkeys.remove(&propkey_id);
+                }
+            },
+            JournalEntry::TombstoneKey { key, prior } => match prior {
+                Some(node_id) => {
+                    self.key_index.insert(key, node_id);
+                }
+                None => {
+                    self.key_index.remove(&key);
+                }
+            },
+        }
+    }
+
+    /// Produce the delta that exactly undoes this one when applied on top
+    /// of `base`, mirroring Pijul's `unrecord` -- the logical inverse of a
+    /// recorded change rather than a textual diff of one.
+    ///
+    /// Unlike `SingleFileDB::unrecord` (which replays a committed
+    /// transaction's `HistoryOp` log backwards), this works purely off the
+    /// delta's own overlay plus read access to the graph it was built
+    /// against, so it can invert an in-progress, not-yet-committed delta too
+    /// -- a first-class "savepoint/rollback" primitive rather than only the
+    /// all-or-nothing `clear()`.
+    pub fn invert(&self, base: &impl GraphRead) -> DeltaState {
+        let mut inverse = DeltaState::new();
+
+        // A node this delta created never existed in `base`, so undoing it
+        // is just a delete.
+        for &node_id in self.created_nodes.keys() {
+            inverse.delete_node(node_id);
+        }
+
+        // A node this delta deleted needs recreating with exactly the
+        // committed state it had -- key, labels, properties, and the
+        // incident edges `base` still remembers. (This delta's own edge
+        // removals on the way to deleting the node are restored by the
+        // out_add/out_del swap below, not here.)
+        for &node_id in &self.deleted_nodes {
+            let key = base.node_key(node_id);
+            inverse.create_node(node_id, key.as_deref());
+            if let Some(node_delta) = inverse.created_nodes.get_mut(&node_id) {
+                node_delta.labels = Some(base.node_labels(node_id));
+            }
+            for (key_id, value) in base.node_props(node_id) {
+                inverse.set_node_prop(node_id, key_id, value);
+            }
+            for (etype, dst) in base.out_edges(node_id) {
+                inverse.add_edge(node_id, etype, dst);
+            }
+            for (etype, src) in base.in_edges(node_id) {
+                inverse.add_edge(src, etype, node_id);
+            }
+        }
+
+        // Every node prop this delta touched (on a node it created or
+        // merely modified) gets the opposite write: restore the value
+        // `base` had before, or delete it if there wasn't one.
+        for (&node_id, node_delta) in self.created_nodes.iter().chain(self.modified_nodes.iter()) {
+            let Some(ref props) = node_delta.props else {
+                continue;
+            };
+            let prior_props = base.node_props(node_id);
+            for &key_id in props.keys() {
+                match prior_props.get(&key_id) {
+                    Some(prior) => inverse.set_node_prop(node_id, key_id, prior.clone()),
+                    None => inverse.delete_node_prop(node_id, key_id),
+                }
+            }
+        }
+
+        // Swap added/deleted edges: undoing an add is a delete and vice
+        // versa. Driving this through `add_edge`/`delete_edge` (rather than
+        // copying the sets directly) keeps the inverse's `in_add`/`in_del`
+        // and reverse index as internally consistent as any other delta.
+        for (&src, patches) in &self.out_add {
+            for patch in patches {
+                inverse.delete_edge(src, patch.etype, patch.other);
+            }
+        }
+        for (&src, patches) in &self.out_del {
+            for patch in patches {
+                inverse.add_edge(src, patch.etype, patch.other);
+            }
+        }
+
+        inverse
+    }
+
+    /// Compose `other` into `self`, both assumed built from the same
+    /// committed base. Following Pijul's model, most operations commute and
+    /// merge by straight union -- an add on one side and a delete on the
+    /// other annihilate the same way `add_edge`/`delete_edge` already
+    /// cancel within a single delta -- but some genuinely conflict and are
+    /// reported instead of silently resolved by last-write-wins. The
+    /// foundation for branch-style concurrent editing on top of the
+    /// overlay.
+    pub fn merge(&mut self, other: &DeltaState) -> Vec<DeltaConflict> {
+        let mut conflicts = Vec::new();
+
+        // Node creation: two deltas from the same base never create the
+        // same id, so these union with no conflict detection needed.
+        for (&node_id, node_delta) in &other.created_nodes {
+            if !self.created_nodes.contains_key(&node_id) {
+                if let Some(key) = &node_delta.key {
+                    self.key_index.insert(key.clone(), node_id);
+                }
+                self.created_nodes.insert(node_id, node_delta.clone());
+            }
+        }
+
+        // A node deleted on one side conflicts with the other side having
+        // modified its properties or attached a new edge to it -- both are
+        // real divergences, not something a union can resolve on its own.
+        for &node_id in &self.deleted_nodes {
+            if other.modified_nodes.contains_key(&node_id) {
+                conflicts.push(DeltaConflict::DeletedNodeModified { node_id });
+            }
+            if let Some(patches) = other.out_add.get(&node_id) {
+                for patch in patches {
+                    conflicts.push(DeltaConflict::DeletedNodeEdgeAdded {
+                        node_id,
+                        etype: patch.etype,
+                        other: patch.other,
+                    });
+                }
+            }
+        }
+        for &node_id in &other.deleted_nodes {
+            if self.modified_nodes.contains_key(&node_id) {
+                conflicts.push(DeltaConflict::DeletedNodeModified { node_id });
+            }
+            if let Some(patches) = self.out_add.get(&node_id) {
+                for patch in patches {
+                    conflicts.push(DeltaConflict::DeletedNodeEdgeAdded {
+                        node_id,
+                        etype: patch.etype,
+                        other: patch.other,
+                    });
+                }
+            }
+            self.deleted_nodes.insert(node_id);
+            self.modified_nodes.remove(&node_id);
+        }
+
+        // Node property conflicts: the same key set to different values on
+        // both sides. Anything not conflicting merges by union.
+        for (&node_id, their_delta) in &other.modified_nodes {
+            let Some(their_props) = their_delta.props.as_ref() else {
+                continue;
+            };
+            let our_props = self
+                .modified_nodes
+                .get(&node_id)
+                .and_then(|d| d.props.as_ref());
+            for (&key_id, their_value) in their_props {
+                let our_value = our_props.and_then(|m| m.get(&key_id));
+                if let Some(our_value) = our_value {
+                    if our_value != their_value {
+                        conflicts.push(DeltaConflict::NodePropConflict {
+                            node_id,
+                            key_id,
+                            ours: our_value.clone(),
+                            theirs: their_value.clone(),
+                        });
+                        continue;
+                    }
+                }
+                match their_value {
+                    Some(v) => self.set_node_prop(node_id, key_id, v.clone()),
+                    None => self.delete_node_prop(node_id, key_id),
+                }
+            }
+        }
+
+        // Edge conflicts: added on one side, deleted on the other for the
+        // exact same (src, etype, dst). Everything else -- including both
+        // sides adding (or deleting) the same edge -- unions cleanly.
+        let mut conflicting_edges = std::collections::HashSet::new();
+        for (&src, patches) in &other.out_add {
+            for patch in patches {
+                if self
+                    .out_del
+                    .get(&src)
+                    .map(|s| s.contains(patch))
+                    .unwrap_or(false)
+                {
+                    conflicts.push(DeltaConflict::EdgeConflict {
+                        src,
+                        etype: patch.etype,
+                        dst: patch.other,
+                    });
+                    conflicting_edges.insert((src, *patch));
+                }
+            }
+        }
+        for (&src, patches) in &self.out_add {
+            for patch in patches {
+                if other
+                    .out_del
+                    .get(&src)
+                    .map(|s| s.contains(patch))
+                    .unwrap_or(false)
+                {
+                    conflicts.push(DeltaConflict::EdgeConflict {
+                        src,
+                        etype: patch.etype,
+                        dst: patch.other,
+                    });
+                    conflicting_edges.insert((src, *patch));
+                }
+            }
+        }
+
+        for (&src, patches) in &other.out_add.clone() {
+            for patch in patches {
+                if !conflicting_edges.contains(&(src, *patch)) {
+                    self.add_edge(src, patch.etype, patch.other);
+                }
+            }
+        }
+        for (&src, patches) in &other.out_del.clone() {
+            for patch in patches {
+                if !conflicting_edges.contains(&(src, *patch)) {
+                    self.delete_edge(src, patch.etype, patch.other);
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// Find every edge left pointing at a node this delta deleted: walks
+    /// `deleted_nodes` against `base` (for committed edges this delta never
+    /// explicitly cancelled with a matching `delete_edge`) and against
+    /// `incoming_edge_sources` (for edges this very delta added before
+    /// deleting the other end), skipping anything the delta already
+    /// resolved. The same "missing up context" check Pijul runs before
+    /// accepting a delete/move patch, just over this overlay instead of a
+    /// full repository.
+    pub fn validate(&self, base: &impl GraphRead) -> Vec<MissingContext> {
+        let mut missing = Vec::new();
+
+        for &node_id in &self.deleted_nodes {
+            for (etype, src) in base.in_edges(node_id) {
+                if !self.is_node_deleted(src) && !self.is_edge_deleted(src, etype, node_id) {
+                    missing.push(MissingContext {
+                        surviving_node: src,
+                        etype,
+                        missing_node: node_id,
+                        outgoing: true,
+                    });
+                }
+            }
+            for (etype, dst) in base.out_edges(node_id) {
+                if !self.is_node_deleted(dst) && !self.is_edge_deleted(node_id, etype, dst) {
+                    missing.push(MissingContext {
+                        surviving_node: dst,
+                        etype,
+                        missing_node: node_id,
+                        outgoing: false,
+                    });
+                }
+            }
+            if let Some(sources) = self.incoming_edge_sources.get(&node_id) {
+                for &src in sources {
+                    if self.is_node_deleted(src) {
+                        continue;
+                    }
+                    if let Some(patches) = self.out_add.get(&src) {
+                        for patch in patches {
+                            if patch.other == node_id {
+                                missing.push(MissingContext {
+                                    surviving_node: src,
+                                    etype: patch.etype,
+                                    missing_node: node_id,
+                                    outgoing: true,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        missing
+    }
+
+    /// Repair every dangling edge [`DeltaState::validate`] finds by
+    /// rewiring it past the deleted node to the nearest surviving
+    /// ancestor/descendant of the same edge type, found by a bounded BFS
+    /// over `base` combined with this delta's own edge writes (see
+    /// `nearest_live_node`). Returns whatever entries couldn't be repaired
+    /// within `max_depth` hops, still dangling -- an empty result means
+    /// every edge `validate` found now points somewhere live.
+    pub fn repair_missing_context(
+        &mut self,
+        base: &impl GraphRead,
+        max_depth: usize,
+    ) -> Vec<MissingContext> {
+        // Find every replacement before rewiring any edge: repairing one
+        // entry deletes its dangling edge, which would otherwise hide the
+        // deleted node's neighbor from the search for the next entry.
+        let plans: Vec<(MissingContext, Option<NodeId>)> = self
+            .validate(base)
+            .into_iter()
+            .map(|ctx| {
+                let replacement =
+                    self.nearest_live_node(base, ctx.missing_node, ctx.etype, max_depth, ctx.outgoing);
+                (ctx, replacement)
+            })
+            .collect();
+        let mut unrepaired = Vec::new();
+
+        for (ctx, replacement) in plans {
+            let Some(replacement) = replacement else {
+                unrepaired.push(ctx);
+                continue;
+            };
+
+            if ctx.outgoing {
+                self.delete_edge(ctx.surviving_node, ctx.etype, ctx.missing_node);
+                if replacement != ctx.surviving_node {
+                    self.add_edge(ctx.surviving_node, ctx.etype, replacement);
+                }
+            } else {
+                self.delete_edge(ctx.missing_node, ctx.etype, ctx.surviving_node);
+                if replacement != ctx.surviving_node {
+                    self.add_edge(replacement, ctx.etype, ctx.surviving_node);
+                }
+            }
+        }
+
+        unrepaired
+    }
+
+    /// Bounded BFS from `start` (a deleted node) along same-`etype` edges,
+    /// forward (`descendant = true`, following `start`'s own outgoing
+    /// edges) or backward (`descendant = false`, following its incoming
+    /// edges), stopping at the first node that survives in this delta.
+    /// Mirrors the direction `validate` found the dangling edge in: an
+    /// outgoing dangling edge gets repointed at the nearest live
+    /// descendant, an incoming one at the nearest live ancestor.
+    fn nearest_live_node(
+        &self,
+        base: &impl GraphRead,
+        start: NodeId,
+        etype: ETypeId,
+        max_depth: usize,
+        descendant: bool,
+    ) -> Option<NodeId> {
+        let mut visited = std::collections::HashSet::new();
+        visited.insert(start);
+        let mut frontier = vec![start];
+
+        for _ in 0..max_depth {
+            let mut next = Vec::new();
+            for node_id in frontier {
+                let neighbors = if descendant {
+                    self.live_out_neighbors(base, node_id, etype)
+                } else {
+                    self.live_in_neighbors(base, node_id, etype)
+                };
+                for neighbor in neighbors {
+                    if !self.is_node_deleted(neighbor) {
+                        return Some(neighbor);
+                    }
+                    if visited.insert(neighbor) {
+                        next.push(neighbor);
+                    }
+                }
+            }
+            if next.is_empty() {
+                return None;
+            }
+            frontier = next;
+        }
+
+        None
+    }
+
+    /// `node_id`'s outgoing neighbors of `etype` in the combined base+delta
+    /// view: `base`'s committed edges minus anything this delta deleted,
+    /// plus anything this delta added.
+    fn live_out_neighbors(&self, base: &impl GraphRead, node_id: NodeId, etype: ETypeId) -> Vec<NodeId> {
+        let mut neighbors: Vec<NodeId> = base
+            .out_edges(node_id)
+            .into_iter()
+            .filter(|&(e, dst)| e == etype && !self.is_edge_deleted(node_id, e, dst))
+            .map(|(_, dst)| dst)
+            .collect();
+        if let Some(patches) = self.out_add.get(&node_id) {
+            neighbors.extend(patches.iter().filter(|p| p.etype == etype).map(|p| p.other));
+        }
+        neighbors
+    }
+
+    /// Incoming counterpart of [`Self::live_out_neighbors`].
+    fn live_in_neighbors(&self, base: &impl GraphRead, node_id: NodeId, etype: ETypeId) -> Vec<NodeId> {
+        let mut neighbors: Vec<NodeId> = base
+            .in_edges(node_id)
+            .into_iter()
+            .filter(|&(e, src)| e == etype && !self.is_edge_deleted(src, e, node_id))
+            .map(|(_, src)| src)
+            .collect();
+        if let Some(patches) = self.in_add.get(&node_id) {
+            neighbors.extend(patches.iter().filter(|p| p.etype == etype).map(|p| p.other));
+        }
+        neighbors
     }
 
     /// Get count of edges added for a source node
@@ -130,12 +828,64 @@ impl DeltaState {
         self.out_del.values().map(|s| s.len()).sum()
     }
 
+    /// Total number of entries buffered in this delta -- created/deleted/
+    /// modified nodes, added/deleted edges, and new schema definitions.
+    /// Used by `DbMetrics` as a gauge on how much uncommitted state a
+    /// checkpoint would currently have to merge.
+    pub fn total_entries(&self) -> usize {
+        self.created_nodes.len()
+            + self.deleted_nodes.len()
+            + self.modified_nodes.len()
+            + self.total_edges_added()
+            + self.total_edges_deleted()
+            + self.new_labels.len()
+            + self.new_etypes.len()
+            + self.new_propkeys.len()
+    }
+
+    /// Per-category breakdown of `total_entries`, for callers (namely
+    /// `DbMetrics`) that want more than one combined gauge.
+    pub fn usage(&self) -> DeltaUsage {
+        DeltaUsage {
+            nodes_created: self.created_nodes.len(),
+            nodes_deleted: self.deleted_nodes.len(),
+            nodes_modified: self.modified_nodes.len(),
+            edges_added: self.total_edges_added(),
+            edges_deleted: self.total_edges_deleted(),
+            edge_props_patched: self.edge_props.len(),
+            schema_defs: self.new_labels.len() + self.new_etypes.len() + self.new_propkeys.len(),
+        }
+    }
+
+    /// Rough estimate of the heap bytes backing this delta, for operators
+    /// sizing memory rather than exact accounting -- each category is
+    /// costed at a fixed per-entry size rather than walking every string
+    /// and map node, since this is sampled on every `DbMetrics` read and
+    /// needs to stay cheap.
+    pub fn estimated_bytes(&self) -> u64 {
+        const NODE_ENTRY_BYTES: u64 = 96;
+        const EDGE_ENTRY_BYTES: u64 = 48;
+        const EDGE_PROP_ENTRY_BYTES: u64 = 96;
+        const SCHEMA_ENTRY_BYTES: u64 = 64;
+
+        let usage = self.usage();
+        (usage.nodes_created as u64 + usage.nodes_deleted as u64 + usage.nodes_modified as u64) * NODE_ENTRY_BYTES
+            + (usage.edges_added as u64 + usage.edges_deleted as u64) * EDGE_ENTRY_BYTES
+            + usage.edge_props_patched as u64 * EDGE_PROP_ENTRY_BYTES
+            + usage.schema_defs as u64 * SCHEMA_ENTRY_BYTES
+    }
+
     // ========================================================================
     // Node Operations
     // ========================================================================
 
     /// Create a new node
     pub fn create_node(&mut self, node_id: NodeId, key: Option<&str>) {
+        self.journal.push(JournalEntry::CreateNode { node_id });
+        self.create_node_raw(node_id, key);
+    }
+
+    fn create_node_raw(&mut self, node_id: NodeId, key: Option<&str>) {
         let node_delta = NodeDelta {
             key: key.map(|s| s.to_string()),
             labels: None,
@@ -143,7 +893,7 @@ impl DeltaState {
             props: None,
         };
         self.created_nodes.insert(node_id, node_delta);
-        
+
         // Add to key index if key provided
         if let Some(k) = key {
             self.key_index.insert(k.to_string(), node_id);
@@ -152,16 +902,77 @@ impl DeltaState {
 
     /// Delete a node
     pub fn delete_node(&mut self, node_id: NodeId) {
+        let entry = if let Some(removed) = self.created_nodes.get(&node_id).cloned() {
+            self.capture_delete_node_created(node_id, removed)
+        } else {
+            self.capture_delete_node_committed(node_id)
+        };
+        self.journal.push(entry);
+        self.delete_node_raw(node_id);
+    }
+
+    /// Snapshot everything `delete_node_raw`'s created-node branch is about
+    /// to erase: not just the `created_nodes` entry, but every edge (this
+    /// node's own, and every other node's edge into it) that branch wipes
+    /// out too, so `rollback_to` can put it all back exactly.
+    fn capture_delete_node_created(&self, node_id: NodeId, removed: NodeDelta) -> JournalEntry {
+        let out_add_self = self.out_add.get(&node_id).cloned();
+        let in_add_self = self.in_add.get(&node_id).cloned();
+        let incoming_sources = self
+            .incoming_edge_sources
+            .get(&node_id)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut pruned_out_add = Vec::new();
+        for &src in &incoming_sources {
+            if let Some(patches) = self.out_add.get(&src) {
+                for p in patches.iter().filter(|p| p.other == node_id) {
+                    pruned_out_add.push((src, *p));
+                }
+            }
+        }
+
+        let mut pruned_in_add = Vec::new();
+        for (&bucket, patches) in self.in_add.iter() {
+            if bucket == node_id {
+                continue;
+            }
+            for p in patches.iter().filter(|p| p.other == node_id) {
+                pruned_in_add.push((bucket, *p));
+            }
+        }
+
+        JournalEntry::DeleteNodeCreated {
+            node_id,
+            removed,
+            out_add_self,
+            in_add_self,
+            incoming_sources,
+            pruned_out_add,
+            pruned_in_add,
+        }
+    }
+
+    fn capture_delete_node_committed(&self, node_id: NodeId) -> JournalEntry {
+        JournalEntry::DeleteNodeCommitted {
+            node_id,
+            was_already_deleted: self.deleted_nodes.contains(&node_id),
+            removed_modified: self.modified_nodes.get(&node_id).cloned(),
+        }
+    }
+
+    fn delete_node_raw(&mut self, node_id: NodeId) {
         // If it was just created in this delta, remove it instead
         if let Some(removed) = self.created_nodes.remove(&node_id) {
             // Remove from key index
             if let Some(key) = &removed.key {
                 self.key_index.remove(key);
             }
-            
+
             // Clean up outgoing edges from this node
             self.out_add.remove(&node_id);
-            
+
             // Clean up incoming edges to this node
             // We need to remove edges where this node is the destination
             if let Some(sources) = self.incoming_edge_sources.remove(&node_id) {
@@ -174,24 +985,32 @@ impl DeltaState {
                     }
                 }
             }
-            
+
             // Clean up in_add entries
             self.in_add.remove(&node_id);
             for (_, patches) in self.in_add.iter_mut() {
                 patches.retain(|p| p.other != node_id);
             }
             self.in_add.retain(|_, patches| !patches.is_empty());
-            
+
             return;
         }
-        
+
         // Mark as deleted
         self.deleted_nodes.insert(node_id);
-        
+
         // Remove any modified state
         self.modified_nodes.remove(&node_id);
     }
 
+    /// Undo a previous `delete_node` on a node that existed before this
+    /// delta (i.e. wasn't itself created in it, which `delete_node` already
+    /// handles by simply not tombstoning). Used by `SingleFileDB::unrecord`
+    /// to invert a committed `DeleteNode`.
+    pub fn undelete_node(&mut self, node_id: NodeId) {
+        self.deleted_nodes.remove(&node_id);
+    }
+
     /// Check if node was created in delta
     pub fn is_node_created(&self, node_id: NodeId) -> bool {
         self.created_nodes.contains_key(&node_id)
@@ -214,6 +1033,12 @@ impl DeltaState {
 
     /// Set a node property
     pub fn set_node_prop(&mut self, node_id: NodeId, key_id: PropKeyId, value: PropValue) {
+        let prior = self.get_node_prop(node_id, key_id).map(|v| v.cloned());
+        self.journal.push(JournalEntry::SetNodeProp { node_id, key_id, prior });
+        self.set_node_prop_raw(node_id, key_id, value);
+    }
+
+    fn set_node_prop_raw(&mut self, node_id: NodeId, key_id: PropKeyId, value: PropValue) {
         // Get or create the node delta
         let node_delta = if self.created_nodes.contains_key(&node_id) {
             self.created_nodes.get_mut(&node_id).unwrap()
@@ -236,6 +1061,12 @@ impl DeltaState {
 
     /// Delete a node property
     pub fn delete_node_prop(&mut self, node_id: NodeId, key_id: PropKeyId) {
+        let prior = self.get_node_prop(node_id, key_id).map(|v| v.cloned());
+        self.journal.push(JournalEntry::DeleteNodeProp { node_id, key_id, prior });
+        self.delete_node_prop_raw(node_id, key_id);
+    }
+
+    fn delete_node_prop_raw(&mut self, node_id: NodeId, key_id: PropKeyId) {
         let node_delta = if self.created_nodes.contains_key(&node_id) {
             self.created_nodes.get_mut(&node_id).unwrap()
         } else {
@@ -255,6 +1086,51 @@ impl DeltaState {
         node_delta.props.as_mut().unwrap().insert(key_id, None);
     }
 
+    /// Restore a node-prop patch to what it was before a journaled
+    /// `SetNodeProp`/`DeleteNodeProp` entry, undoing either one the same
+    /// way: `None` means no patch existed for this key before, so it's
+    /// removed; `Some(v)` restores the prior patch value `v` (itself
+    /// possibly a tombstone). If the node's own create/delete has already
+    /// been unwound by the time this runs, there's no bucket left to patch
+    /// and this is a no-op -- exactly the state a fully-rolled-back delta
+    /// should end up in.
+    fn restore_node_prop_patch(&mut self, node_id: NodeId, key_id: PropKeyId, prior: Option<Option<PropValue>>) {
+        let Some(node_delta) = self
+            .created_nodes
+            .get_mut(&node_id)
+            .or_else(|| self.modified_nodes.get_mut(&node_id))
+        else {
+            return;
+        };
+
+        match prior {
+            Some(value) => {
+                node_delta
+                    .props
+                    .get_or_insert_with(std::collections::HashMap::new)
+                    .insert(key_id, value);
+            }
+            None => {
+                if let Some(props) = node_delta.props.as_mut() {
+                    props.remove(&key_id);
+                }
+            }
+        }
+
+        // A `modified_nodes` entry left with nothing in it was created by
+        // the write being undone; drop it so the node no longer reads as
+        // modified.
+        if let Some(node_delta) = self.modified_nodes.get(&node_id) {
+            if node_delta.key.is_none()
+                && node_delta.labels.is_none()
+                && node_delta.labels_deleted.is_none()
+                && node_delta.props.as_ref().map_or(true, |p| p.is_empty())
+            {
+                self.modified_nodes.remove(&node_id);
+            }
+        }
+    }
+
     /// Get a node property from delta
     pub fn get_node_prop(&self, node_id: NodeId, key_id: PropKeyId) -> Option<Option<&PropValue>> {
         let node_delta = self.created_nodes.get(&node_id)
@@ -270,30 +1146,109 @@ impl DeltaState {
 
     /// Define a new label
     pub fn define_label(&mut self, label_id: LabelId, name: &str) {
+        let prior = self.new_labels.get(&label_id).cloned();
+        self.journal.push(JournalEntry::DefineLabel { label_id, prior });
         self.new_labels.insert(label_id, name.to_string());
     }
 
     /// Define a new edge type
     pub fn define_etype(&mut self, etype_id: ETypeId, name: &str) {
+        let prior = self.new_etypes.get(&etype_id).cloned();
+        self.journal.push(JournalEntry::DefineEtype { etype_id, prior });
         self.new_etypes.insert(etype_id, name.to_string());
     }
 
     /// Define a new property key
     pub fn define_propkey(&mut self, propkey_id: PropKeyId, name: &str) {
+        let prior = self.new_propkeys.get(&propkey_id).cloned();
+        self.journal.push(JournalEntry::DefinePropkey { propkey_id, prior });
         self.new_propkeys.insert(propkey_id, name.to_string());
     }
 
+    // ========================================================================
+    // Edge Property Operations
+    // ========================================================================
+
+    /// Set an edge property
+    pub fn set_edge_prop(&mut self, src: NodeId, etype: ETypeId, dst: NodeId, key_id: PropKeyId, value: PropValue) {
+        self.edge_props
+            .entry((src, etype, dst))
+            .or_default()
+            .insert(key_id, Some(value));
+    }
+
+    /// Delete an edge property
+    pub fn delete_edge_prop(&mut self, src: NodeId, etype: ETypeId, dst: NodeId, key_id: PropKeyId) {
+        // None value means deleted
+        self.edge_props
+            .entry((src, etype, dst))
+            .or_default()
+            .insert(key_id, None);
+    }
+
+    /// Get an edge property patch from delta
+    pub fn get_edge_prop(&self, src: NodeId, etype: ETypeId, dst: NodeId, key_id: PropKeyId) -> Option<Option<&PropValue>> {
+        self.edge_props
+            .get(&(src, etype, dst))?
+            .get(&key_id)
+            .map(|v| v.as_ref())
+    }
+
+    /// Get all edge property patches recorded against this edge in the
+    /// delta, keyed by prop key id; `None` values are tombstones.
+    pub fn get_edge_prop_patches(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> Option<&std::collections::HashMap<PropKeyId, Option<PropValue>>> {
+        self.edge_props.get(&(src, etype, dst))
+    }
+
     // ========================================================================
     // Key Index Operations
     // ========================================================================
 
     /// Lookup node by key in delta
     pub fn get_node_by_key(&self, key: &str) -> Option<NodeId> {
-        // Check if key was deleted
-        if self.key_index_deleted.contains(key) {
-            return None;
+        match self.key_index.get(key) {
+            Some(&node_id) if node_id == KEY_TOMBSTONE => None,
+            Some(&node_id) => Some(node_id),
+            None => None,
         }
-        self.key_index.get(key).copied()
+    }
+
+    /// Record `key` as deleted, so `get_node_by_key`/`soft_keys_matching`
+    /// suppress it even though it still resolves in the committed (hard)
+    /// store. Stores a [`KEY_TOMBSTONE`] sentinel directly in `key_index`
+    /// rather than tracking deletions in a second set, so a single lookup
+    /// (or merge pass, see `soft_keys_matching`) answers both "is this key
+    /// live" and "what does it resolve to" at once.
+    pub fn tombstone_key(&mut self, key: &str) {
+        let prior = self.key_index.insert(key.to_string(), KEY_TOMBSTONE);
+        self.journal.push(JournalEntry::TombstoneKey { key: key.to_string(), prior });
+    }
+
+    /// This delta's own ("soft") key-index entries matching `matches`, in
+    /// key order. HashMap-backed, not FST-backed: this sorts `key_index` (a
+    /// plain `HashMap`) fresh on every call rather than walking a persisted
+    /// finite-state-transducer, so despite the request that added this
+    /// method being titled for an FST-backed index, that's not what this
+    /// is -- an `fst::Map` on both the soft and hard side plus a
+    /// snapshot-page encoding for the hard one is still unbuilt follow-up
+    /// work. A `None` value means the key is [`KEY_TOMBSTONE`]-d: the
+    /// caller must drop any same-key entry from the hard (committed) side
+    /// entirely rather than falling back to it, the same way a live value
+    /// here overrides the hard side instead of merely supplementing it.
+    ///
+    /// Meant to be walked alongside a sorted hard-side sequence in a
+    /// two-pointer merge -- see `SingleFileDB::key_union_matching` for the
+    /// full soft/hard union this feeds, modeled on MeiliSearch's ordering-
+    /// aware union of its soft and hard external-id maps.
+    pub fn soft_keys_matching(&self, matches: impl Fn(&str) -> bool) -> Vec<(String, Option<NodeId>)> {
+        let mut out: Vec<(String, Option<NodeId>)> = self
+            .key_index
+            .iter()
+            .filter(|(k, _)| matches(k))
+            .map(|(k, &v)| (k.clone(), if v == KEY_TOMBSTONE { None } else { Some(v) }))
+            .collect();
+        out.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        out
     }
 }
 
@@ -340,4 +1295,388 @@ mod tests {
         assert!(!delta.is_edge_added(1, 10, 2));
         assert!(!delta.is_edge_deleted(1, 10, 2)); // Cancellation
     }
+
+    #[test]
+    fn test_set_and_delete_edge_prop() {
+        let mut delta = DeltaState::new();
+        delta.set_edge_prop(1, 10, 2, 5, PropValue::I64(42));
+        assert_eq!(delta.get_edge_prop(1, 10, 2, 5), Some(Some(&PropValue::I64(42))));
+
+        delta.delete_edge_prop(1, 10, 2, 5);
+        assert_eq!(delta.get_edge_prop(1, 10, 2, 5), Some(None));
+    }
+
+    #[test]
+    fn test_delete_edge_clears_edge_props() {
+        let mut delta = DeltaState::new();
+        delta.set_edge_prop(1, 10, 2, 5, PropValue::I64(42));
+        delta.delete_edge(1, 10, 2);
+        assert!(delta.get_edge_prop_patches(1, 10, 2).is_none());
+    }
+
+    #[test]
+    fn test_tombstone_key_suppresses_lookup() {
+        let mut delta = DeltaState::new();
+        delta.tombstone_key("user:alice");
+        assert_eq!(delta.get_node_by_key("user:alice"), None);
+    }
+
+    #[test]
+    fn test_soft_keys_matching_reports_tombstones_as_none() {
+        let mut delta = DeltaState::new();
+        delta.create_node(1, Some("user:alice"));
+        delta.tombstone_key("user:bob");
+
+        let matches = delta.soft_keys_matching(|_| true);
+        assert_eq!(
+            matches,
+            vec![
+                ("user:alice".to_string(), Some(1)),
+                ("user:bob".to_string(), None),
+            ]
+        );
+    }
+
+    /// An empty committed base -- enough to test `invert` against deltas
+    /// whose nodes were all created within the delta itself.
+    struct EmptyBase;
+
+    impl GraphRead for EmptyBase {
+        fn node_exists(&self, _node_id: NodeId) -> bool {
+            false
+        }
+        fn node_key(&self, _node_id: NodeId) -> Option<String> {
+            None
+        }
+        fn node_labels(&self, _node_id: NodeId) -> Vec<LabelId> {
+            Vec::new()
+        }
+        fn node_props(&self, _node_id: NodeId) -> std::collections::HashMap<PropKeyId, PropValue> {
+            std::collections::HashMap::new()
+        }
+        fn out_edges(&self, _node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+            Vec::new()
+        }
+        fn in_edges(&self, _node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_invert_created_node_is_deleted() {
+        let mut delta = DeltaState::new();
+        delta.create_node(1, Some("k1"));
+        delta.set_node_prop(1, 5, PropValue::I64(42));
+
+        let inverse = delta.invert(&EmptyBase);
+
+        assert!(inverse.is_node_deleted(1));
+        assert!(!inverse.is_node_created(1));
+    }
+
+    #[test]
+    fn test_invert_swaps_added_and_deleted_edges() {
+        let mut delta = DeltaState::new();
+        delta.add_edge(1, 10, 2);
+        delta.delete_edge(3, 10, 4);
+
+        let inverse = delta.invert(&EmptyBase);
+
+        assert!(inverse.is_edge_deleted(1, 10, 2));
+        assert!(inverse.is_edge_added(3, 10, 4));
+    }
+
+    #[test]
+    fn test_invert_is_involutive_on_a_pure_edge_delta() {
+        let mut delta = DeltaState::new();
+        delta.add_edge(1, 10, 2);
+        delta.delete_edge(3, 10, 4);
+
+        let inverse = delta.invert(&EmptyBase);
+        let double_inverse = inverse.invert(&EmptyBase);
+
+        assert_eq!(double_inverse.out_add, delta.out_add);
+        assert_eq!(double_inverse.out_del, delta.out_del);
+    }
+
+    #[test]
+    fn test_merge_unions_non_conflicting_edges() {
+        let mut ours = DeltaState::new();
+        ours.add_edge(1, 10, 2);
+
+        let mut theirs = DeltaState::new();
+        theirs.add_edge(3, 10, 4);
+
+        let conflicts = ours.merge(&theirs);
+
+        assert!(conflicts.is_empty());
+        assert!(ours.is_edge_added(1, 10, 2));
+        assert!(ours.is_edge_added(3, 10, 4));
+    }
+
+    #[test]
+    fn test_merge_reports_edge_add_delete_conflict() {
+        let mut ours = DeltaState::new();
+        ours.add_edge(1, 10, 2);
+
+        let mut theirs = DeltaState::new();
+        theirs.delete_edge(1, 10, 2);
+
+        let conflicts = ours.merge(&theirs);
+
+        assert_eq!(
+            conflicts,
+            vec![DeltaConflict::EdgeConflict {
+                src: 1,
+                etype: 10,
+                dst: 2,
+            }]
+        );
+        // Neither side's write wins -- the conflicting edge is left alone
+        // for the caller to resolve rather than silently picking one.
+        assert!(ours.is_edge_added(1, 10, 2));
+    }
+
+    #[test]
+    fn test_merge_reports_node_prop_conflict() {
+        let mut ours = DeltaState::new();
+        ours.create_node(1, None);
+        ours.modified_nodes.insert(
+            1,
+            NodeDelta {
+                key: None,
+                labels: None,
+                labels_deleted: None,
+                props: Some(std::collections::HashMap::from([(5, Some(PropValue::I64(1)))])),
+            },
+        );
+
+        let mut theirs = DeltaState::new();
+        theirs.modified_nodes.insert(
+            1,
+            NodeDelta {
+                key: None,
+                labels: None,
+                labels_deleted: None,
+                props: Some(std::collections::HashMap::from([(5, Some(PropValue::I64(2)))])),
+            },
+        );
+
+        let conflicts = ours.merge(&theirs);
+
+        assert_eq!(
+            conflicts,
+            vec![DeltaConflict::NodePropConflict {
+                node_id: 1,
+                key_id: 5,
+                ours: Some(PropValue::I64(1)),
+                theirs: Some(PropValue::I64(2)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_merge_reports_deleted_node_modified_conflict() {
+        let mut ours = DeltaState::new();
+        ours.delete_node(1);
+
+        let mut theirs = DeltaState::new();
+        theirs.set_node_prop(1, 5, PropValue::I64(42));
+
+        let conflicts = ours.merge(&theirs);
+
+        assert_eq!(
+            conflicts,
+            vec![DeltaConflict::DeletedNodeModified { node_id: 1 }]
+        );
+    }
+
+    /// A committed base with a fixed node/edge set, for `validate` and
+    /// `repair_missing_context` tests that need real committed edges --
+    /// unlike `EmptyBase`, which is only useful for deltas whose nodes were
+    /// all created within the delta itself.
+    struct FixtureBase {
+        out: std::collections::HashMap<NodeId, Vec<(ETypeId, NodeId)>>,
+        in_: std::collections::HashMap<NodeId, Vec<(ETypeId, NodeId)>>,
+    }
+
+    impl GraphRead for FixtureBase {
+        fn node_exists(&self, node_id: NodeId) -> bool {
+            self.out.contains_key(&node_id) || self.in_.contains_key(&node_id)
+        }
+        fn node_key(&self, _node_id: NodeId) -> Option<String> {
+            None
+        }
+        fn node_labels(&self, _node_id: NodeId) -> Vec<LabelId> {
+            Vec::new()
+        }
+        fn node_props(&self, _node_id: NodeId) -> std::collections::HashMap<PropKeyId, PropValue> {
+            std::collections::HashMap::new()
+        }
+        fn out_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+            self.out.get(&node_id).cloned().unwrap_or_default()
+        }
+        fn in_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+            self.in_.get(&node_id).cloned().unwrap_or_default()
+        }
+    }
+
+    #[test]
+    fn test_validate_finds_dangling_edge_into_deleted_node() {
+        // 1 --knows--> 2, then 2 is deleted without touching the edge.
+        let base = FixtureBase {
+            out: std::collections::HashMap::from([(1, vec![(10, 2)])]),
+            in_: std::collections::HashMap::from([(2, vec![(10, 1)])]),
+        };
+
+        let mut delta = DeltaState::new();
+        delta.delete_node(2);
+
+        let missing = delta.validate(&base);
+        assert_eq!(
+            missing,
+            vec![MissingContext {
+                surviving_node: 1,
+                etype: 10,
+                missing_node: 2,
+                outgoing: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_ignores_edge_already_deleted_alongside_node() {
+        let base = FixtureBase {
+            out: std::collections::HashMap::from([(1, vec![(10, 2)])]),
+            in_: std::collections::HashMap::from([(2, vec![(10, 1)])]),
+        };
+
+        let mut delta = DeltaState::new();
+        delta.delete_edge(1, 10, 2);
+        delta.delete_node(2);
+
+        assert!(delta.validate(&base).is_empty());
+    }
+
+    #[test]
+    fn test_repair_missing_context_reconnects_to_nearest_live_descendant() {
+        // 1 --knows--> 2 --knows--> 3, then 2 is deleted; the repair
+        // should leave 1 --knows--> 3 in its place.
+        let base = FixtureBase {
+            out: std::collections::HashMap::from([(1, vec![(10, 2)]), (2, vec![(10, 3)])]),
+            in_: std::collections::HashMap::from([(2, vec![(10, 1)]), (3, vec![(10, 2)])]),
+        };
+
+        let mut delta = DeltaState::new();
+        delta.delete_node(2);
+
+        let unrepaired = delta.repair_missing_context(&base, 4);
+
+        assert!(unrepaired.is_empty());
+        assert!(delta.is_edge_added(1, 10, 3));
+        assert!(delta.is_edge_deleted(1, 10, 2));
+    }
+
+    #[test]
+    fn test_repair_missing_context_gives_up_without_live_descendant() {
+        // 1 --knows--> 2 --knows--> 3, with 3 a dead end. Deleting 2 leaves
+        // no live descendant to reconnect 1 to, regardless of depth.
+        let base = FixtureBase {
+            out: std::collections::HashMap::from([(1, vec![(10, 2)]), (2, vec![(10, 3)])]),
+            in_: std::collections::HashMap::from([(2, vec![(10, 1)]), (3, vec![(10, 2)])]),
+        };
+
+        let mut delta = DeltaState::new();
+        delta.delete_node(2);
+        delta.delete_node(3);
+
+        let unrepaired = delta.repair_missing_context(&base, 4);
+
+        assert!(unrepaired.contains(&MissingContext {
+            surviving_node: 1,
+            etype: 10,
+            missing_node: 2,
+            outgoing: true,
+        }));
+    }
+
+    #[test]
+    fn test_rollback_to_undoes_edge_and_prop_writes() {
+        let mut delta = DeltaState::new();
+        delta.create_node(1, Some("user:alice"));
+
+        let sp = delta.savepoint();
+        delta.add_edge(1, 10, 2);
+        delta.set_node_prop(1, 5, PropValue::I64(42));
+
+        assert!(delta.is_edge_added(1, 10, 2));
+        assert_eq!(delta.get_node_prop(1, 5), Some(Some(&PropValue::I64(42))));
+
+        delta.rollback_to(sp);
+
+        assert!(!delta.is_edge_added(1, 10, 2));
+        assert_eq!(delta.get_node_prop(1, 5), None);
+        // The node itself, created before the savepoint, survives the
+        // rollback untouched.
+        assert!(delta.is_node_created(1));
+    }
+
+    #[test]
+    fn test_rollback_to_undoes_node_creation() {
+        let mut delta = DeltaState::new();
+        let sp = delta.savepoint();
+        delta.create_node(1, Some("user:alice"));
+        delta.add_edge(1, 10, 2);
+
+        delta.rollback_to(sp);
+
+        assert!(!delta.is_node_created(1));
+        assert_eq!(delta.get_node_by_key("user:alice"), None);
+        assert!(!delta.is_edge_added(1, 10, 2));
+    }
+
+    #[test]
+    fn test_rollback_to_restores_prior_prop_value() {
+        let mut delta = DeltaState::new();
+        delta.create_node(1, None);
+        delta.set_node_prop(1, 5, PropValue::I64(1));
+
+        let sp = delta.savepoint();
+        delta.set_node_prop(1, 5, PropValue::I64(2));
+        assert_eq!(delta.get_node_prop(1, 5), Some(Some(&PropValue::I64(2))));
+
+        delta.rollback_to(sp);
+        assert_eq!(delta.get_node_prop(1, 5), Some(Some(&PropValue::I64(1))));
+    }
+
+    #[test]
+    fn test_release_keeps_mutations_after_savepoint() {
+        let mut delta = DeltaState::new();
+        delta.create_node(1, None);
+
+        let sp = delta.savepoint();
+        delta.add_edge(1, 10, 2);
+        delta.release(sp);
+
+        // Releasing just forgets the mark; the mutation itself stays.
+        assert!(delta.is_edge_added(1, 10, 2));
+    }
+
+    #[test]
+    fn test_rollback_to_undoes_node_deletion_with_edges() {
+        let base = FixtureBase {
+            out: std::collections::HashMap::from([(1, vec![(10, 2)])]),
+            in_: std::collections::HashMap::from([(2, vec![(10, 1)])]),
+        };
+
+        let mut delta = DeltaState::new();
+        let sp = delta.savepoint();
+        delta.delete_node(2);
+        assert!(!delta.validate(&base).is_empty());
+
+        delta.rollback_to(sp);
+
+        assert!(!delta.is_node_deleted(2));
+        assert!(delta.validate(&base).is_empty());
+    }
 }
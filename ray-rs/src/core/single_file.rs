@@ -5,13 +5,14 @@
 //!
 //! Ported from src/ray/graph-db/single-file.ts
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 
 use parking_lot::{Mutex, RwLock};
 
 use crate::constants::*;
+use crate::core::delta::{DeltaUsage, KEY_TOMBSTONE};
 use crate::core::pager::{create_pager, open_pager, is_valid_page_size, pages_to_store, FilePager};
 use crate::core::snapshot::writer::{build_snapshot_to_memory, NodeData, EdgeData, SnapshotBuildInput};
 use crate::core::snapshot::reader::SnapshotData;
@@ -20,14 +21,32 @@ use crate::core::wal::record::{
     extract_committed_transactions, parse_create_node_payload, parse_delete_node_payload,
     parse_add_edge_payload, parse_delete_edge_payload, parse_set_node_prop_payload,
     parse_del_node_prop_payload, parse_define_label_payload, parse_define_etype_payload,
-    parse_define_propkey_payload, ParsedWalRecord, WalRecord,
+    parse_define_propkey_payload, parse_undelete_node_payload, parse_set_edge_prop_payload,
+    parse_del_edge_prop_payload, ParsedWalRecord, WalRecord,
     build_begin_payload, build_commit_payload, build_rollback_payload,
     build_create_node_payload, build_delete_node_payload, build_add_edge_payload,
     build_delete_edge_payload, build_set_node_prop_payload, build_del_node_prop_payload,
     build_define_label_payload, build_define_etype_payload, build_define_propkey_payload,
+    build_undelete_node_payload, build_set_edge_prop_payload, build_del_edge_prop_payload,
 };
 use crate::error::{RayError, Result};
 use crate::types::*;
+use crate::vector::types::VectorManifest;
+
+mod vector;
+
+thread_local! {
+    /// `TxId` of the read-only transaction `begin(true)` opened on this
+    /// thread, if any. Reads never pin a snapshot (every read method
+    /// consults the live `self.delta`/`self.snapshot` directly), so any
+    /// number of read-only transactions can be open across different
+    /// threads at once -- including alongside the one in-progress write
+    /// transaction in `SingleFileDB::current_tx`. This thread-local is what
+    /// lets the zero-argument `commit`/`rollback`/`current_txid` calls on a
+    /// given thread unambiguously mean "the read transaction I, this
+    /// thread, opened" rather than some other thread's.
+    static ACTIVE_READ_TX: std::cell::Cell<Option<TxId>> = const { std::cell::Cell::new(None) };
+}
 
 // ============================================================================
 // Single-File GraphDB
@@ -57,8 +76,28 @@ pub struct SingleFileDB {
     next_propkey_id: AtomicU32,
     next_tx_id: AtomicU64,
 
-    /// Current active transaction
+    /// The one in-progress *write* transaction, if any. Every write method
+    /// mutates the single shared `self.delta` overlay directly, so two of
+    /// these can't run concurrently without stomping on each other's
+    /// uncommitted state -- `begin` still serializes writers the way it
+    /// always has. See [`read_txs`](Self::read_txs) for the transactions
+    /// that *can* run alongside this one.
     pub current_tx: Mutex<Option<TxState>>,
+    /// Every open read-only transaction, keyed by its [`TxId`]. Unlike
+    /// `current_tx`, any number of these can be open at once -- including
+    /// alongside the one in-progress writer -- because read methods never
+    /// pin a snapshot; they consult the live `self.delta`/`self.snapshot`
+    /// directly regardless of which (if any) read-only transaction is
+    /// nominally open. `begin(true)` records the calling thread's `TxId`
+    /// here (and in its `ACTIVE_READ_TX` thread-local) so that thread's own
+    /// `commit`/`rollback` can find it again unambiguously.
+    ///
+    /// This is read-concurrency bookkeeping, not a multi-writer MVCC
+    /// registry: it only ever holds read-only `TxState`s, `current_tx`
+    /// still admits exactly one writer, and nothing here buffers or merges
+    /// concurrent writes. [`SingleFileDB::begin_optimistic`] is the actual
+    /// concurrent-writer mechanism.
+    read_txs: Mutex<HashMap<TxId, TxState>>,
 
     /// Label name -> ID mapping
     label_names: RwLock<HashMap<String, LabelId>>,
@@ -81,22 +120,540 @@ pub struct SingleFileDB {
     background_checkpoint: bool,
     /// Current checkpoint state
     checkpoint_status: Mutex<CheckpointStatus>,
+    /// Default commit durability when a transaction doesn't pick one
+    default_durability: Durability,
+    /// Snapshot compression applied by `checkpoint`
+    default_compression: Option<SnapshotCompression>,
+    /// Set when this open rebuilt state from an exhaustive WAL scan, either
+    /// because the snapshot failed to parse or `options.repair` asked for it.
+    pub last_repair: Option<RepairReport>,
+
+    /// Commit-version allocator for optimistic concurrency checks: bumped
+    /// once per successful write commit.
+    next_version: AtomicU64,
+    /// Last commit version at which each node was touched, so a committing
+    /// transaction can detect a write-write conflict against work done by
+    /// someone else after its snapshot was taken. `current_tx` still admits
+    /// only one regular transaction at a time, but [`OptimisticTxn`] builds
+    /// its writes against a private buffer and validates its read set
+    /// against this map at commit, which is how several of those can be
+    /// built concurrently and still have conflicting writes caught.
+    committed_versions: RwLock<HashMap<NodeId, u64>>,
+
+    /// Progress/abort-signal for the currently (or most recently) running
+    /// background checkpoint.
+    checkpoint_progress: std::sync::Arc<CheckpointProgress>,
+
+    /// Size/timing metrics from the most recent snapshot build, read back via
+    /// [`SingleFileDB::compression_stats`].
+    last_compression_stats: RwLock<Option<CompressionStats>>,
+
+    /// Structured counters across the WAL, checkpoint, and transaction
+    /// subsystems, read back via [`SingleFileDB::metrics`].
+    metrics: DbMetrics,
+
+    /// Opt-in per-operation timing for `commit`/`checkpoint`/
+    /// `background_checkpoint`, read back via [`SingleFileDB::perf_context`].
+    perf: PerfContext,
+
+    /// Batches `Durability::Immediate` fsyncs across committers so several
+    /// arriving close together share one `wal.flush` + `pager.sync`.
+    group_commit: GroupCommitCoordinator,
+
+    /// Reusable frontier/result buffers for [`SingleFileDB::reachable`] and
+    /// [`SingleFileDB::shortest_path`], cleared (not reallocated) at the
+    /// start of each call.
+    traversal_scratch: Mutex<TraversalScratch>,
+
+    /// Bounded log of committed transactions' inverse deltas, newest at the
+    /// back, used by [`SingleFileDB::unrecord`] to undo a past commit.
+    /// Capped at [`MAX_COMMIT_HISTORY`]; entries older than that are no
+    /// longer revertible.
+    history: Mutex<VecDeque<CommitHistoryEntry>>,
+
+    /// Lazily-decoded, LRU-bounded cache of snapshot node property maps
+    /// backing [`SingleFileDB::get_node_props`]. Cleared whenever the
+    /// mapped snapshot is replaced.
+    node_prop_cache: Mutex<NodePropCache>,
+    /// Fingerprint of the file backing `snapshot` as of the last time it was
+    /// mapped, checked by [`SingleFileDB::verify_snapshot_identity`].
+    snapshot_identity: RwLock<Option<SnapshotIdentity>>,
+
+    /// Live [`ReadSnapshot`] handles per mapped snapshot generation. A
+    /// generation present here with a nonzero count is pinned: `checkpoint`
+    /// and `background_checkpoint` refuse to overwrite its pages until every
+    /// handle referencing it is dropped.
+    snapshot_refs: Mutex<HashMap<u64, usize>>,
+
+    /// Named subgraphs ("column families") registered by
+    /// [`SingleFileDB::create_graph`], keyed by name. Each has its own
+    /// node-id sequence and edge-type/property-key name tables, isolated
+    /// from the default namespace and every other named one -- see
+    /// [`GraphNamespace`] and [`GraphHandle`].
+    namespaces: RwLock<HashMap<String, std::sync::Arc<GraphNamespace>>>,
+    /// Allocator for [`NamespaceId`]s handed out by `create_graph`. Starts
+    /// at 1 -- 0 is reserved for the default (unnamed) namespace that every
+    /// plain `create_node`/`add_edge` call already uses.
+    next_namespace_id: AtomicU32,
+
+    /// Nodes touched by a commit since the last real `checkpoint`, drained
+    /// by [`SingleFileDB::checkpoint_incremental`] into a [`PendingLayer`]
+    /// marker.
+    dirty_since_checkpoint: Mutex<HashSet<NodeId>>,
+    /// Layer markers cut by `checkpoint_incremental` since the last real
+    /// `checkpoint`, newest at the back. See `checkpoint_incremental` for
+    /// what these do and don't represent.
+    pending_layers: Mutex<VecDeque<PendingLayer>>,
+    /// Per-property-key vector stores, loaded from the snapshot at open and
+    /// mutated in place by committed transactions. See
+    /// `vector::apply_pending_vectors` and `TxState::pending_vectors`.
+    vector_stores: RwLock<HashMap<PropKeyId, VectorManifest>>,
 }
 
-/// Transaction state
+/// How many past commits [`SingleFileDB::history`] keeps around for
+/// [`SingleFileDB::unrecord`]. Oldest entries are dropped once this is
+/// exceeded, the same way the WAL itself is bounded.
+const MAX_COMMIT_HISTORY: usize = 256;
+
+/// How many markers [`SingleFileDB::checkpoint_incremental`] accumulates
+/// before folding them into one real `checkpoint`.
+const MAX_PENDING_LAYERS: usize = 8;
+
+/// A cheap, in-memory-only marker cut by
+/// [`SingleFileDB::checkpoint_incremental`] recording that a batch of
+/// writes has piled up since the last real `checkpoint`. Doesn't hold a
+/// copy of the changed data itself -- nothing reads it back out; see
+/// `checkpoint_incremental`'s doc comment for what this is (and isn't) a
+/// stand-in for.
+#[derive(Debug, Clone, Copy)]
+struct PendingLayer {
+    generation: u64,
+    dirty_nodes: usize,
+}
+
+/// Identifies a savepoint within the active transaction. Opaque and only
+/// meaningful to the `SingleFileDB` that issued it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SavepointId(u64);
+
+/// A marker recorded by `SingleFileDB::savepoint`: the position in
+/// [`DeltaState`]'s own mutation journal (see `delta::SavepointId`) plus a
+/// copy of the id allocators, as they stood at the moment the savepoint was
+/// taken. `rollback_to` replays the journal back to that position instead of
+/// restoring a cloned `DeltaState` wholesale -- O(changes-since) instead of
+/// O(whole-delta), and the id allocators (which the journal doesn't track)
+/// are still restored from `ids` as before.
 #[derive(Debug)]
+struct Savepoint {
+    id: SavepointId,
+    delta_mark: crate::core::delta::SavepointId,
+    ids: IdAllocatorSnapshot,
+}
+
+/// Snapshot of the node/label/etype/propkey id allocators at a point in a
+/// transaction, kept beside a delta journal mark in [`Savepoint`] and beside
+/// a delta clone in [`TxState`] so
+/// a rollback can reclaim ids the transaction allocated but never committed.
+/// Schema name maps (`label_names`/`etype_ids`/etc.) are deliberately not
+/// captured here: `get_or_create_label` and friends are shared, idempotent
+/// namespaces that aren't gated by `current_tx` at all today, so a name
+/// registered mid-transaction stays registered even if the transaction rolls
+/// back -- only the numeric allocators it consumed are reclaimed.
+#[derive(Debug, Clone, Copy)]
+struct IdAllocatorSnapshot {
+    next_node_id: u64,
+    next_label_id: u32,
+    next_etype_id: u32,
+    next_propkey_id: u32,
+}
+
+/// Scratch buffers for the semi-naive epoch expansion behind
+/// [`SingleFileDB::reachable`] and [`SingleFileDB::shortest_path`]: `result`
+/// is the reached-so-far set, `frontier` the nodes discovered last epoch,
+/// and `next_frontier` the ones being discovered this epoch, swapped into
+/// `frontier` once the epoch finishes. Kept on `SingleFileDB` and cleared in
+/// place at the start of each call so repeated queries don't reallocate.
+#[derive(Debug, Default)]
+struct TraversalScratch {
+    result: std::collections::HashSet<NodeId>,
+    frontier: Vec<NodeId>,
+    next_frontier: Vec<NodeId>,
+}
+
+/// How many decoded node property maps [`NodePropCache`] keeps resident.
+const NODE_PROP_CACHE_CAPACITY: usize = 4096;
+
+/// Bounded LRU cache of node property maps decoded from snapshot pages,
+/// keyed by physical node id, backing [`SingleFileDB::get_node_props`]. A hot
+/// node's page is decoded once and reused on every later read instead of
+/// calling back into `SnapshotData::get_node_props` (which re-walks the raw
+/// page bytes) every time; a cold one that's only ever read once never
+/// displaces more than a single slot.
+#[derive(Debug, Default)]
+struct NodePropCache {
+    entries: HashMap<u32, HashMap<PropKeyId, PropValue>>,
+    /// Recency order, least-recently-used at the front. Kept in sync with
+    /// `entries` by every access going through `get_or_decode`.
+    order: VecDeque<u32>,
+}
+
+impl NodePropCache {
+    /// Return the decoded prop map for physical node `phys`, serving it from
+    /// cache when present and marking it most-recently-used either way.
+    /// Falls back to `decode` (a snapshot page decode) on a miss, evicting
+    /// the least-recently-used entry first if the cache is full.
+    fn get_or_decode(
+        &mut self,
+        phys: u32,
+        decode: impl FnOnce() -> Option<HashMap<PropKeyId, PropValue>>,
+    ) -> Option<HashMap<PropKeyId, PropValue>> {
+        if let Some(props) = self.entries.get(&phys) {
+            let props = props.clone();
+            self.touch(phys);
+            return Some(props);
+        }
+
+        let props = decode()?;
+        if self.entries.len() >= NODE_PROP_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(phys, props.clone());
+        self.order.push_back(phys);
+        Some(props)
+    }
+
+    fn touch(&mut self, phys: u32) {
+        if let Some(pos) = self.order.iter().position(|&p| p == phys) {
+            self.order.remove(pos);
+            self.order.push_back(phys);
+        }
+    }
+
+    /// Drop every cached entry. Called whenever the mapped snapshot is
+    /// replaced (checkpoint, reopen), since physical node ids are only
+    /// meaningful relative to the snapshot generation that produced them.
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// Fingerprint of the file backing the mapped snapshot (length + mtime),
+/// taken right after `open`/`checkpoint` map it. Re-checked by
+/// [`SingleFileDB::verify_snapshot_identity`] before a caller trusts
+/// mmap-derived offsets or [`NodePropCache`] against a file that may have
+/// been replaced or truncated out from under an already-open handle.
+///
+/// This only guards a single open `SingleFileDB`'s in-process caches.
+/// Persisting the fingerprint into the on-disk header so a *second* process
+/// opening the same file could make the same check before trusting its own
+/// mmap would need a field on `DbHeaderV1`, but that type lives in
+/// `core::pager`/`constants`, neither present in this checkout -- left as a
+/// follow-up once those modules are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SnapshotIdentity {
+    file_len: u64,
+    mtime_nanos: u64,
+}
+
+impl SnapshotIdentity {
+    fn current(pager: &FilePager) -> Result<Self> {
+        let meta = pager.file().metadata()?;
+        let mtime_nanos = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Ok(Self {
+            file_len: meta.len(),
+            mtime_nanos,
+        })
+    }
+}
+
+/// A single node or edge yielded by [`graph_entities_iter`].
+enum GraphEntity {
+    Node(NodeData),
+    Edge(EdgeData),
+}
+
+/// Apply an edge's delta property patches (if any) on top of its
+/// snapshot-decoded props, using the same `Some` overwrites / `None`
+/// tombstones rule as node props. Shared by `SingleFileDB::get_edge_props`,
+/// `collect_graph_data`, and `graph_entities_iter` so the merge logic lives
+/// in exactly one place.
+fn merge_edge_props(
+    mut props: HashMap<PropKeyId, PropValue>,
+    delta: &DeltaState,
+    src: NodeId,
+    etype: ETypeId,
+    dst: NodeId,
+) -> HashMap<PropKeyId, PropValue> {
+    if let Some(patches) = delta.get_edge_prop_patches(src, etype, dst) {
+        for (&key_id, value) in patches {
+            match value {
+                Some(v) => { props.insert(key_id, v.clone()); }
+                None => { props.remove(&key_id); }
+            }
+        }
+    }
+    props
+}
+
+/// Lazily merge the immutable snapshot with the delta overlay (honoring
+/// deletions) into a single stream of nodes and edges, in the same order
+/// `collect_graph_data` used to build them into `Vec`s: snapshot nodes (and
+/// their out-edges) first, then delta-created nodes, then delta-added edges.
+/// Unlike `collect_graph_data`, nothing here is collected into a
+/// database-wide `Vec` -- the only per-step allocation is the handful of
+/// edges attached to whichever node is currently being yielded -- so a
+/// caller like `stream_snapshot_to_pager` can consume it with memory
+/// proportional to one node at a time instead of the whole graph.
+fn graph_entities_iter<'a>(
+    snapshot: &'a Option<SnapshotData>,
+    delta: &'a DeltaState,
+) -> impl Iterator<Item = GraphEntity> + 'a {
+    let from_snapshot = snapshot.iter().flat_map(move |snapshot| {
+        let num_nodes = snapshot.header.num_nodes as usize;
+        (0..num_nodes).flat_map(move |phys| {
+            let mut entities = Vec::new();
+
+            let node_id = match snapshot.get_node_id(phys as u32) {
+                Some(id) => id,
+                None => return entities.into_iter(),
+            };
+            if delta.is_node_deleted(node_id) {
+                return entities.into_iter();
+            }
+
+            let key = snapshot.get_node_key(phys as u32);
+            let mut props = HashMap::new();
+            if let Some(snapshot_props) = snapshot.get_node_props(phys as u32) {
+                for (key_id, value) in snapshot_props {
+                    props.insert(key_id, value);
+                }
+            }
+            if let Some(node_delta) = delta.get_node_delta(node_id) {
+                if let Some(ref delta_props) = node_delta.props {
+                    for (&key_id, value) in delta_props {
+                        match value {
+                            Some(v) => { props.insert(key_id, v.clone()); }
+                            None => { props.remove(&key_id); }
+                        }
+                    }
+                }
+            }
+
+            entities.push(GraphEntity::Node(NodeData {
+                node_id,
+                key,
+                labels: Vec::new(),
+                props,
+            }));
+
+            for edge_info in snapshot.get_out_edges(phys as u32) {
+                let dst_node_id = match snapshot.get_node_id(edge_info.dst) {
+                    Some(id) => id,
+                    None => continue,
+                };
+                if delta.is_node_deleted(dst_node_id) {
+                    continue;
+                }
+                if delta.is_edge_deleted(node_id, edge_info.etype, dst_node_id) {
+                    continue;
+                }
+                let edge_props = snapshot
+                    .get_edge_props(phys as u32, edge_info.etype, edge_info.dst)
+                    .unwrap_or_default();
+                entities.push(GraphEntity::Edge(EdgeData {
+                    src: node_id,
+                    etype: edge_info.etype,
+                    dst: dst_node_id,
+                    props: merge_edge_props(edge_props, delta, node_id, edge_info.etype, dst_node_id),
+                }));
+            }
+
+            entities.into_iter()
+        })
+    });
+
+    let from_created_nodes = delta.created_nodes.iter().map(move |(&node_id, node_delta)| {
+        let mut props = HashMap::new();
+        if let Some(ref delta_props) = node_delta.props {
+            for (&key_id, value) in delta_props {
+                if let Some(v) = value {
+                    props.insert(key_id, v.clone());
+                }
+            }
+        }
+        GraphEntity::Node(NodeData {
+            node_id,
+            key: node_delta.key.clone(),
+            labels: Vec::new(),
+            props,
+        })
+    });
+
+    let from_delta_edges = delta.out_add.iter().flat_map(move |(&src, patches)| {
+        if delta.is_node_deleted(src) {
+            return Vec::new().into_iter();
+        }
+        patches
+            .iter()
+            .filter(move |patch| !delta.is_node_deleted(patch.other))
+            .map(move |patch| {
+                GraphEntity::Edge(EdgeData {
+                    src,
+                    etype: patch.etype,
+                    dst: patch.other,
+                    props: merge_edge_props(HashMap::new(), delta, src, patch.etype, patch.other),
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    });
+
+    from_snapshot.chain(from_created_nodes).chain(from_delta_edges)
+}
+
+/// Walk `predecessors` back from `to` to `from` and reverse it into a
+/// forward path, for [`SingleFileDB::shortest_path`]. Only called once `to`
+/// is known to be in `predecessors` (or equal to `from`), so every lookup
+/// along the way is expected to succeed.
+fn reconstruct_traversal_path(
+    predecessors: &HashMap<NodeId, NodeId>,
+    from: NodeId,
+    to: NodeId,
+) -> Vec<NodeId> {
+    let mut path = vec![to];
+    let mut current = to;
+    while current != from {
+        current = predecessors[&current];
+        path.push(current);
+    }
+    path.reverse();
+    path
+}
+
+/// A single delta-level mutation applied by a write transaction, captured in
+/// the order it happened so [`SingleFileDB::unrecord`] can walk a commit's
+/// ops in reverse and apply each one's inverse.
+#[derive(Debug, Clone)]
+enum HistoryOp {
+    CreateNode(NodeId),
+    /// Inverse is un-tombstoning `node_id`; the underlying snapshot/delta
+    /// row it pointed at was never touched by a plain delete.
+    DeleteNode(NodeId),
+    AddEdge(NodeId, ETypeId, NodeId),
+    DeleteEdge(NodeId, ETypeId, NodeId),
+    /// The value `key_id` held on `node_id` immediately before this write,
+    /// or `None` if it was unset -- either way, the state to restore.
+    SetNodeProp(NodeId, PropKeyId, Option<PropValue>),
+    /// The value `key_id` held on edge `(src, etype, dst)` immediately
+    /// before this write, or `None` if it was unset.
+    SetEdgeProp(NodeId, ETypeId, NodeId, PropKeyId, Option<PropValue>),
+}
+
+/// One committed transaction's recorded delta, kept in
+/// [`SingleFileDB::history`] until it ages out or is reverted.
+#[derive(Debug, Clone)]
+struct CommitHistoryEntry {
+    txid: TxId,
+    ops: Vec<HistoryOp>,
+    /// Every node this transaction created, deleted, or otherwise wrote to
+    /// (same contents as its `TxState::write_set`), used by `unrecord`'s
+    /// dependency check.
+    touched: std::collections::HashSet<NodeId>,
+}
+
+/// Transaction state
 pub struct TxState {
     pub txid: TxId,
     pub read_only: bool,
     pub snapshot_ts: u64,
+    /// Durability level this transaction commits with.
+    pub durability: Durability,
+    /// Commit-version counter value at `begin()`, i.e. the version the first
+    /// commit after `begin()` is stamped with. A write-write conflict is any
+    /// node in `write_set` whose `committed_versions` entry is at least this.
+    snapshot_version: u64,
+    /// Node IDs created, deleted, or otherwise mutated by this transaction
+    /// (including both endpoints of any edge it added or removed), checked
+    /// for conflicts and stamped with a new commit version in `commit()`.
+    write_set: std::collections::HashSet<NodeId>,
+    /// Ops applied so far, in order, so `commit` can record this
+    /// transaction's inverse delta into [`SingleFileDB::history`].
+    ops: Vec<HistoryOp>,
+    /// Stack of nested savepoints taken within this transaction, outermost
+    /// first. A savepoint becomes invalid (and is removed) once it or an
+    /// outer savepoint it nests under is rolled back to or released.
+    savepoints: Vec<Savepoint>,
+    next_savepoint_id: u64,
+    /// Delta as it stood when this transaction began, restored wholesale by
+    /// `rollback`.
+    start_delta: DeltaState,
+    /// Id allocators as they stood when this transaction began, restored
+    /// alongside `start_delta` by `rollback`.
+    start_ids: IdAllocatorSnapshot,
+    /// Callbacks queued via [`SingleFileDB::on_commit`], run in registration
+    /// order once this transaction's commit is durable -- dropped unrun if
+    /// the transaction rolls back instead.
+    on_commit: Vec<Box<dyn FnOnce() + Send>>,
+    /// Vector sets/deletes queued by this transaction, applied to
+    /// [`SingleFileDB::vector_stores`] by `commit` (`Some` is a set, `None` a
+    /// delete). Unlike node/edge props these aren't staged through `delta`
+    /// because a vector store is an IVF manifest, not a patchable map --
+    /// simplest to just buffer the raw ops here and replay them once, after
+    /// the transaction's COMMIT record is durable. A rolled-back transaction
+    /// just drops this map unapplied; `vector_stores` is never touched.
+    pub(crate) pending_vectors: HashMap<(NodeId, PropKeyId), Option<VectorRef>>,
+}
+
+impl std::fmt::Debug for TxState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TxState")
+            .field("txid", &self.txid)
+            .field("read_only", &self.read_only)
+            .field("snapshot_ts", &self.snapshot_ts)
+            .field("durability", &self.durability)
+            .field("snapshot_version", &self.snapshot_version)
+            .field("write_set", &self.write_set)
+            .field("ops", &self.ops)
+            .field("savepoints", &self.savepoints)
+            .field("next_savepoint_id", &self.next_savepoint_id)
+            .field("on_commit", &format_args!("[{} callback(s)]", self.on_commit.len()))
+            .field("pending_vectors", &self.pending_vectors.len())
+            .finish()
+    }
 }
 
 impl TxState {
-    pub fn new(txid: TxId, read_only: bool, snapshot_ts: u64) -> Self {
+    pub fn new(
+        txid: TxId,
+        read_only: bool,
+        snapshot_ts: u64,
+        durability: Durability,
+        snapshot_version: u64,
+        start_delta: DeltaState,
+        start_ids: IdAllocatorSnapshot,
+    ) -> Self {
         Self {
             txid,
             read_only,
             snapshot_ts,
+            durability,
+            snapshot_version,
+            write_set: std::collections::HashSet::new(),
+            ops: Vec::new(),
+            savepoints: Vec::new(),
+            next_savepoint_id: 1,
+            start_delta,
+            start_ids,
+            on_commit: Vec::new(),
+            pending_vectors: HashMap::new(),
         }
     }
 }
@@ -112,10 +669,709 @@ pub enum CheckpointStatus {
     Completing,
 }
 
+/// Finer-grained phase within a running background checkpoint than
+/// [`CheckpointStatus`] tracks, surfaced via [`CheckpointProgress::phase`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointPhase {
+    /// No checkpoint running.
+    Idle,
+    /// Copying the schema name maps; nodes and edges are merged from the
+    /// snapshot and delta while `WritingSnapshot` streams them out.
+    Collecting,
+    /// Serializing and writing the new snapshot to disk.
+    WritingSnapshot,
+    /// Merging the secondary WAL region back into the primary.
+    MergingWal,
+    /// Brief final header update before returning to `Idle`.
+    Completing,
+}
+
+/// Live progress counters for a [`SingleFileDB::background_checkpoint`] run,
+/// handed out via `Arc` from [`SingleFileDB::checkpoint_progress`] so a
+/// caller can poll it from another thread, plus an abort flag a caller can
+/// set via [`SingleFileDB::request_checkpoint_abort`].
+#[derive(Debug)]
+pub struct CheckpointProgress {
+    phase: Mutex<CheckpointPhase>,
+    nodes_written: AtomicU64,
+    edges_written: AtomicU64,
+    total_nodes: AtomicU64,
+    total_edges: AtomicU64,
+    abort_requested: std::sync::atomic::AtomicBool,
+}
+
+impl CheckpointProgress {
+    fn new() -> Self {
+        Self {
+            phase: Mutex::new(CheckpointPhase::Idle),
+            nodes_written: AtomicU64::new(0),
+            edges_written: AtomicU64::new(0),
+            total_nodes: AtomicU64::new(0),
+            total_edges: AtomicU64::new(0),
+            abort_requested: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    fn reset(&self) {
+        *self.phase.lock() = CheckpointPhase::Idle;
+        self.nodes_written.store(0, Ordering::SeqCst);
+        self.edges_written.store(0, Ordering::SeqCst);
+        self.total_nodes.store(0, Ordering::SeqCst);
+        self.total_edges.store(0, Ordering::SeqCst);
+        self.abort_requested.store(false, Ordering::SeqCst);
+    }
+
+    fn set_phase(&self, phase: CheckpointPhase) {
+        *self.phase.lock() = phase;
+    }
+
+    pub fn phase(&self) -> CheckpointPhase {
+        *self.phase.lock()
+    }
+
+    pub fn nodes_written(&self) -> u64 {
+        self.nodes_written.load(Ordering::SeqCst)
+    }
+
+    pub fn edges_written(&self) -> u64 {
+        self.edges_written.load(Ordering::SeqCst)
+    }
+
+    pub fn total_nodes(&self) -> u64 {
+        self.total_nodes.load(Ordering::SeqCst)
+    }
+
+    pub fn total_edges(&self) -> u64 {
+        self.total_edges.load(Ordering::SeqCst)
+    }
+
+    pub fn abort_requested(&self) -> bool {
+        self.abort_requested.load(Ordering::SeqCst)
+    }
+}
+
+/// How long a group-commit leader waits for late arrivals before running
+/// its `wal.flush` + `pager.sync`. Short enough that a lone committer barely
+/// notices, long enough to let a burst of near-simultaneous commits land in
+/// the same round.
+const GROUP_COMMIT_COALESCE_WINDOW: std::time::Duration = std::time::Duration::from_micros(200);
+
+/// Coordinates `Durability::Immediate` fsyncs across committers so several
+/// arriving close together share one `wal.flush` + `pager.sync` instead of
+/// each paying for its own. The first thread to reach an idle round becomes
+/// leader: it waits out [`GROUP_COMMIT_COALESCE_WINDOW`], runs the real
+/// sync, then wakes every follower that joined behind it with the same
+/// result -- a sync failure is reported to the whole batch, not just the
+/// leader, so no follower ever reports durability the leader didn't
+/// actually achieve.
+///
+/// `commit()` releases the `current_tx` slot (via `take()`) before it ever
+/// reaches this coordinator, so a new writer's `begin()` + `commit()` can
+/// already be racing in right behind this one by the time `join` runs --
+/// there really can be several threads here at once, not just one. That
+/// only pays off because `join` itself never holds `pager`/`wal_buffer`
+/// while waiting: only the thread that ends up leading a round acquires
+/// them, inside the `sync` closure, so followers pile up behind its
+/// coalesce window on the condvar instead of blocking on the same mutexes
+/// the leader needs.
+struct GroupCommitCoordinator {
+    state: Mutex<GroupCommitState>,
+    cond: parking_lot::Condvar,
+}
+
+struct GroupCommitState {
+    /// Bumped once per completed round.
+    round: u64,
+    /// `true` while some thread is running the sync for the current round.
+    leader_active: bool,
+    /// `true` once the current round's leader has called `sync`; a caller
+    /// joining after that waits for the next round instead.
+    sync_started: bool,
+    /// Result of the most recently completed round, stringified since
+    /// `RayError` isn't `Clone` -- every follower woken for that round maps
+    /// it back through `RayError::Internal`. `None` until a round has run.
+    last_result: Option<std::result::Result<(), String>>,
+}
+
+impl GroupCommitCoordinator {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(GroupCommitState {
+                round: 0,
+                leader_active: false,
+                sync_started: false,
+                last_result: None,
+            }),
+            cond: parking_lot::Condvar::new(),
+        }
+    }
+
+    /// Run `sync` as part of a group commit round coalesced over
+    /// `coalesce_window`. If another thread is already leading a round whose
+    /// sync has not started yet, waits for it to finish and returns its
+    /// result instead of running `sync` at all. A round whose sync already
+    /// started is waited out first, since it may have flushed before this
+    /// caller's record was written.
+    fn join(
+        &self,
+        coalesce_window: std::time::Duration,
+        sync: impl FnOnce() -> Result<()>,
+    ) -> Result<()> {
+        let mut state = self.state.lock();
+        while state.leader_active && state.sync_started {
+            let in_flight = state.round;
+            while state.round == in_flight {
+                self.cond.wait(&mut state);
+            }
+        }
+        let joined_at_round = state.round;
+
+        if state.leader_active {
+            while state.round == joined_at_round {
+                self.cond.wait(&mut state);
+            }
+            return state
+                .last_result
+                .clone()
+                .expect("round advanced past joined_at_round without recording a result")
+                .map_err(RayError::Internal);
+        }
+
+        state.leader_active = true;
+        drop(state);
+
+        if !coalesce_window.is_zero() {
+            std::thread::sleep(coalesce_window);
+        }
+        self.state.lock().sync_started = true;
+        let result = sync();
+
+        let mut state = self.state.lock();
+        state.round += 1;
+        state.leader_active = false;
+        state.sync_started = false;
+        state.last_result = Some(result.as_ref().map(|_| ()).map_err(|e| e.to_string()));
+        self.cond.notify_all();
+        result
+    }
+}
+
+/// Outcome of a WAL-only recovery scan, produced either by
+/// [`repair_single_file`] or by `open_single_file` when the snapshot region
+/// fails to parse. Lets a caller judge whether to accept a partial recovery
+/// (and then [`SingleFileDB::compact`] into a clean file) instead of silently
+/// opening a database that quietly lost its snapshot.
+#[derive(Debug, Clone, Default)]
+pub struct RepairReport {
+    /// Whether the snapshot region parsed successfully. If `false`, every
+    /// node/edge/property that was only in the snapshot (not re-applied by a
+    /// later WAL transaction) is gone -- only `transactions_recovered` exists.
+    pub snapshot_ok: bool,
+    /// Number of committed transactions successfully replayed from the WAL.
+    pub transactions_recovered: usize,
+    /// Number of individual WAL records (across all replayed transactions)
+    /// that were read and validated.
+    pub records_recovered: usize,
+    /// Offset of the first CRC/framing failure in the WAL, if the scan
+    /// stopped before reaching the header's claimed `wal_head`.
+    pub first_bad_offset: Option<u64>,
+    /// How many WAL bytes between `first_bad_offset` and the header's
+    /// claimed `wal_head` were discarded as unreadable.
+    pub bytes_dropped: u64,
+}
+
+/// Size/timing metrics from the most recent snapshot build, surfaced via
+/// [`SingleFileDB::compression_stats`] so a caller can judge whether the
+/// configured [`SnapshotCompression`] codec/level is paying for itself.
+/// Recorded by `checkpoint`, `background_checkpoint`, and `compact`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompressionStats {
+    /// Size of the serialized snapshot before compression.
+    pub uncompressed_bytes: u64,
+    /// Size actually written to disk (equal to `uncompressed_bytes` when no
+    /// codec is configured).
+    pub compressed_bytes: u64,
+    /// Wall-clock time spent building and compressing the snapshot.
+    pub elapsed_ms: u64,
+}
+
+impl CompressionStats {
+    /// Compressed size as a fraction of uncompressed (1.0 = no savings).
+    pub fn ratio(&self) -> f64 {
+        if self.uncompressed_bytes == 0 {
+            return 1.0;
+        }
+        self.compressed_bytes as f64 / self.uncompressed_bytes as f64
+    }
+}
+
+// ============================================================================
+// Metrics
+// ============================================================================
+
+/// Gauges that describe current state rather than accumulate over time, so
+/// they're sampled fresh by [`SingleFileDB::metrics`] -- the only place that
+/// already holds (or can cheaply take) the delta/header/WAL-buffer locks --
+/// and handed to [`DbMetrics::snapshot`] rather than tracked as counters.
+struct DbMetricsGauges {
+    delta_usage: DeltaUsage,
+    delta_bytes_estimate: u64,
+    checkpoint_phase: CheckpointPhase,
+    snapshot_resident_pages: u64,
+    wal_used_bytes: u64,
+    wal_capacity_bytes: u64,
+    pending_layers: usize,
+}
+
+/// Lock-free counters for the WAL, checkpoint, and transaction subsystems,
+/// bumped at the existing instrumentation points (`begin`/`commit`/
+/// `rollback`, `write_wal`, `checkpoint`/`complete_background_checkpoint`)
+/// instead of sitting behind `wal_stats()` alone. Gated behind the `metrics`
+/// feature: with it off, every field and method below compiles away to
+/// nothing, so a build that doesn't ask for metrics doesn't pay even the
+/// atomic-increment cost on the hot write path.
+#[derive(Debug)]
+struct DbMetrics {
+    #[cfg(feature = "metrics")]
+    tx_begun: AtomicU64,
+    #[cfg(feature = "metrics")]
+    tx_committed: AtomicU64,
+    #[cfg(feature = "metrics")]
+    tx_rolled_back: AtomicU64,
+    #[cfg(feature = "metrics")]
+    wal_bytes_written: AtomicU64,
+    #[cfg(feature = "metrics")]
+    wal_flush_count: AtomicU64,
+    #[cfg(feature = "metrics")]
+    checkpoint_count: AtomicU64,
+    #[cfg(feature = "metrics")]
+    checkpoint_total_ms: AtomicU64,
+    #[cfg(feature = "metrics")]
+    last_checkpoint_at_ms: AtomicU64,
+    #[cfg(feature = "metrics")]
+    snapshot_uncompressed_bytes: AtomicU64,
+    #[cfg(feature = "metrics")]
+    snapshot_compressed_bytes: AtomicU64,
+    #[cfg(feature = "metrics")]
+    wal_bytes_since_checkpoint: AtomicU64,
+    #[cfg(feature = "metrics")]
+    reads_from_delta: AtomicU64,
+    #[cfg(feature = "metrics")]
+    reads_from_snapshot: AtomicU64,
+}
+
+impl DbMetrics {
+    fn new() -> Self {
+        #[cfg(feature = "metrics")]
+        {
+            Self {
+                tx_begun: AtomicU64::new(0),
+                tx_committed: AtomicU64::new(0),
+                tx_rolled_back: AtomicU64::new(0),
+                wal_bytes_written: AtomicU64::new(0),
+                wal_flush_count: AtomicU64::new(0),
+                checkpoint_count: AtomicU64::new(0),
+                checkpoint_total_ms: AtomicU64::new(0),
+                last_checkpoint_at_ms: AtomicU64::new(0),
+                snapshot_uncompressed_bytes: AtomicU64::new(0),
+                snapshot_compressed_bytes: AtomicU64::new(0),
+                wal_bytes_since_checkpoint: AtomicU64::new(0),
+                reads_from_delta: AtomicU64::new(0),
+                reads_from_snapshot: AtomicU64::new(0),
+            }
+        }
+        #[cfg(not(feature = "metrics"))]
+        {
+            Self {}
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record_tx_begin(&self) {
+        self.tx_begun.fetch_add(1, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_tx_begin(&self) {}
+
+    #[cfg(feature = "metrics")]
+    fn record_tx_commit(&self) {
+        self.tx_committed.fetch_add(1, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_tx_commit(&self) {}
+
+    #[cfg(feature = "metrics")]
+    fn record_tx_rollback(&self) {
+        self.tx_rolled_back.fetch_add(1, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_tx_rollback(&self) {}
+
+    #[cfg(feature = "metrics")]
+    fn record_wal_write(&self, bytes: usize) {
+        self.wal_bytes_written.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.wal_bytes_since_checkpoint.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_wal_write(&self, _bytes: usize) {}
+
+    #[cfg(feature = "metrics")]
+    fn record_read_from_delta(&self) {
+        self.reads_from_delta.fetch_add(1, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_read_from_delta(&self) {}
+
+    #[cfg(feature = "metrics")]
+    fn record_read_from_snapshot(&self) {
+        self.reads_from_snapshot.fetch_add(1, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_read_from_snapshot(&self) {}
+
+    #[cfg(feature = "metrics")]
+    fn record_wal_flush(&self) {
+        self.wal_flush_count.fetch_add(1, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_wal_flush(&self) {}
+
+    #[cfg(feature = "metrics")]
+    fn record_checkpoint(&self, duration_ms: u64, uncompressed_bytes: u64, compressed_bytes: u64) {
+        self.checkpoint_count.fetch_add(1, Ordering::Relaxed);
+        self.checkpoint_total_ms.fetch_add(duration_ms, Ordering::Relaxed);
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.last_checkpoint_at_ms.store(now_ms, Ordering::Relaxed);
+        self.snapshot_uncompressed_bytes.store(uncompressed_bytes, Ordering::Relaxed);
+        self.snapshot_compressed_bytes.store(compressed_bytes, Ordering::Relaxed);
+        self.wal_bytes_since_checkpoint.store(0, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_checkpoint(&self, _duration_ms: u64, _uncompressed_bytes: u64, _compressed_bytes: u64) {}
+
+    /// Snapshot the accumulated counters plus the gauges (delta composition,
+    /// checkpoint phase, resident snapshot/WAL sizes...) that aren't counters
+    /// at all and so are sampled fresh by the caller -- the only place with
+    /// access to the delta, header, and WAL buffer locks -- rather than
+    /// tracked here.
+    fn snapshot(&self, gauges: DbMetricsGauges) -> DbMetricsSnapshot {
+        let delta_entries = gauges.delta_usage.nodes_created
+            + gauges.delta_usage.nodes_deleted
+            + gauges.delta_usage.nodes_modified
+            + gauges.delta_usage.edges_added
+            + gauges.delta_usage.edges_deleted
+            + gauges.delta_usage.schema_defs;
+        #[cfg(feature = "metrics")]
+        {
+            DbMetricsSnapshot {
+                tx_begun: self.tx_begun.load(Ordering::Relaxed),
+                tx_committed: self.tx_committed.load(Ordering::Relaxed),
+                tx_rolled_back: self.tx_rolled_back.load(Ordering::Relaxed),
+                wal_bytes_written: self.wal_bytes_written.load(Ordering::Relaxed),
+                wal_bytes_since_checkpoint: self.wal_bytes_since_checkpoint.load(Ordering::Relaxed),
+                wal_flush_count: self.wal_flush_count.load(Ordering::Relaxed),
+                checkpoint_count: self.checkpoint_count.load(Ordering::Relaxed),
+                checkpoint_total_ms: self.checkpoint_total_ms.load(Ordering::Relaxed),
+                last_checkpoint_at_ms: self.last_checkpoint_at_ms.load(Ordering::Relaxed),
+                snapshot_uncompressed_bytes: self.snapshot_uncompressed_bytes.load(Ordering::Relaxed),
+                snapshot_compressed_bytes: self.snapshot_compressed_bytes.load(Ordering::Relaxed),
+                reads_from_delta: self.reads_from_delta.load(Ordering::Relaxed),
+                reads_from_snapshot: self.reads_from_snapshot.load(Ordering::Relaxed),
+                delta_entries,
+                delta_usage: gauges.delta_usage,
+                delta_bytes_estimate: gauges.delta_bytes_estimate,
+                checkpoint_phase: gauges.checkpoint_phase,
+                snapshot_resident_pages: gauges.snapshot_resident_pages,
+                wal_used_bytes: gauges.wal_used_bytes,
+                wal_capacity_bytes: gauges.wal_capacity_bytes,
+                pending_layers: gauges.pending_layers,
+            }
+        }
+        #[cfg(not(feature = "metrics"))]
+        {
+            DbMetricsSnapshot {
+                delta_entries,
+                delta_usage: gauges.delta_usage,
+                delta_bytes_estimate: gauges.delta_bytes_estimate,
+                checkpoint_phase: gauges.checkpoint_phase,
+                snapshot_resident_pages: gauges.snapshot_resident_pages,
+                wal_used_bytes: gauges.wal_used_bytes,
+                wal_capacity_bytes: gauges.wal_capacity_bytes,
+                pending_layers: gauges.pending_layers,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// Point-in-time read of [`DbMetrics`], returned by
+/// [`SingleFileDB::metrics`]. Every counter is `0` when the `metrics`
+/// feature is disabled -- `delta_entries` and `checkpoint_phase` are still
+/// accurate either way since they're sampled directly rather than counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbMetricsSnapshot {
+    /// Transactions started via `begin`/`begin_with_durability`.
+    pub tx_begun: u64,
+    /// Transactions that reached `commit()` successfully.
+    pub tx_committed: u64,
+    /// Transactions that were rolled back, explicitly or via `rollback()`.
+    pub tx_rolled_back: u64,
+    /// Total bytes of WAL record payload written via `write_wal`.
+    pub wal_bytes_written: u64,
+    /// Bytes of WAL record payload written since the last completed
+    /// checkpoint -- resets to `0` on every `checkpoint`/
+    /// `background_checkpoint`, unlike the cumulative `wal_bytes_written`.
+    pub wal_bytes_since_checkpoint: u64,
+    /// Number of times the WAL buffer was flushed to the pager.
+    pub wal_flush_count: u64,
+    /// Number of completed checkpoints (blocking or background).
+    pub checkpoint_count: u64,
+    /// Sum of wall-clock milliseconds spent across all completed checkpoints.
+    pub checkpoint_total_ms: u64,
+    /// Unix epoch milliseconds of the most recent completed checkpoint, `0`
+    /// if none has run yet.
+    pub last_checkpoint_at_ms: u64,
+    /// Serialized snapshot size before compression, from the most recent
+    /// checkpoint.
+    pub snapshot_uncompressed_bytes: u64,
+    /// Snapshot size actually written to disk, from the most recent
+    /// checkpoint.
+    pub snapshot_compressed_bytes: u64,
+    /// Calls to [`SingleFileDB::get_node_prop`] answered from the delta
+    /// overlay (a pending modification or newly created node) rather than
+    /// falling through to the snapshot.
+    pub reads_from_delta: u64,
+    /// Calls to [`SingleFileDB::get_node_prop`] answered from the mapped
+    /// snapshot.
+    pub reads_from_snapshot: u64,
+    /// Current number of entries buffered in the uncommitted delta overlay
+    /// (created/deleted/modified nodes, added/deleted edges, new schema
+    /// definitions), sampled at the time `metrics()` was called rather than
+    /// tracked incrementally.
+    pub delta_entries: usize,
+    /// Per-category breakdown of the delta. Every category except
+    /// `edge_props_patched` is counted in `delta_entries`. See [`DeltaUsage`].
+    pub delta_usage: DeltaUsage,
+    /// Rough estimate of the heap bytes backing the current delta overlay.
+    /// See [`DeltaState::estimated_bytes`](crate::core::delta::DeltaState::estimated_bytes)
+    /// for what this does and doesn't account for.
+    pub delta_bytes_estimate: u64,
+    /// Pages occupied by the currently mapped snapshot.
+    pub snapshot_resident_pages: u64,
+    /// Bytes currently used in the WAL buffer, from `wal_stats()`.
+    pub wal_used_bytes: u64,
+    /// Total capacity of the WAL buffer, from `wal_stats()`. Compare against
+    /// `wal_used_bytes` for WAL utilization.
+    pub wal_capacity_bytes: u64,
+    /// Number of [`PendingLayer`] markers currently batched by
+    /// [`SingleFileDB::checkpoint_incremental`], waiting to be folded into a
+    /// real checkpoint.
+    pub pending_layers: usize,
+    /// Phase of the checkpoint currently (or most recently) running.
+    pub checkpoint_phase: CheckpointPhase,
+}
+
+impl Default for DbMetricsSnapshot {
+    fn default() -> Self {
+        Self {
+            tx_begun: 0,
+            tx_committed: 0,
+            tx_rolled_back: 0,
+            wal_bytes_written: 0,
+            wal_bytes_since_checkpoint: 0,
+            wal_flush_count: 0,
+            checkpoint_count: 0,
+            checkpoint_total_ms: 0,
+            last_checkpoint_at_ms: 0,
+            snapshot_uncompressed_bytes: 0,
+            snapshot_compressed_bytes: 0,
+            reads_from_delta: 0,
+            reads_from_snapshot: 0,
+            delta_entries: 0,
+            delta_usage: DeltaUsage::default(),
+            delta_bytes_estimate: 0,
+            snapshot_resident_pages: 0,
+            wal_used_bytes: 0,
+            wal_capacity_bytes: 0,
+            pending_layers: 0,
+            checkpoint_phase: CheckpointPhase::Idle,
+        }
+    }
+}
+
+/// Opt-in per-operation timing, modeled on RocksDB's `PerfContext`: where
+/// [`DbMetrics`] tracks always-on counters at specific instrumentation
+/// points, this tracks total wall-clock time and a representative byte count
+/// for exactly three operations -- `commit`, `checkpoint`, and
+/// `background_checkpoint` -- read back via [`SingleFileDB::perf_context`].
+/// Gated behind the same `metrics` feature as `DbMetrics`, for the same
+/// reason: a build that doesn't ask for profiling shouldn't pay for an
+/// `Instant::now()` on every commit.
+#[derive(Debug)]
+struct PerfContext {
+    #[cfg(feature = "metrics")]
+    commit_count: AtomicU64,
+    #[cfg(feature = "metrics")]
+    commit_total_ns: AtomicU64,
+    #[cfg(feature = "metrics")]
+    commit_bytes: AtomicU64,
+    #[cfg(feature = "metrics")]
+    checkpoint_count: AtomicU64,
+    #[cfg(feature = "metrics")]
+    checkpoint_total_ns: AtomicU64,
+    #[cfg(feature = "metrics")]
+    checkpoint_bytes: AtomicU64,
+    #[cfg(feature = "metrics")]
+    background_checkpoint_count: AtomicU64,
+    #[cfg(feature = "metrics")]
+    background_checkpoint_total_ns: AtomicU64,
+    #[cfg(feature = "metrics")]
+    background_checkpoint_bytes: AtomicU64,
+}
+
+impl PerfContext {
+    fn new() -> Self {
+        #[cfg(feature = "metrics")]
+        {
+            Self {
+                commit_count: AtomicU64::new(0),
+                commit_total_ns: AtomicU64::new(0),
+                commit_bytes: AtomicU64::new(0),
+                checkpoint_count: AtomicU64::new(0),
+                checkpoint_total_ns: AtomicU64::new(0),
+                checkpoint_bytes: AtomicU64::new(0),
+                background_checkpoint_count: AtomicU64::new(0),
+                background_checkpoint_total_ns: AtomicU64::new(0),
+                background_checkpoint_bytes: AtomicU64::new(0),
+            }
+        }
+        #[cfg(not(feature = "metrics"))]
+        {
+            Self {}
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record_commit(&self, elapsed: std::time::Duration, bytes: u64) {
+        self.commit_count.fetch_add(1, Ordering::Relaxed);
+        self.commit_total_ns.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.commit_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_commit(&self, _elapsed: std::time::Duration, _bytes: u64) {}
+
+    #[cfg(feature = "metrics")]
+    fn record_checkpoint(&self, elapsed: std::time::Duration, bytes: u64) {
+        self.checkpoint_count.fetch_add(1, Ordering::Relaxed);
+        self.checkpoint_total_ns.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.checkpoint_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_checkpoint(&self, _elapsed: std::time::Duration, _bytes: u64) {}
+
+    #[cfg(feature = "metrics")]
+    fn record_background_checkpoint(&self, elapsed: std::time::Duration, bytes: u64) {
+        self.background_checkpoint_count.fetch_add(1, Ordering::Relaxed);
+        self.background_checkpoint_total_ns.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.background_checkpoint_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    fn record_background_checkpoint(&self, _elapsed: std::time::Duration, _bytes: u64) {}
+
+    fn snapshot(&self) -> PerfContextSnapshot {
+        #[cfg(feature = "metrics")]
+        {
+            PerfContextSnapshot {
+                commit_count: self.commit_count.load(Ordering::Relaxed),
+                commit_total_ns: self.commit_total_ns.load(Ordering::Relaxed),
+                commit_bytes: self.commit_bytes.load(Ordering::Relaxed),
+                checkpoint_count: self.checkpoint_count.load(Ordering::Relaxed),
+                checkpoint_total_ns: self.checkpoint_total_ns.load(Ordering::Relaxed),
+                checkpoint_bytes: self.checkpoint_bytes.load(Ordering::Relaxed),
+                background_checkpoint_count: self.background_checkpoint_count.load(Ordering::Relaxed),
+                background_checkpoint_total_ns: self.background_checkpoint_total_ns.load(Ordering::Relaxed),
+                background_checkpoint_bytes: self.background_checkpoint_bytes.load(Ordering::Relaxed),
+            }
+        }
+        #[cfg(not(feature = "metrics"))]
+        {
+            PerfContextSnapshot::default()
+        }
+    }
+}
+
+/// Point-in-time read of [`PerfContext`], returned by
+/// [`SingleFileDB::perf_context`]. Every field is `0` when the `metrics`
+/// feature is disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PerfContextSnapshot {
+    /// Number of successful `commit()` calls, read-only ones included
+    /// (those write no WAL record and add 0 bytes below).
+    pub commit_count: u64,
+    /// Total nanoseconds spent inside `commit()` across all of them.
+    pub commit_total_ns: u64,
+    /// Total bytes of the COMMIT WAL record itself -- not the whole
+    /// transaction's writes, which are accounted for by `DbMetrics`'
+    /// `wal_bytes_written` as they happen.
+    pub commit_bytes: u64,
+    /// Number of completed blocking `checkpoint()` calls.
+    pub checkpoint_count: u64,
+    /// Total nanoseconds spent inside `checkpoint()` across all of them.
+    pub checkpoint_total_ns: u64,
+    /// Total uncompressed snapshot bytes built across all of them.
+    pub checkpoint_bytes: u64,
+    /// Number of completed `background_checkpoint()` calls.
+    pub background_checkpoint_count: u64,
+    /// Total nanoseconds spent inside `background_checkpoint()` across all
+    /// of them.
+    pub background_checkpoint_total_ns: u64,
+    /// Total uncompressed snapshot bytes built across all of them.
+    pub background_checkpoint_bytes: u64,
+}
+
 // ============================================================================
 // Open Options
 // ============================================================================
 
+/// Commit durability level, selectable per-transaction and as a default in
+/// [`SingleFileOpenOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// Only update the in-memory delta and WAL buffer; nothing is flushed or
+    /// fsynced on commit. Fastest, but a crash can lose committed
+    /// transactions -- intended for bulk-load workloads that can be retried.
+    None,
+    /// Flush WAL bytes to the OS page cache on commit but defer `fsync`
+    /// until something else syncs the file (a checkpoint, or a later
+    /// `Immediate` commit); there is no periodic background flush.
+    /// Survives process crashes but not OS/power failure.
+    #[default]
+    Eventual,
+    /// Flush the WAL buffer and `fsync` the pager on every commit. Slowest,
+    /// but every successful commit is durable across a crash.
+    Immediate,
+}
+
+/// Snapshot page compression, selected at open time and applied to every
+/// snapshot `checkpoint` writes afterwards. `None` (the default) keeps the
+/// existing uncompressed layout so `.raydb` files written by older versions
+/// keep opening unchanged; `Lz4`/`Zstd` ask `build_snapshot_to_memory` to
+/// emit the block-compressed layout (fixed-size compressed blocks plus a
+/// block-offset directory) that `SnapshotData` decompresses on demand, block
+/// by block, into its page cache instead of inflating the whole snapshot.
+/// The codec is stored in the snapshot header so `reload_snapshot` can tell
+/// which one to use without being told again at open time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotCompression {
+    Lz4,
+    /// Zstd at the given level (1-22; higher trades write speed for a
+    /// smaller snapshot). Forwarded as-is to `build_snapshot_to_memory`.
+    Zstd { level: i32 },
+}
+
 /// Options for opening a single-file database
 #[derive(Debug, Clone)]
 pub struct SingleFileOpenOptions {
@@ -133,6 +1389,20 @@ pub struct SingleFileOpenOptions {
     pub checkpoint_threshold: f64,
     /// Use background (non-blocking) checkpoint instead of blocking (default true)
     pub background_checkpoint: bool,
+    /// Default commit durability level when a transaction doesn't override it
+    pub durability: Durability,
+    /// Snapshot compression applied by `checkpoint` (default: uncompressed)
+    pub compression: Option<SnapshotCompression>,
+    /// Always rebuild state from an exhaustive WAL scan (recording a
+    /// [`RepairReport`] on the opened `SingleFileDB`) instead of only doing
+    /// so implicitly when the snapshot fails to parse.
+    pub repair: bool,
+    /// In read-only mode, fail [`open_single_file`]/[`open_single_file_read_only`]
+    /// instead of opening when the file has uncheckpointed WAL data -- a
+    /// sign the read-only view would start out already lagging behind
+    /// whatever the writer has committed but not yet rolled into a
+    /// snapshot. Ignored when `read_only` is `false`.
+    pub error_if_wal_nonempty: bool,
 }
 
 impl Default for SingleFileOpenOptions {
@@ -145,6 +1415,10 @@ impl Default for SingleFileOpenOptions {
             auto_checkpoint: false,
             checkpoint_threshold: 0.8,
             background_checkpoint: true,
+            durability: Durability::default(),
+            compression: None,
+            repair: false,
+            error_if_wal_nonempty: false,
         }
     }
 }
@@ -188,6 +1462,26 @@ impl SingleFileOpenOptions {
         self.background_checkpoint = value;
         self
     }
+
+    pub fn durability(mut self, value: Durability) -> Self {
+        self.durability = value;
+        self
+    }
+
+    pub fn compression(mut self, value: Option<SnapshotCompression>) -> Self {
+        self.compression = value;
+        self
+    }
+
+    pub fn repair(mut self, value: bool) -> Self {
+        self.repair = value;
+        self
+    }
+
+    pub fn error_if_wal_nonempty(mut self, value: bool) -> Self {
+        self.error_if_wal_nonempty = value;
+        self
+    }
 }
 
 // ============================================================================
@@ -224,7 +1518,7 @@ pub fn open_single_file<P: AsRef<Path>>(
     }
 
     // Open or create pager
-    let (mut pager, header, is_new) = if file_exists {
+    let (mut pager, mut header, is_new) = if file_exists {
         // Open existing database
         let mut pager = open_pager(path, options.page_size)?;
 
@@ -256,6 +1550,10 @@ pub fn open_single_file<P: AsRef<Path>>(
         (pager, header, true)
     };
 
+    if options.read_only && options.error_if_wal_nonempty && header.wal_head > 0 {
+        return Err(RayError::ReadOnlyViewStale);
+    }
+
     // Initialize WAL buffer
     let wal_buffer = WalBuffer::from_header(&header);
 
@@ -272,6 +1570,7 @@ pub fn open_single_file<P: AsRef<Path>>(
 
     // Initialize delta
     let mut delta = DeltaState::new();
+    let mut vector_stores: HashMap<PropKeyId, VectorManifest> = HashMap::new();
 
     // Schema maps
     let mut label_names: HashMap<String, LabelId> = HashMap::new();
@@ -282,6 +1581,7 @@ pub fn open_single_file<P: AsRef<Path>>(
     let mut propkey_ids: HashMap<PropKeyId, String> = HashMap::new();
 
     // Load snapshot if exists
+    let mut snapshot_failed = false;
     let snapshot = if header.snapshot_page_count > 0 {
         // Calculate snapshot offset in bytes
         let snapshot_offset = (header.snapshot_start_page * header.page_size as u64) as usize;
@@ -315,17 +1615,20 @@ pub fn open_single_file<P: AsRef<Path>>(
                         propkey_ids.insert(i, name.to_string());
                     }
                 }
-                
+
                 // Update ID allocators from snapshot
                 next_node_id = snap.header.max_node_id + 1;
                 next_label_id = snap.header.num_labels as u32 + 1;
                 next_etype_id = snap.header.num_etypes as u32 + 1;
                 next_propkey_id = snap.header.num_propkeys as u32 + 1;
-                
+
+                vector_stores = vector::vector_stores_from_snapshot(&snap)?;
+
                 Some(snap)
             }
             Err(e) => {
                 eprintln!("Warning: Failed to parse snapshot: {}", e);
+                snapshot_failed = true;
                 None
             }
         }
@@ -333,14 +1636,28 @@ pub fn open_single_file<P: AsRef<Path>>(
         None
     };
 
-    // Replay WAL for recovery (if not a new database)
-    if !is_new && header.wal_head > 0 {
+    // Replay WAL for recovery (if not a new database), or exhaustively if the
+    // snapshot failed to parse or repair mode was requested explicitly.
+    let mut last_repair = None;
+    if !is_new && (header.wal_head > 0 || snapshot_failed || options.repair) {
+        let claimed_head = header.wal_head;
+
         // Read WAL records from the circular buffer
-        let wal_records = scan_wal_records(&mut pager, &header)?;
+        let (wal_records, recovered_head) = scan_wal_records(&mut pager, &header)?;
+        if recovered_head != claimed_head {
+            // Torn tail: trust the last CRC-verified boundary instead of the
+            // header's claimed head so a subsequent append can't be preceded
+            // by bytes that look like a valid (but uncommitted/corrupt) record.
+            header.wal_head = recovered_head;
+        }
         let committed = extract_committed_transactions(&wal_records);
 
         // Replay committed transactions
+        let mut transactions_recovered = 0usize;
+        let mut records_recovered = 0usize;
         for (_txid, records) in committed {
+            transactions_recovered += 1;
+            records_recovered += records.len();
             for record in records {
                 replay_wal_record(
                     record,
@@ -355,11 +1672,34 @@ pub fn open_single_file<P: AsRef<Path>>(
                     &mut etype_ids,
                     &mut propkey_names,
                     &mut propkey_ids,
+                    &mut vector_stores,
                 );
             }
         }
+
+        if snapshot_failed || options.repair {
+            let wal_size = header.wal_page_count * header.page_size as u64;
+            let bytes_dropped = if wal_size > 0 {
+                (claimed_head + wal_size - recovered_head) % wal_size
+            } else {
+                0
+            };
+            last_repair = Some(RepairReport {
+                snapshot_ok: !snapshot_failed,
+                transactions_recovered,
+                records_recovered,
+                first_bad_offset: (bytes_dropped > 0).then_some(recovered_head),
+                bytes_dropped,
+            });
+        }
     }
 
+    let snapshot_identity = if snapshot.is_some() {
+        SnapshotIdentity::current(&pager).ok()
+    } else {
+        None
+    };
+
     Ok(SingleFileDB {
         path: path.to_path_buf(),
         read_only: options.read_only,
@@ -374,6 +1714,7 @@ pub fn open_single_file<P: AsRef<Path>>(
         next_propkey_id: AtomicU32::new(next_propkey_id),
         next_tx_id: AtomicU64::new(next_tx_id),
         current_tx: Mutex::new(None),
+        read_txs: Mutex::new(HashMap::new()),
         label_names: RwLock::new(label_names),
         label_ids: RwLock::new(label_ids),
         etype_names: RwLock::new(etype_names),
@@ -384,9 +1725,52 @@ pub fn open_single_file<P: AsRef<Path>>(
         checkpoint_threshold: options.checkpoint_threshold,
         background_checkpoint: options.background_checkpoint,
         checkpoint_status: Mutex::new(CheckpointStatus::Idle),
+        default_durability: options.durability,
+        default_compression: options.compression,
+        last_repair,
+        next_version: AtomicU64::new(1),
+        committed_versions: RwLock::new(HashMap::new()),
+        checkpoint_progress: std::sync::Arc::new(CheckpointProgress::new()),
+        last_compression_stats: RwLock::new(None),
+        metrics: DbMetrics::new(),
+        perf: PerfContext::new(),
+        group_commit: GroupCommitCoordinator::new(),
+        traversal_scratch: Mutex::new(TraversalScratch::default()),
+        history: Mutex::new(VecDeque::new()),
+        node_prop_cache: Mutex::new(NodePropCache::default()),
+        snapshot_identity: RwLock::new(snapshot_identity),
+        snapshot_refs: Mutex::new(HashMap::new()),
+        namespaces: RwLock::new(HashMap::new()),
+        next_namespace_id: AtomicU32::new(1),
+        dirty_since_checkpoint: Mutex::new(HashSet::new()),
+        pending_layers: Mutex::new(VecDeque::new()),
+        vector_stores: RwLock::new(vector_stores),
     })
 }
 
+/// Open `path` for read-only access, mirroring RocksDB's
+/// `DB::open_for_read_only`.
+///
+/// Behaves exactly like [`open_single_file`] -- including replaying any
+/// existing WAL into the in-memory delta, so query results reflect every
+/// transaction committed to disk -- except the returned handle never starts
+/// a write transaction (`begin(false)` returns [`RayError::ReadOnly`], same
+/// as `open_single_file(path, opts.read_only(true))`) and never writes to
+/// the file. Any number of reader processes can open the same `.raydb` this
+/// way concurrently with a process that has it open for writing.
+///
+/// `options.read_only` is forced to `true` regardless of what's passed in.
+/// Set `options.error_if_wal_nonempty` to fail fast instead of opening when
+/// the file has uncheckpointed WAL data, which means this read-only view
+/// would start out already lagging behind whatever the writer has committed
+/// but not yet rolled into a snapshot.
+pub fn open_single_file_read_only<P: AsRef<Path>>(
+    path: P,
+    options: SingleFileOpenOptions,
+) -> Result<SingleFileDB> {
+    open_single_file(path, options.read_only(true))
+}
+
 /// Close a single-file database
 pub fn close_single_file(db: SingleFileDB) -> Result<()> {
     // Flush WAL and sync to disk
@@ -414,82 +1798,271 @@ pub fn close_single_file(db: SingleFileDB) -> Result<()> {
     Ok(())
 }
 
+/// Dry-run WAL repair scan: read-only diagnostic for a `.raydb` file,
+/// independent of `open_single_file`. Scans the WAL exhaustively (accepting
+/// each record only if its framing/CRC validates), replays every recoverable
+/// committed transaction into a throwaway `DeltaState`, and reports how much
+/// of the WAL was readable -- without touching the file or requiring the
+/// snapshot region to parse at all.
+///
+/// Use this to inspect a database before deciding whether `open_single_file`
+/// with `SingleFileOpenOptions::repair(true)` (or the automatic fallback on
+/// snapshot-parse failure) would lose anything.
+pub fn repair_single_file<P: AsRef<Path>>(path: P) -> Result<RepairReport> {
+    let path = path.as_ref();
+
+    // The header page's own layout doesn't depend on the configured page
+    // size, so open with the default first and reopen once the real size is
+    // known if it differs.
+    let mut pager = open_pager(path, DEFAULT_PAGE_SIZE)?;
+    let header_data = pager.read_page(0)?;
+    let header = DbHeaderV1::parse(&header_data)?;
+    if header.page_size as usize != DEFAULT_PAGE_SIZE {
+        pager = open_pager(path, header.page_size as usize)?;
+    }
+
+    let snapshot_ok = if header.snapshot_page_count > 0 {
+        let snapshot_offset = (header.snapshot_start_page * header.page_size as u64) as usize;
+        SnapshotData::parse_at_offset(
+            std::sync::Arc::new(unsafe {
+                // Safety: We're creating an owned Mmap from the file; this is
+                // a read-only diagnostic scan and never writes through it.
+                memmap2::Mmap::map(pager.file())?
+            }),
+            snapshot_offset,
+            &crate::core::snapshot::reader::ParseSnapshotOptions::default(),
+        )
+        .is_ok()
+    } else {
+        true
+    };
+
+    let claimed_head = header.wal_head;
+    let (wal_records, recovered_head) = scan_wal_records(&mut pager, &header)?;
+    let committed = extract_committed_transactions(&wal_records);
+
+    let mut delta = DeltaState::new();
+    let mut next_node_id = INITIAL_NODE_ID;
+    let mut next_label_id = INITIAL_LABEL_ID;
+    let mut next_etype_id = INITIAL_ETYPE_ID;
+    let mut next_propkey_id = INITIAL_PROPKEY_ID;
+    let mut label_names = HashMap::new();
+    let mut label_ids = HashMap::new();
+    let mut etype_names = HashMap::new();
+    let mut etype_ids = HashMap::new();
+    let mut propkey_names = HashMap::new();
+    let mut propkey_ids = HashMap::new();
+    let mut vector_stores: HashMap<PropKeyId, VectorManifest> = HashMap::new();
+
+    let mut transactions_recovered = 0usize;
+    let mut records_recovered = 0usize;
+    for (_txid, records) in committed {
+        transactions_recovered += 1;
+        records_recovered += records.len();
+        for record in records {
+            replay_wal_record(
+                record,
+                &mut delta,
+                &mut next_node_id,
+                &mut next_label_id,
+                &mut next_etype_id,
+                &mut next_propkey_id,
+                &mut label_names,
+                &mut label_ids,
+                &mut etype_names,
+                &mut etype_ids,
+                &mut propkey_names,
+                &mut propkey_ids,
+                &mut vector_stores,
+            );
+        }
+    }
+
+    let wal_size = header.wal_page_count * header.page_size as u64;
+    let bytes_dropped = if wal_size > 0 {
+        (claimed_head + wal_size - recovered_head) % wal_size
+    } else {
+        0
+    };
+
+    Ok(RepairReport {
+        snapshot_ok,
+        transactions_recovered,
+        records_recovered,
+        first_bad_offset: (bytes_dropped > 0).then_some(recovered_head),
+        bytes_dropped,
+    })
+}
+
 // ============================================================================
 // WAL Scanning
 // ============================================================================
 
-/// Scan WAL records from the circular buffer
-fn scan_wal_records(pager: &mut FilePager, header: &DbHeaderV1) -> Result<Vec<ParsedWalRecord>> {
+/// Per-frame fragment tag. Replaces the old `0xFFFFFFFF` skip-to-start
+/// marker: a record whose aligned size exceeds the contiguous span left
+/// before wrap is split into a `First` fragment filling the tail, zero or
+/// more `Middle` fragments, and a `Last` fragment that closes it out, so no
+/// tail space is wasted and a logical record is no longer bounded by the
+/// contiguous free span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WalFragmentTag {
+    Full = 0,
+    First = 1,
+    Middle = 2,
+    Last = 3,
+}
+
+impl WalFragmentTag {
+    fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            0 => Some(Self::Full),
+            1 => Some(Self::First),
+            2 => Some(Self::Middle),
+            3 => Some(Self::Last),
+            _ => None,
+        }
+    }
+}
+
+/// Scan WAL records from the circular buffer.
+///
+/// Also returns the offset of the last known-good record boundary, which is
+/// the same as `header.wal_head` unless a torn/corrupted tail was found, in
+/// which case it is the position just after the last record whose CRC32
+/// verified -- callers should persist this as the recovered `wal_head` so a
+/// later append doesn't leave the ambiguous bytes behind it looking valid.
+///
+/// Pairs with [`WalBuffer::write_record`]'s frame encoder, which is the only
+/// thing allowed to emit the `[frag_len][crc32][WalFragmentTag][payload]`
+/// frames this function reads -- both sides must agree on the tag byte's
+/// meaning and on where the CRC32 coverage starts (the tag byte, not the
+/// length) or recovery silently desyncs instead of failing loudly.
+fn scan_wal_records(
+    pager: &mut FilePager,
+    header: &DbHeaderV1,
+) -> Result<(Vec<ParsedWalRecord>, u64)> {
     use crate::core::wal::record::parse_wal_record;
 
     let mut records = Vec::new();
-    let wal_start = header.wal_start_page * header.page_size as u64;
     let wal_size = header.wal_page_count * header.page_size as u64;
 
     let mut pos = header.wal_tail;
     let head = header.wal_head;
+    let mut last_good_pos = pos;
 
     // If tail == head, WAL is empty
     if pos == head {
-        return Ok(records);
+        return Ok((records, head));
     }
 
     // Read the WAL area into memory for scanning
     // This is simpler than page-by-page reading for now
     let wal_data = read_wal_area(pager, header)?;
 
+    // Accumulator for a logical record currently being reassembled from a
+    // First..Last fragment run. `None` when not mid-reassembly.
+    let mut reassembly: Option<Vec<u8>> = None;
+
     while pos != head {
-        // Handle wrap-around
+        // Handle wrap-around: a fragment frame always carries its own length,
+        // so wrapping mid-reassembly is fine -- the fragment simply starts
+        // at offset 0 and `reassembly` carries the prior fragments' bytes.
         let actual_pos = pos % wal_size;
-
-        // Check for skip marker
-        if actual_pos + 8 > wal_size as u64 {
-            // Not enough space for header, wrap to start
-            pos = 0;
-            continue;
-        }
-
         let offset = actual_pos as usize;
-        if offset + 4 > wal_data.len() {
+
+        // Frame layout: [u32 frag_len][u32 crc32][u8 frag_tag][payload, frag_len bytes]
+        // crc32 covers the frag_tag byte + payload only.
+        if offset + 9 > wal_data.len() {
             break;
         }
-
-        let rec_len = u32::from_le_bytes([
+        let frag_len = u32::from_le_bytes([
             wal_data[offset],
             wal_data[offset + 1],
             wal_data[offset + 2],
             wal_data[offset + 3],
         ]) as usize;
+        if frag_len == 0 {
+            break; // Unwritten tail: nothing more to scan.
+        }
+        let stored_crc = u32::from_le_bytes([
+            wal_data[offset + 4],
+            wal_data[offset + 5],
+            wal_data[offset + 6],
+            wal_data[offset + 7],
+        ]);
+        let Some(tag) = WalFragmentTag::from_byte(wal_data[offset + 8]) else {
+            break; // Unrecognized tag: torn or corrupted frame.
+        };
+        if offset + 9 + frag_len > wal_data.len() {
+            break; // Torn write: frame claims more bytes than are present.
+        }
+        let frag_body = &wal_data[offset + 9..offset + 9 + frag_len];
+        if crate::util::crc::crc32_ieee(&[&[wal_data[offset + 8]], frag_body].concat()) != stored_crc
+        {
+            // CRC mismatch: torn tail of the log. Stop scanning rather than
+            // emitting a partially-reassembled or corrupt logical record.
+            break;
+        }
 
-        // Skip marker check
-        if rec_len == 0 {
-            if offset + 8 <= wal_data.len() {
-                let marker = u32::from_le_bytes([
-                    wal_data[offset + 4],
-                    wal_data[offset + 5],
-                    wal_data[offset + 6],
-                    wal_data[offset + 7],
-                ]);
-                if marker == 0xFFFFFFFF {
-                    // Skip to start
-                    pos = 0;
-                    continue;
+        let aligned_size = crate::util::binary::align_up(frag_len + 9, WAL_RECORD_ALIGNMENT);
+        let next_pos = (actual_pos + aligned_size as u64) % wal_size;
+
+        match tag {
+            WalFragmentTag::Full => {
+                if let Some(record) = parse_reassembled_record(frag_body) {
+                    records.push(record);
+                    pos = next_pos;
+                    last_good_pos = pos;
+                } else {
+                    break;
+                }
+            }
+            WalFragmentTag::First => {
+                reassembly = Some(frag_body.to_vec());
+                pos = next_pos;
+                // Only commit `last_good_pos` once the run closes with Last,
+                // so a First with no matching Last is treated as torn.
+            }
+            WalFragmentTag::Middle => {
+                match reassembly.as_mut() {
+                    Some(buf) => buf.extend_from_slice(frag_body),
+                    None => break, // Middle with no preceding First: corrupt.
+                }
+                pos = next_pos;
+            }
+            WalFragmentTag::Last => {
+                let Some(mut buf) = reassembly.take() else {
+                    break; // Last with no preceding First: corrupt.
+                };
+                buf.extend_from_slice(frag_body);
+                if let Some(record) = parse_reassembled_record(&buf) {
+                    records.push(record);
+                    pos = next_pos;
+                    last_good_pos = pos;
+                } else {
+                    break;
                 }
             }
-            break; // Invalid record
-        }
-
-        // Parse the record
-        if let Some(record) = parse_wal_record(&wal_data, offset) {
-            let aligned_size = crate::util::binary::align_up(rec_len, WAL_RECORD_ALIGNMENT);
-            pos = (actual_pos + aligned_size as u64) % wal_size;
-            records.push(record);
-        } else {
-            break; // Invalid record
         }
     }
 
-    Ok(records)
+    Ok((records, last_good_pos))
+}
+
+/// Reassemble a logical record's payload bytes (type byte + record payload,
+/// with no frame header) into a `ParsedWalRecord` by handing it to the
+/// existing single-frame parser via a synthetic `[len][crc][tag][payload]`
+/// buffer it already knows how to read.
+fn parse_reassembled_record(payload: &[u8]) -> Option<ParsedWalRecord> {
+    use crate::core::wal::record::parse_wal_record;
+
+    let mut synthetic = Vec::with_capacity(9 + payload.len());
+    synthetic.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    let crc = crate::util::crc::crc32_ieee(&[&[WalFragmentTag::Full as u8], payload].concat());
+    synthetic.extend_from_slice(&crc.to_le_bytes());
+    synthetic.push(WalFragmentTag::Full as u8);
+    synthetic.extend_from_slice(payload);
+    parse_wal_record(&synthetic, 0)
 }
 
 /// Read the entire WAL area into memory
@@ -522,6 +2095,7 @@ fn replay_wal_record(
     etype_ids: &mut HashMap<ETypeId, String>,
     propkey_names: &mut HashMap<String, PropKeyId>,
     propkey_ids: &mut HashMap<PropKeyId, String>,
+    vector_stores: &mut HashMap<PropKeyId, VectorManifest>,
 ) {
     match record.record_type {
         WalRecordType::CreateNode => {
@@ -537,6 +2111,11 @@ fn replay_wal_record(
                 delta.delete_node(data.node_id);
             }
         }
+        WalRecordType::UndeleteNode => {
+            if let Some(data) = parse_undelete_node_payload(&record.payload) {
+                delta.undelete_node(data.node_id);
+            }
+        }
         WalRecordType::AddEdge => {
             if let Some(data) = parse_add_edge_payload(&record.payload) {
                 delta.add_edge(data.src, data.etype, data.dst);
@@ -557,6 +2136,16 @@ fn replay_wal_record(
                 delta.delete_node_prop(data.node_id, data.key_id);
             }
         }
+        WalRecordType::SetEdgeProp => {
+            if let Some(data) = parse_set_edge_prop_payload(&record.payload) {
+                delta.set_edge_prop(data.src, data.etype, data.dst, data.key_id, data.value);
+            }
+        }
+        WalRecordType::DelEdgeProp => {
+            if let Some(data) = parse_del_edge_prop_payload(&record.payload) {
+                delta.delete_edge_prop(data.src, data.etype, data.dst, data.key_id);
+            }
+        }
         WalRecordType::DefineLabel => {
             if let Some(data) = parse_define_label_payload(&record.payload) {
                 delta.define_label(data.label_id, &data.name);
@@ -587,1877 +2176,4842 @@ fn replay_wal_record(
                 }
             }
         }
+        WalRecordType::SetNodeVector => {
+            vector::replay_set_node_vector(vector_stores, &record.payload);
+        }
+        WalRecordType::DelNodeVector => {
+            vector::replay_del_node_vector(vector_stores, &record.payload);
+        }
         _ => {
-            // Other record types (vectors, edge props, etc.) - skip for now
+            // Other record types - skip for now
         }
     }
 }
 
-// ============================================================================
-// SingleFileDB Implementation
-// ============================================================================
+/// A repeatable-read view of the graph pinned by [`SingleFileDB::snapshot`].
+///
+/// Capturing one clones the current delta overlay and takes a cheap,
+/// `Arc`-backed reference to the currently mapped snapshot, so later commits
+/// against the live `SingleFileDB` can't change what this handle sees.
+/// `generation`/`commit_seq` record what was pinned for diagnostics; the
+/// isolation itself comes from the whole delta being cloned atomically
+/// under its `RwLock` at capture time, not from filtering individual delta
+/// records by sequence number -- the delta doesn't track one per entry.
+///
+/// While at least one `ReadSnapshot` is alive for the database's current
+/// snapshot generation, [`SingleFileDB::checkpoint`] and
+/// [`SingleFileDB::background_checkpoint`] refuse to run rather than
+/// overwrite the pages this handle's mapped snapshot points at. Dropping the
+/// last live handle for a generation lets the next checkpoint proceed.
+pub struct ReadSnapshot<'a> {
+    db: &'a SingleFileDB,
+    generation: u64,
+    commit_seq: u64,
+    snapshot: Option<SnapshotData>,
+    delta: DeltaState,
+}
 
-impl SingleFileDB {
-    /// Allocate a new node ID
-    pub fn alloc_node_id(&self) -> NodeId {
-        self.next_node_id.fetch_add(1, Ordering::SeqCst)
+impl<'a> ReadSnapshot<'a> {
+    /// The snapshot generation pinned by this handle.
+    pub fn generation(&self) -> u64 {
+        self.generation
     }
 
-    /// Allocate a new label ID
-    pub fn alloc_label_id(&self) -> LabelId {
-        self.next_label_id.fetch_add(1, Ordering::SeqCst)
+    /// The commit sequence number (see [`SingleFileDB::commit`]'s
+    /// `next_version` counter) as of capture.
+    pub fn commit_seq(&self) -> u64 {
+        self.commit_seq
     }
 
-    /// Allocate a new edge type ID
-    pub fn alloc_etype_id(&self) -> ETypeId {
-        self.next_etype_id.fetch_add(1, Ordering::SeqCst)
+    /// Check if a node exists in this view.
+    pub fn node_exists(&self, node_id: NodeId) -> bool {
+        if self.delta.is_node_deleted(node_id) {
+            return false;
+        }
+        if self.delta.is_node_created(node_id) {
+            return true;
+        }
+        self.snapshot.as_ref().map(|s| s.has_node(node_id)).unwrap_or(false)
     }
 
-    /// Allocate a new property key ID
-    pub fn alloc_propkey_id(&self) -> PropKeyId {
-        self.next_propkey_id.fetch_add(1, Ordering::SeqCst)
+    /// Check if an edge exists in this view.
+    pub fn edge_exists(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> bool {
+        if self.delta.is_edge_deleted(src, etype, dst) {
+            return false;
+        }
+        if self.delta.is_edge_added(src, etype, dst) {
+            return true;
+        }
+        if let Some(ref snap) = self.snapshot {
+            if let (Some(src_phys), Some(dst_phys)) =
+                (snap.get_phys_node(src), snap.get_phys_node(dst))
+            {
+                return snap.has_edge(src_phys, etype, dst_phys);
+            }
+        }
+        false
     }
 
-    /// Allocate a new transaction ID
-    pub fn alloc_tx_id(&self) -> TxId {
-        self.next_tx_id.fetch_add(1, Ordering::SeqCst)
-    }
+    /// Get all properties for a node, as of this view. Mirrors
+    /// [`SingleFileDB::get_node_props`] exactly, merging the pinned
+    /// snapshot with the pinned delta instead of the live ones.
+    pub fn get_node_props(&self, node_id: NodeId) -> Option<HashMap<PropKeyId, PropValue>> {
+        if self.delta.is_node_deleted(node_id) {
+            return None;
+        }
 
-    /// Get or create a label ID by name
-    pub fn get_or_create_label(&self, name: &str) -> LabelId {
-        {
-            let names = self.label_names.read();
-            if let Some(&id) = names.get(name) {
-                return id;
+        let mut props = HashMap::new();
+        if let Some(ref snap) = self.snapshot {
+            if let Some(phys) = snap.get_phys_node(node_id) {
+                if let Some(snapshot_props) = snap.get_node_props(phys) {
+                    props = snapshot_props;
+                }
             }
         }
 
-        let id = self.alloc_label_id();
-        {
-            let mut names = self.label_names.write();
-            let mut ids = self.label_ids.write();
-            if let Some(&existing) = names.get(name) {
-                return existing;
+        if let Some(node_delta) = self.delta.get_node_delta(node_id) {
+            if let Some(ref delta_props) = node_delta.props {
+                for (&key_id, value) in delta_props {
+                    match value {
+                        Some(v) => { props.insert(key_id, v.clone()); }
+                        None => { props.remove(&key_id); }
+                    }
+                }
             }
-            names.insert(name.to_string(), id);
-            ids.insert(id, name.to_string());
         }
-        id
-    }
 
-    /// Get label ID by name
-    pub fn get_label_id(&self, name: &str) -> Option<LabelId> {
-        self.label_names.read().get(name).copied()
-    }
+        let node_exists_in_delta = self.delta.is_node_created(node_id)
+            || self.delta.get_node_delta(node_id).is_some();
 
-    /// Get label name by ID
-    pub fn get_label_name(&self, id: LabelId) -> Option<String> {
-        self.label_ids.read().get(&id).cloned()
+        if !node_exists_in_delta {
+            match self.snapshot.as_ref() {
+                Some(snap) if snap.get_phys_node(node_id).is_none() => return None,
+                None => return None,
+                _ => {}
+            }
+        }
+
+        Some(props)
     }
 
-    /// Get or create an edge type ID by name
-    pub fn get_or_create_etype(&self, name: &str) -> ETypeId {
-        {
-            let names = self.etype_names.read();
-            if let Some(&id) = names.get(name) {
-                return id;
+    /// Get outgoing edges for a node, as of this view. Mirrors
+    /// [`SingleFileDB::get_out_edges`].
+    pub fn get_out_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+        if self.delta.is_node_deleted(node_id) {
+            return Vec::new();
+        }
+
+        let mut edges = Vec::new();
+        if let Some(ref snap) = self.snapshot {
+            if let Some(phys) = snap.get_phys_node(node_id) {
+                for (dst_phys, etype) in snap.iter_out_edges(phys) {
+                    if let Some(dst_node_id) = snap.get_node_id(dst_phys) {
+                        if self.delta.is_node_deleted(dst_node_id) {
+                            continue;
+                        }
+                        if self.delta.is_edge_deleted(node_id, etype, dst_node_id) {
+                            continue;
+                        }
+                        edges.push((etype, dst_node_id));
+                    }
+                }
             }
         }
 
-        let id = self.alloc_etype_id();
-        {
-            let mut names = self.etype_names.write();
-            let mut ids = self.etype_ids.write();
-            if let Some(&existing) = names.get(name) {
-                return existing;
+        if let Some(added_edges) = self.delta.out_add.get(&node_id) {
+            for edge_patch in added_edges {
+                if self.delta.is_node_deleted(edge_patch.other) {
+                    continue;
+                }
+                edges.push((edge_patch.etype, edge_patch.other));
             }
-            names.insert(name.to_string(), id);
-            ids.insert(id, name.to_string());
         }
-        id
-    }
 
-    /// Get edge type ID by name
-    pub fn get_etype_id(&self, name: &str) -> Option<ETypeId> {
-        self.etype_names.read().get(name).copied()
+        edges.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        edges
     }
 
-    /// Get edge type name by ID
-    pub fn get_etype_name(&self, id: ETypeId) -> Option<String> {
-        self.etype_ids.read().get(&id).cloned()
-    }
+    /// Get incoming edges for a node, as of this view. Mirrors
+    /// [`SingleFileDB::get_in_edges`].
+    pub fn get_in_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+        if self.delta.is_node_deleted(node_id) {
+            return Vec::new();
+        }
 
-    /// Get or create a property key ID by name
-    pub fn get_or_create_propkey(&self, name: &str) -> PropKeyId {
-        {
-            let names = self.propkey_names.read();
-            if let Some(&id) = names.get(name) {
-                return id;
+        let mut edges = Vec::new();
+        if let Some(ref snap) = self.snapshot {
+            if let Some(phys) = snap.get_phys_node(node_id) {
+                for (src_phys, etype, _out_index) in snap.iter_in_edges(phys) {
+                    if let Some(src_node_id) = snap.get_node_id(src_phys) {
+                        if self.delta.is_node_deleted(src_node_id) {
+                            continue;
+                        }
+                        if self.delta.is_edge_deleted(src_node_id, etype, node_id) {
+                            continue;
+                        }
+                        edges.push((etype, src_node_id));
+                    }
+                }
             }
         }
 
-        let id = self.alloc_propkey_id();
-        {
-            let mut names = self.propkey_names.write();
-            let mut ids = self.propkey_ids.write();
-            if let Some(&existing) = names.get(name) {
-                return existing;
+        if let Some(added_edges) = self.delta.in_add.get(&node_id) {
+            for edge_patch in added_edges {
+                if self.delta.is_node_deleted(edge_patch.other) {
+                    continue;
+                }
+                edges.push((edge_patch.etype, edge_patch.other));
             }
-            names.insert(name.to_string(), id);
-            ids.insert(id, name.to_string());
         }
-        id
+
+        edges.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        edges
     }
 
-    /// Get property key ID by name
-    pub fn get_propkey_id(&self, name: &str) -> Option<PropKeyId> {
-        self.propkey_names.read().get(name).copied()
+    /// Get a node's vector for this view, as of the commit sequence pinned by
+    /// `snapshot`. Mirrors `get_node_props`/`get_out_edges` above by reusing
+    /// the live accessor rather than duplicating its lookup: delegates to
+    /// [`SingleFileDB::node_vector_as_of`] with `commit_seq` as the snapshot
+    /// timestamp. As `node_vector_as_of`'s own doc comment notes, there's no
+    /// version chain for vectors, so this returns the live value rather than
+    /// one actually pinned to `commit_seq`.
+    pub fn node_vector(&self, node_id: NodeId, prop_key_id: PropKeyId) -> Option<VectorRef> {
+        self.db.node_vector_as_of(node_id, prop_key_id, self.commit_seq)
     }
+}
 
-    /// Get property key name by ID
-    pub fn get_propkey_name(&self, id: PropKeyId) -> Option<String> {
-        self.propkey_ids.read().get(&id).cloned()
+impl<'a> Drop for ReadSnapshot<'a> {
+    fn drop(&mut self) {
+        let mut refs = self.db.snapshot_refs.lock();
+        if let Some(count) = refs.get_mut(&self.generation) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                refs.remove(&self.generation);
+            }
+        }
     }
+}
 
-    /// Check if a node exists
-    pub fn node_exists(&self, node_id: NodeId) -> bool {
-        let delta = self.delta.read();
+/// A single buffered mutation recorded by an [`OptimisticTxn`], replayed
+/// through the regular single-writer write methods at commit time.
+///
+/// Distinct from [`HistoryOp`]: `HistoryOp` records the value a write
+/// *overwrote* so `unrecord` can restore it, while `OptimisticOp` records the
+/// new value itself so it can be replayed forward once the transaction
+/// validates.
+#[derive(Debug, Clone)]
+enum OptimisticOp {
+    CreateNode(NodeId, Option<String>),
+    DeleteNode(NodeId),
+    AddEdge(NodeId, ETypeId, NodeId),
+    DeleteEdge(NodeId, ETypeId, NodeId),
+    SetNodeProp(NodeId, PropKeyId, PropValue),
+    DeleteNodeProp(NodeId, PropKeyId),
+    SetEdgeProp(NodeId, ETypeId, NodeId, PropKeyId, PropValue),
+    DeleteEdgeProp(NodeId, ETypeId, NodeId, PropKeyId),
+}
 
-        if delta.is_node_deleted(node_id) {
-            return false;
-        }
+/// A multi-writer transaction following the OptimisticTransactionDB pattern:
+/// several of these can be built concurrently against the same
+/// [`SingleFileDB`] (unlike the single exclusive transaction `begin` hands
+/// out), buffering their writes locally, and conflicts are only detected
+/// when one of them calls [`OptimisticTxn::commit`].
+///
+/// Every node this transaction reads or writes -- including the endpoints of
+/// an `edge_exists` check, so add/remove races on an edge are caught too --
+/// is recorded in a read set tagged with the commit version it had as of
+/// this transaction's `begin_optimistic` call, not whatever's live the first
+/// time the transaction body happens to reference it; otherwise a node left
+/// untouched until late in the transaction would pick up a concurrent
+/// commit landed in between as its own baseline and never detect it as a
+/// conflict. `commit` briefly takes the database's regular exclusive
+/// transaction slot, re-validates that read set against
+/// [`SingleFileDB::committed_versions`], and only then replays the buffered
+/// writes through the ordinary write methods and commits, so a successful
+/// optimistic commit is indistinguishable on the WAL from an ordinary one.
+/// A conflicting read set instead fails with `RayError::Conflict` and
+/// buffers nothing, so the caller can rebuild and retry.
+///
+/// Node creation allocates its id eagerly, off the same shared atomic
+/// counter `create_node` uses, so two transactions built at the same time
+/// never collide on an id even though neither has committed yet.
+pub struct OptimisticTxn<'a> {
+    db: &'a SingleFileDB,
+    durability: Durability,
+    local_delta: DeltaState,
+    ops: Vec<OptimisticOp>,
+    read_set: HashMap<NodeId, u64>,
+    /// A full snapshot of [`SingleFileDB::committed_versions`] taken at
+    /// `begin_optimistic` time, before this transaction's first buffered op.
+    /// `touch` must stamp a node's read-set entry with its version *as of
+    /// begin*, not whatever's live in `committed_versions` the first time
+    /// this transaction happens to reference it -- otherwise a node that's
+    /// untouched until late in the transaction body would pick up any
+    /// concurrent commit that landed in between as its baseline, and
+    /// `commit`'s validation would compare that baseline against itself and
+    /// see no conflict, silently clobbering the write it never actually saw.
+    base_versions: HashMap<NodeId, u64>,
+}
 
-        if delta.is_node_created(node_id) {
-            return true;
-        }
+impl<'a> OptimisticTxn<'a> {
+    fn touch(&mut self, node_id: NodeId) {
+        self.read_set
+            .entry(node_id)
+            .or_insert_with(|| self.base_versions.get(&node_id).copied().unwrap_or(0));
+    }
 
-        // Check snapshot
-        if let Some(ref snapshot) = *self.snapshot.read() {
-            return snapshot.has_node(node_id);
-        }
+    /// Reserve a fresh id and buffer a node creation, visible to this
+    /// transaction's own later reads but not to anything else until commit.
+    pub fn create_node(&mut self, key: Option<&str>) -> NodeId {
+        let node_id = self.db.alloc_node_id();
+        self.local_delta.create_node(node_id, key);
+        self.ops
+            .push(OptimisticOp::CreateNode(node_id, key.map(str::to_string)));
+        node_id
+    }
 
-        false
+    /// Buffer a node deletion.
+    pub fn delete_node(&mut self, node_id: NodeId) {
+        self.touch(node_id);
+        self.local_delta.delete_node(node_id);
+        self.ops.push(OptimisticOp::DeleteNode(node_id));
     }
 
-    /// Check if an edge exists
-    pub fn edge_exists(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> bool {
-        let delta = self.delta.read();
+    /// Buffer an edge addition. Both endpoints join the read set, since a
+    /// concurrent delete of either would invalidate this edge.
+    pub fn add_edge(&mut self, src: NodeId, etype: ETypeId, dst: NodeId) {
+        self.touch(src);
+        self.touch(dst);
+        self.local_delta.add_edge(src, etype, dst);
+        self.ops.push(OptimisticOp::AddEdge(src, etype, dst));
+    }
 
-        if delta.is_edge_deleted(src, etype, dst) {
+    /// Buffer an edge deletion. Both endpoints join the read set.
+    pub fn delete_edge(&mut self, src: NodeId, etype: ETypeId, dst: NodeId) {
+        self.touch(src);
+        self.touch(dst);
+        self.local_delta.delete_edge(src, etype, dst);
+        self.ops.push(OptimisticOp::DeleteEdge(src, etype, dst));
+    }
+
+    /// Buffer a node property write.
+    pub fn set_node_prop(&mut self, node_id: NodeId, key_id: PropKeyId, value: PropValue) {
+        self.touch(node_id);
+        self.local_delta.set_node_prop(node_id, key_id, value.clone());
+        self.ops.push(OptimisticOp::SetNodeProp(node_id, key_id, value));
+    }
+
+    /// Buffer a node property write by key name.
+    pub fn set_node_prop_by_name(&mut self, node_id: NodeId, key_name: &str, value: PropValue) {
+        let key_id = self.db.get_or_create_propkey(key_name);
+        self.set_node_prop(node_id, key_id, value);
+    }
+
+    /// Buffer a node property deletion.
+    pub fn delete_node_prop(&mut self, node_id: NodeId, key_id: PropKeyId) {
+        self.touch(node_id);
+        self.local_delta.delete_node_prop(node_id, key_id);
+        self.ops.push(OptimisticOp::DeleteNodeProp(node_id, key_id));
+    }
+
+    /// Buffer an edge property write. Both endpoints join the read set.
+    pub fn set_edge_prop(
+        &mut self,
+        src: NodeId,
+        etype: ETypeId,
+        dst: NodeId,
+        key_id: PropKeyId,
+        value: PropValue,
+    ) {
+        self.touch(src);
+        self.touch(dst);
+        self.local_delta.set_edge_prop(src, etype, dst, key_id, value.clone());
+        self.ops
+            .push(OptimisticOp::SetEdgeProp(src, etype, dst, key_id, value));
+    }
+
+    /// Buffer an edge property deletion. Both endpoints join the read set.
+    pub fn delete_edge_prop(&mut self, src: NodeId, etype: ETypeId, dst: NodeId, key_id: PropKeyId) {
+        self.touch(src);
+        self.touch(dst);
+        self.local_delta.delete_edge_prop(src, etype, dst, key_id);
+        self.ops
+            .push(OptimisticOp::DeleteEdgeProp(src, etype, dst, key_id));
+    }
+
+    /// Check whether a node exists in this transaction's own view (the live
+    /// snapshot plus everything buffered so far).
+    pub fn node_exists(&self, node_id: NodeId) -> bool {
+        if self.local_delta.is_node_deleted(node_id) {
             return false;
         }
-
-        if delta.is_edge_added(src, etype, dst) {
+        if self.local_delta.is_node_created(node_id) {
             return true;
         }
+        self.db
+            .snapshot
+            .read()
+            .as_ref()
+            .map(|s| s.has_node(node_id))
+            .unwrap_or(false)
+    }
 
-        // Check snapshot
-        if let Some(ref snapshot) = *self.snapshot.read() {
+    /// Check whether an edge exists in this transaction's own view. Both
+    /// endpoints join the read set, so a concurrent add/remove of this exact
+    /// edge is caught at commit even though this call only reads.
+    pub fn edge_exists(&mut self, src: NodeId, etype: ETypeId, dst: NodeId) -> bool {
+        self.touch(src);
+        self.touch(dst);
+        if self.local_delta.is_edge_deleted(src, etype, dst) {
+            return false;
+        }
+        if self.local_delta.is_edge_added(src, etype, dst) {
+            return true;
+        }
+        if let Some(ref snap) = *self.db.snapshot.read() {
             if let (Some(src_phys), Some(dst_phys)) =
-                (snapshot.get_phys_node(src), snapshot.get_phys_node(dst))
+                (snap.get_phys_node(src), snap.get_phys_node(dst))
             {
-                return snapshot.has_edge(src_phys, etype, dst_phys);
+                return snap.has_edge(src_phys, etype, dst_phys);
             }
         }
-
         false
     }
 
-    // ========================================================================
-    // Transaction Methods
-    // ========================================================================
+    /// Get all properties for a node in this transaction's own view. The
+    /// node joins the read set, same as `edge_exists`.
+    pub fn get_node_props(&mut self, node_id: NodeId) -> Option<HashMap<PropKeyId, PropValue>> {
+        self.touch(node_id);
 
-    /// Begin a new transaction
-    pub fn begin(&self, read_only: bool) -> Result<TxId> {
-        if self.read_only && !read_only {
-            return Err(RayError::ReadOnly);
+        if self.local_delta.is_node_deleted(node_id) {
+            return None;
         }
 
-        let mut current_tx = self.current_tx.lock();
-        if current_tx.is_some() {
-            return Err(RayError::TransactionInProgress);
+        let mut props = HashMap::new();
+        if let Some(ref snap) = *self.db.snapshot.read() {
+            if let Some(phys) = snap.get_phys_node(node_id) {
+                if let Some(snapshot_props) = snap.get_node_props(phys) {
+                    props = snapshot_props;
+                }
+            }
         }
 
-        let txid = self.alloc_tx_id();
-        let snapshot_ts = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_nanos() as u64)
-            .unwrap_or(0);
+        if let Some(node_delta) = self.local_delta.get_node_delta(node_id) {
+            if let Some(ref delta_props) = node_delta.props {
+                for (&key_id, value) in delta_props {
+                    match value {
+                        Some(v) => { props.insert(key_id, v.clone()); }
+                        None => { props.remove(&key_id); }
+                    }
+                }
+            }
+        }
 
-        // Write BEGIN record to WAL (for write transactions)
-        if !read_only {
-            let record = WalRecord::new(WalRecordType::Begin, txid, build_begin_payload());
-            let mut pager = self.pager.lock();
-            let mut wal = self.wal_buffer.lock();
-            wal.write_record(&record, &mut pager)?;
+        let node_exists_in_delta = self.local_delta.is_node_created(node_id)
+            || self.local_delta.get_node_delta(node_id).is_some();
+
+        if !node_exists_in_delta {
+            match self.db.snapshot.read().as_ref() {
+                Some(snap) if snap.get_phys_node(node_id).is_none() => return None,
+                None => return None,
+                _ => {}
+            }
         }
 
-        *current_tx = Some(TxState::new(txid, read_only, snapshot_ts));
-        Ok(txid)
+        Some(props)
     }
 
-    /// Commit the current transaction
-    pub fn commit(&self) -> Result<()> {
-        // Take the transaction and release the lock immediately
-        let tx = {
-            let mut current_tx = self.current_tx.lock();
-            current_tx.take().ok_or(RayError::NoTransaction)?
-        };
-
-        if tx.read_only {
-            // Read-only transactions don't need WAL
-            return Ok(());
-        }
+    /// Validate the read set and, if every key is still at the committed
+    /// version this transaction saw, replay the buffered writes and commit.
+    ///
+    /// Takes the database's regular exclusive transaction slot for the
+    /// whole validate-and-replay window (the same slot `begin`/`commit`
+    /// use), so no other regular or optimistic commit can land between this
+    /// transaction's validation check and its write -- that's the "global
+    /// write lock briefly" this type is documented to take. A conflicting
+    /// read set rolls back the (empty) underlying transaction and returns
+    /// `RayError::Conflict` without buffering anything; nothing this
+    /// transaction did is visible to anyone else until this call succeeds.
+    pub fn commit(self) -> Result<()> {
+        self.db.begin_with_durability(false, self.durability)?;
 
-        // Write COMMIT record to WAL
-        let record = WalRecord::new(WalRecordType::Commit, tx.txid, build_commit_payload());
         {
-            let mut pager = self.pager.lock();
-            let mut wal = self.wal_buffer.lock();
-            wal.write_record(&record, &mut pager)?;
-
-            // Flush WAL to disk
-            wal.flush(&mut pager)?;
-            pager.sync()?;
+            let committed_versions = self.db.committed_versions.read();
+            for (&node_id, &observed) in &self.read_set {
+                let current = committed_versions.get(&node_id).copied().unwrap_or(0);
+                if current != observed {
+                    drop(committed_versions);
+                    let _ = self.db.rollback();
+                    return Err(RayError::Conflict(node_id));
+                }
+            }
         }
 
-        // Check if auto-checkpoint should be triggered
-        // Note: We release all locks above first to avoid deadlock during checkpoint
-        if self.auto_checkpoint && self.should_checkpoint(self.checkpoint_threshold) {
-            // Don't trigger if checkpoint is already running
-            if !self.is_checkpoint_running() {
-                // Use background or blocking checkpoint based on config
-                let result = if self.background_checkpoint {
-                    self.background_checkpoint()
-                } else {
-                    self.checkpoint()
-                };
-                
-                // Log errors but don't fail the commit
-                if let Err(e) = result {
-                    eprintln!("Warning: Auto-checkpoint failed: {}", e);
+        for op in &self.ops {
+            let result = match op {
+                OptimisticOp::CreateNode(node_id, key) => {
+                    self.db.create_node_with_id(*node_id, key.as_deref())
+                }
+                OptimisticOp::DeleteNode(node_id) => self.db.delete_node(*node_id),
+                OptimisticOp::AddEdge(src, etype, dst) => self.db.add_edge(*src, *etype, *dst),
+                OptimisticOp::DeleteEdge(src, etype, dst) => {
+                    self.db.delete_edge(*src, *etype, *dst)
+                }
+                OptimisticOp::SetNodeProp(node_id, key_id, value) => {
+                    self.db.set_node_prop(*node_id, *key_id, value.clone())
+                }
+                OptimisticOp::DeleteNodeProp(node_id, key_id) => {
+                    self.db.delete_node_prop(*node_id, *key_id)
+                }
+                OptimisticOp::SetEdgeProp(src, etype, dst, key_id, value) => {
+                    self.db.set_edge_prop(*src, *etype, *dst, *key_id, value.clone())
                 }
+                OptimisticOp::DeleteEdgeProp(src, etype, dst, key_id) => {
+                    self.db.delete_edge_prop(*src, *etype, *dst, *key_id)
+                }
+            };
+            if let Err(e) = result {
+                let _ = self.db.rollback();
+                return Err(e);
             }
         }
 
-        Ok(())
+        self.db.commit()
     }
+}
 
-    /// Rollback the current transaction
-    pub fn rollback(&self) -> Result<()> {
-        let mut current_tx = self.current_tx.lock();
-        let tx = current_tx.take().ok_or(RayError::NoTransaction)?;
+/// Identifies a named subgraph ("column family") registered with
+/// [`SingleFileDB::create_graph`]. 0 is reserved for the default, unnamed
+/// namespace every plain `create_node`/`add_edge` call already uses.
+pub type NamespaceId = u32;
 
-        if tx.read_only {
-            // Read-only transactions don't need WAL
-            return Ok(());
-        }
+/// How many of [`NodeId`]'s high bits tag which namespace a node belongs to.
+/// The default namespace (id 0) never sets these bits, so it keeps using the
+/// full id space it always has; a named namespace's ids are confined to the
+/// remaining low bits, which is why `create_graph` only hands out 2^16 - 1
+/// of them and each holds up to 2^48 nodes.
+const NAMESPACE_ID_BITS: u32 = 16;
 
-        // Write ROLLBACK record to WAL
-        let record = WalRecord::new(WalRecordType::Rollback, tx.txid, build_rollback_payload());
-        let mut pager = self.pager.lock();
-        let mut wal = self.wal_buffer.lock();
-        wal.write_record(&record, &mut pager)?;
+fn namespaced_node_id(namespace: NamespaceId, local_seq: u64) -> NodeId {
+    ((namespace as u64) << (64 - NAMESPACE_ID_BITS)) | local_seq
+}
 
-        // Discard pending writes (rollback doesn't need to be durable)
-        wal.discard_pending();
+/// Per-namespace state backing a named subgraph: its own node-id sequence
+/// and its own edge-type/property-key name tables.
+///
+/// Edge-type and property-key *ids* still come from the database-wide
+/// counters and WAL dictionary (`SingleFileDB::get_or_create_etype`/
+/// `get_or_create_propkey`) -- splitting those would mean a parallel WAL
+/// record format per namespace, which is out of scope here. What's
+/// namespace-private is the *name*: `GraphHandle::get_or_create_etype`
+/// qualifies the name before registering it database-wide, so two
+/// namespaces (or a namespace and the default one) can each use the same
+/// human-readable type name without colliding on the same underlying id.
+///
+/// `snapshot_page_count`/`active_snapshot_gen` are bookkeeping for a future
+/// checkpoint scheduler that skips namespaces with nothing new to persist;
+/// `checkpoint`/`compact_into` don't read them yet and still rewrite the
+/// whole file on every run, so registering more namespaces doesn't yet save
+/// checkpoint work by itself.
+struct GraphNamespace {
+    id: NamespaceId,
+    next_node_seq: AtomicU64,
+    etype_names: RwLock<HashMap<String, ETypeId>>,
+    etype_ids: RwLock<HashMap<ETypeId, String>>,
+    propkey_names: RwLock<HashMap<String, PropKeyId>>,
+    propkey_ids: RwLock<HashMap<PropKeyId, String>>,
+    snapshot_page_count: AtomicU64,
+    active_snapshot_gen: AtomicU64,
+}
 
-        // TODO: Discard delta changes for this transaction
+/// A handle to one named subgraph registered via [`SingleFileDB::create_graph`],
+/// returned by [`SingleFileDB::graph_handle`].
+///
+/// `create_node` allocates from this namespace's own sequence (tagged into
+/// the node id's high bits, see [`namespaced_node_id`]), so two handles for
+/// different namespaces -- or a handle and the default, unnamed namespace --
+/// never hand out the same id. Edges and property writes are otherwise
+/// ordinary `SingleFileDB` operations: once a node id is namespace-tagged,
+/// every existing read/write method already keys off that id transparently.
+pub struct GraphHandle<'a> {
+    db: &'a SingleFileDB,
+    ns: std::sync::Arc<GraphNamespace>,
+}
 
-        Ok(())
+impl<'a> GraphHandle<'a> {
+    /// The namespace id this handle was registered under.
+    pub fn namespace_id(&self) -> NamespaceId {
+        self.ns.id
     }
 
-    /// Check if there's an active transaction
-    pub fn has_transaction(&self) -> bool {
-        self.current_tx.lock().is_some()
+    /// Snapshot page count as of the database's last checkpoint, tracked
+    /// per-namespace for a future checkpoint scheduler -- see
+    /// [`GraphNamespace`].
+    pub fn snapshot_page_count(&self) -> u64 {
+        self.ns.snapshot_page_count.load(Ordering::SeqCst)
     }
 
-    /// Get the current transaction ID (if any)
-    pub fn current_txid(&self) -> Option<TxId> {
-        self.current_tx.lock().as_ref().map(|tx| tx.txid)
+    /// Snapshot generation as of the database's last checkpoint, tracked
+    /// per-namespace for a future checkpoint scheduler -- see
+    /// [`GraphNamespace`].
+    pub fn active_snapshot_gen(&self) -> u64 {
+        self.ns.active_snapshot_gen.load(Ordering::SeqCst)
     }
 
-    // ========================================================================
-    // Write Methods (require active transaction)
-    // ========================================================================
+    /// Create a node scoped to this namespace.
+    pub fn create_node(&self, key: Option<&str>) -> Result<NodeId> {
+        let local_seq = self.ns.next_node_seq.fetch_add(1, Ordering::SeqCst);
+        let node_id = namespaced_node_id(self.ns.id, local_seq);
+        self.db.create_node_with_id(node_id, key)?;
+        Ok(node_id)
+    }
 
-    /// Write a WAL record (internal helper)
-    fn write_wal(&self, record: WalRecord) -> Result<()> {
-        let mut pager = self.pager.lock();
-        let mut wal = self.wal_buffer.lock();
-        wal.write_record(&record, &mut pager)?;
-        Ok(())
+    /// Add an edge between two nodes, which may belong to this namespace or
+    /// any other -- `SingleFileDB` itself doesn't enforce that an edge stays
+    /// within one namespace.
+    pub fn add_edge(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> Result<()> {
+        self.db.add_edge(src, etype, dst)
     }
 
-    /// Get current transaction ID or error
-    fn require_write_tx(&self) -> Result<TxId> {
-        let current_tx = self.current_tx.lock();
-        match current_tx.as_ref() {
-            Some(tx) if !tx.read_only => Ok(tx.txid),
-            Some(_) => Err(RayError::ReadOnly),
-            None => Err(RayError::NoTransaction),
-        }
+    /// Add an edge by type name, scoped to this namespace's own edge-type
+    /// name table (see [`GraphHandle::get_or_create_etype`]).
+    pub fn add_edge_by_name(&self, src: NodeId, etype_name: &str, dst: NodeId) -> Result<()> {
+        let etype = self.get_or_create_etype(etype_name);
+        self.db.add_edge(src, etype, dst)
     }
 
-    /// Create a node
-    pub fn create_node(&self, key: Option<&str>) -> Result<NodeId> {
-        let txid = self.require_write_tx()?;
-        let node_id = self.alloc_node_id();
+    /// Get outgoing edges for a node. A thin wrapper over
+    /// `SingleFileDB::get_out_edges`: nothing keeps an edge inside one
+    /// namespace, so the result includes any edge from `node_id` to a node
+    /// of another namespace.
+    pub fn get_out_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+        self.db.get_out_edges(node_id)
+    }
 
-        // Write WAL record
-        let record = WalRecord::new(
-            WalRecordType::CreateNode,
-            txid,
-            build_create_node_payload(node_id, key),
-        );
-        self.write_wal(record)?;
+    /// Resolve (creating if necessary) an edge-type id under this
+    /// namespace's own name table.
+    pub fn get_or_create_etype(&self, name: &str) -> ETypeId {
+        if let Some(&id) = self.ns.etype_names.read().get(name) {
+            return id;
+        }
+        let qualified = format!("ns{}:{}", self.ns.id, name);
+        let id = self.db.get_or_create_etype(&qualified);
+        self.ns.etype_names.write().insert(name.to_string(), id);
+        self.ns.etype_ids.write().insert(id, name.to_string());
+        id
+    }
 
-        // Update delta
-        self.delta.write().create_node(node_id, key);
+    /// Resolve (creating if necessary) a property-key id under this
+    /// namespace's own name table.
+    pub fn get_or_create_propkey(&self, name: &str) -> PropKeyId {
+        if let Some(&id) = self.ns.propkey_names.read().get(name) {
+            return id;
+        }
+        let qualified = format!("ns{}:{}", self.ns.id, name);
+        let id = self.db.get_or_create_propkey(&qualified);
+        self.ns.propkey_names.write().insert(name.to_string(), id);
+        self.ns.propkey_ids.write().insert(id, name.to_string());
+        id
+    }
 
-        Ok(node_id)
+    /// Set a node property by key name, scoped to this namespace's own
+    /// property-key name table.
+    pub fn set_node_prop_by_name(&self, node_id: NodeId, key_name: &str, value: PropValue) -> Result<()> {
+        let key_id = self.get_or_create_propkey(key_name);
+        self.db.set_node_prop(node_id, key_id, value)
     }
+}
 
-    /// Delete a node
-    pub fn delete_node(&self, node_id: NodeId) -> Result<()> {
-        let txid = self.require_write_tx()?;
+/// Handle passed to the closure given to [`SingleFileDB::with_write_tx`]/
+/// [`SingleFileDB::with_read_tx`], scoped to that one transaction. Derefs to
+/// `&SingleFileDB`, so the closure body calls the same `create_node`/
+/// `get_node_prop`/... methods it would use after a manual `begin` -- there's
+/// no separate vocabulary for "inside a scoped transaction", just a
+/// reminder at the call site that one is open.
+pub struct TxHandle<'a> {
+    db: &'a SingleFileDB,
+}
 
-        // Write WAL record
-        let record = WalRecord::new(
-            WalRecordType::DeleteNode,
-            txid,
-            build_delete_node_payload(node_id),
-        );
-        self.write_wal(record)?;
+impl<'a> std::ops::Deref for TxHandle<'a> {
+    type Target = SingleFileDB;
+    fn deref(&self) -> &SingleFileDB {
+        self.db
+    }
+}
 
-        // Update delta
-        self.delta.write().delete_node(node_id);
+// ============================================================================
+// SingleFileDB Implementation
+// ============================================================================
 
-        Ok(())
+impl SingleFileDB {
+    /// Allocate a new node ID
+    pub fn alloc_node_id(&self) -> NodeId {
+        self.next_node_id.fetch_add(1, Ordering::SeqCst)
     }
 
-    /// Add an edge
-    pub fn add_edge(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> Result<()> {
-        let txid = self.require_write_tx()?;
+    /// Allocate a new label ID
+    pub fn alloc_label_id(&self) -> LabelId {
+        self.next_label_id.fetch_add(1, Ordering::SeqCst)
+    }
 
-        // Write WAL record
-        let record = WalRecord::new(
-            WalRecordType::AddEdge,
-            txid,
-            build_add_edge_payload(src, etype, dst),
-        );
-        self.write_wal(record)?;
+    /// Allocate a new edge type ID
+    pub fn alloc_etype_id(&self) -> ETypeId {
+        self.next_etype_id.fetch_add(1, Ordering::SeqCst)
+    }
 
-        // Update delta
-        self.delta.write().add_edge(src, etype, dst);
+    /// Allocate a new property key ID
+    pub fn alloc_propkey_id(&self) -> PropKeyId {
+        self.next_propkey_id.fetch_add(1, Ordering::SeqCst)
+    }
 
-        Ok(())
+    /// Allocate a new transaction ID
+    pub fn alloc_tx_id(&self) -> TxId {
+        self.next_tx_id.fetch_add(1, Ordering::SeqCst)
     }
 
-    /// Add an edge by type name
-    pub fn add_edge_by_name(&self, src: NodeId, etype_name: &str, dst: NodeId) -> Result<()> {
-        let etype = self.get_or_create_etype(etype_name);
-        self.add_edge(src, etype, dst)
+    /// Capture the node/label/etype/propkey id allocators, for a transaction
+    /// or savepoint to restore on rollback.
+    fn snapshot_id_allocators(&self) -> IdAllocatorSnapshot {
+        IdAllocatorSnapshot {
+            next_node_id: self.next_node_id.load(Ordering::SeqCst),
+            next_label_id: self.next_label_id.load(Ordering::SeqCst),
+            next_etype_id: self.next_etype_id.load(Ordering::SeqCst),
+            next_propkey_id: self.next_propkey_id.load(Ordering::SeqCst),
+        }
     }
 
-    /// Delete an edge
-    pub fn delete_edge(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> Result<()> {
-        let txid = self.require_write_tx()?;
+    /// Reset the id allocators to a previously captured snapshot.
+    fn restore_id_allocators(&self, snapshot: IdAllocatorSnapshot) {
+        self.next_node_id.store(snapshot.next_node_id, Ordering::SeqCst);
+        self.next_label_id.store(snapshot.next_label_id, Ordering::SeqCst);
+        self.next_etype_id.store(snapshot.next_etype_id, Ordering::SeqCst);
+        self.next_propkey_id.store(snapshot.next_propkey_id, Ordering::SeqCst);
+    }
 
-        // Write WAL record
-        let record = WalRecord::new(
-            WalRecordType::DeleteEdge,
-            txid,
-            build_delete_edge_payload(src, etype, dst),
-        );
-        self.write_wal(record)?;
-
-        // Update delta
-        self.delta.write().delete_edge(src, etype, dst);
+    /// Get or create a label ID by name
+    pub fn get_or_create_label(&self, name: &str) -> LabelId {
+        {
+            let names = self.label_names.read();
+            if let Some(&id) = names.get(name) {
+                return id;
+            }
+        }
 
-        Ok(())
+        let id = self.alloc_label_id();
+        {
+            let mut names = self.label_names.write();
+            let mut ids = self.label_ids.write();
+            if let Some(&existing) = names.get(name) {
+                return existing;
+            }
+            names.insert(name.to_string(), id);
+            ids.insert(id, name.to_string());
+        }
+        id
     }
 
-    /// Set a node property
-    pub fn set_node_prop(&self, node_id: NodeId, key_id: PropKeyId, value: PropValue) -> Result<()> {
-        let txid = self.require_write_tx()?;
-
-        // Write WAL record
-        let record = WalRecord::new(
-            WalRecordType::SetNodeProp,
-            txid,
-            build_set_node_prop_payload(node_id, key_id, &value),
-        );
-        self.write_wal(record)?;
-
-        // Update delta
-        self.delta.write().set_node_prop(node_id, key_id, value);
-
-        Ok(())
+    /// Get label ID by name
+    pub fn get_label_id(&self, name: &str) -> Option<LabelId> {
+        self.label_names.read().get(name).copied()
     }
 
-    /// Set a node property by key name
-    pub fn set_node_prop_by_name(&self, node_id: NodeId, key_name: &str, value: PropValue) -> Result<()> {
-        let key_id = self.get_or_create_propkey(key_name);
-        self.set_node_prop(node_id, key_id, value)
+    /// Get label name by ID
+    pub fn get_label_name(&self, id: LabelId) -> Option<String> {
+        self.label_ids.read().get(&id).cloned()
     }
 
-    /// Delete a node property
-    pub fn delete_node_prop(&self, node_id: NodeId, key_id: PropKeyId) -> Result<()> {
-        let txid = self.require_write_tx()?;
-
-        // Write WAL record
-        let record = WalRecord::new(
-            WalRecordType::DelNodeProp,
-            txid,
-            build_del_node_prop_payload(node_id, key_id),
-        );
-        self.write_wal(record)?;
+    /// Get or create an edge type ID by name
+    pub fn get_or_create_etype(&self, name: &str) -> ETypeId {
+        {
+            let names = self.etype_names.read();
+            if let Some(&id) = names.get(name) {
+                return id;
+            }
+        }
 
-        // Update delta
-        self.delta.write().delete_node_prop(node_id, key_id);
+        let id = self.alloc_etype_id();
+        {
+            let mut names = self.etype_names.write();
+            let mut ids = self.etype_ids.write();
+            if let Some(&existing) = names.get(name) {
+                return existing;
+            }
+            names.insert(name.to_string(), id);
+            ids.insert(id, name.to_string());
+        }
+        id
+    }
 
-        Ok(())
+    /// Get edge type ID by name
+    pub fn get_etype_id(&self, name: &str) -> Option<ETypeId> {
+        self.etype_names.read().get(name).copied()
     }
 
-    /// Define a new label (writes to WAL for durability)
-    pub fn define_label(&self, name: &str) -> Result<LabelId> {
-        let txid = self.require_write_tx()?;
+    /// Get edge type name by ID
+    pub fn get_etype_name(&self, id: ETypeId) -> Option<String> {
+        self.etype_ids.read().get(&id).cloned()
+    }
 
-        // Check if already exists
-        if let Some(id) = self.get_label_id(name) {
-            return Ok(id);
+    /// Get or create a property key ID by name
+    pub fn get_or_create_propkey(&self, name: &str) -> PropKeyId {
+        {
+            let names = self.propkey_names.read();
+            if let Some(&id) = names.get(name) {
+                return id;
+            }
         }
 
-        let label_id = self.alloc_label_id();
-
-        // Write WAL record
-        let record = WalRecord::new(
-            WalRecordType::DefineLabel,
-            txid,
-            build_define_label_payload(label_id, name),
-        );
-        self.write_wal(record)?;
-
-        // Update schema maps
+        let id = self.alloc_propkey_id();
         {
-            let mut names = self.label_names.write();
-            let mut ids = self.label_ids.write();
-            names.insert(name.to_string(), label_id);
-            ids.insert(label_id, name.to_string());
+            let mut names = self.propkey_names.write();
+            let mut ids = self.propkey_ids.write();
+            if let Some(&existing) = names.get(name) {
+                return existing;
+            }
+            names.insert(name.to_string(), id);
+            ids.insert(id, name.to_string());
         }
+        id
+    }
 
-        // Update delta
-        self.delta.write().define_label(label_id, name);
+    /// Get property key ID by name
+    pub fn get_propkey_id(&self, name: &str) -> Option<PropKeyId> {
+        self.propkey_names.read().get(name).copied()
+    }
 
-        Ok(label_id)
+    /// Get property key name by ID
+    pub fn get_propkey_name(&self, id: PropKeyId) -> Option<String> {
+        self.propkey_ids.read().get(&id).cloned()
     }
 
-    /// Define a new edge type (writes to WAL for durability)
-    pub fn define_etype(&self, name: &str) -> Result<ETypeId> {
-        let txid = self.require_write_tx()?;
+    /// Check if a node exists
+    pub fn node_exists(&self, node_id: NodeId) -> bool {
+        let delta = self.delta.read();
 
-        // Check if already exists
-        if let Some(id) = self.get_etype_id(name) {
-            return Ok(id);
+        if delta.is_node_deleted(node_id) {
+            return false;
         }
 
-        let etype_id = self.alloc_etype_id();
-
-        // Write WAL record
-        let record = WalRecord::new(
-            WalRecordType::DefineEtype,
-            txid,
-            build_define_etype_payload(etype_id, name),
-        );
-        self.write_wal(record)?;
-
-        // Update schema maps
-        {
-            let mut names = self.etype_names.write();
-            let mut ids = self.etype_ids.write();
-            names.insert(name.to_string(), etype_id);
-            ids.insert(etype_id, name.to_string());
+        if delta.is_node_created(node_id) {
+            return true;
         }
 
-        // Update delta
-        self.delta.write().define_etype(etype_id, name);
+        // Check snapshot
+        if let Some(ref snapshot) = *self.snapshot.read() {
+            return snapshot.has_node(node_id);
+        }
 
-        Ok(etype_id)
+        false
     }
 
-    /// Define a new property key (writes to WAL for durability)
-    pub fn define_propkey(&self, name: &str) -> Result<PropKeyId> {
-        let txid = self.require_write_tx()?;
+    /// Check if an edge exists
+    pub fn edge_exists(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> bool {
+        let delta = self.delta.read();
 
-        // Check if already exists
-        if let Some(id) = self.get_propkey_id(name) {
-            return Ok(id);
+        if delta.is_edge_deleted(src, etype, dst) {
+            return false;
         }
 
-        let propkey_id = self.alloc_propkey_id();
-
-        // Write WAL record
-        let record = WalRecord::new(
-            WalRecordType::DefinePropkey,
-            txid,
-            build_define_propkey_payload(propkey_id, name),
-        );
-        self.write_wal(record)?;
-
-        // Update schema maps
-        {
-            let mut names = self.propkey_names.write();
-            let mut ids = self.propkey_ids.write();
-            names.insert(name.to_string(), propkey_id);
-            ids.insert(propkey_id, name.to_string());
+        if delta.is_edge_added(src, etype, dst) {
+            return true;
         }
 
-        // Update delta
-        self.delta.write().define_propkey(propkey_id, name);
+        // Check snapshot
+        if let Some(ref snapshot) = *self.snapshot.read() {
+            if let (Some(src_phys), Some(dst_phys)) =
+                (snapshot.get_phys_node(src), snapshot.get_phys_node(dst))
+            {
+                return snapshot.has_edge(src_phys, etype, dst_phys);
+            }
+        }
 
-        Ok(propkey_id)
+        false
     }
 
     // ========================================================================
-    // WAL Statistics
+    // Transaction Methods
     // ========================================================================
 
-    /// Get WAL buffer statistics
-    pub fn wal_stats(&self) -> crate::core::wal::buffer::WalBufferStats {
-        self.wal_buffer.lock().stats()
+    /// Begin a new transaction using the database's default durability level.
+    pub fn begin(&self, read_only: bool) -> Result<TxId> {
+        self.begin_with_durability(read_only, self.default_durability)
     }
 
-    // ========================================================================
-    // Checkpoint / Compaction
-    // ========================================================================
-
-    /// Perform a checkpoint - merge snapshot + delta into new snapshot
+    /// Begin a new transaction that commits with the given [`Durability`]
+    /// instead of the database's default.
     ///
-    /// This:
-    /// 1. Collects all graph data from snapshot + delta
-    /// 2. Builds a new snapshot in memory
-    /// 3. Writes the new snapshot to disk (after WAL)
-    /// 4. Updates header to point to new snapshot
-    /// 5. Clears WAL and delta
-    pub fn checkpoint(&self) -> Result<()> {
-        if self.read_only {
+    /// Read-only transactions are never serialized against each other or
+    /// against an in-progress writer -- every read method already consults
+    /// the live `self.delta`/`self.snapshot` rather than a pinned snapshot,
+    /// so a `begin(true)` on one thread never has to wait for `begin(false)`
+    /// on another. A second concurrent *write* transaction started through
+    /// *this* method is still rejected with `RayError::TransactionInProgress`:
+    /// writers that go through `begin`/`commit` mutate the single shared
+    /// `self.delta` overlay in place, so only one of them can be open at a
+    /// time. Callers who need several writers in flight at once should use
+    /// [`SingleFileDB::begin_optimistic`] instead, which gives each writer
+    /// its own isolated working set and only serializes briefly at commit to
+    /// validate and replay -- `begin`/`commit` themselves are not getting an
+    /// MVCC redesign; they stay single-writer by design.
+    pub fn begin_with_durability(&self, read_only: bool, durability: Durability) -> Result<TxId> {
+        if self.read_only && !read_only {
             return Err(RayError::ReadOnly);
         }
 
-        // Don't checkpoint with active transaction
-        if self.has_transaction() {
+        if ACTIVE_READ_TX.with(|active| active.get()).is_some() {
+            // Either a second read-only `begin` on a thread that already has
+            // one open (rejected below just like before), or -- the bug this
+            // guards against -- a write `begin` on a thread that never
+            // committed/rolled back its own read-only transaction. Letting
+            // the write through would leave this thread's `ACTIVE_READ_TX`
+            // set, and `commit`/`rollback` both check that thread-local
+            // *first*, so they'd silently service the stale read transaction
+            // instead of the write one -- permanently wedging the single
+            // writer slot in `current_tx`.
             return Err(RayError::TransactionInProgress);
         }
 
-        // Collect all graph data
-        let (nodes, edges, labels, etypes, propkeys) = self.collect_graph_data();
+        let txid = self.alloc_tx_id();
+        let snapshot_ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
 
-        // Get current header state
-        let header = self.header.read().clone();
-        let new_gen = header.active_snapshot_gen + 1;
+        if read_only {
+            let snapshot_version = self.next_version.load(Ordering::SeqCst);
+            let start_delta = self.delta.read().clone();
+            let start_ids = self.snapshot_id_allocators();
+            self.read_txs.lock().insert(
+                txid,
+                TxState::new(
+                    txid,
+                    true,
+                    snapshot_ts,
+                    durability,
+                    snapshot_version,
+                    start_delta,
+                    start_ids,
+                ),
+            );
+            ACTIVE_READ_TX.with(|active| active.set(Some(txid)));
+            self.metrics.record_tx_begin();
+            return Ok(txid);
+        }
 
-        // Build new snapshot in memory
-        let snapshot_buffer = build_snapshot_to_memory(SnapshotBuildInput {
-            generation: new_gen,
-            nodes,
-            edges,
-            labels,
-            etypes,
-            propkeys,
-            compression: None, // TODO: Add compression support
-        })?;
-
-        // Calculate where to place new snapshot (after WAL)
-        let wal_end_page = header.wal_start_page + header.wal_page_count;
-        let new_snapshot_start_page = wal_end_page;
-        let new_snapshot_page_count = pages_to_store(snapshot_buffer.len(), header.page_size as usize) as u64;
+        let mut current_tx = self.current_tx.lock();
+        if current_tx.is_some() {
+            return Err(RayError::TransactionInProgress);
+        }
 
-        // Write snapshot to file
+        // Write BEGIN record to WAL
+        let record = WalRecord::new(WalRecordType::Begin, txid, build_begin_payload());
         {
             let mut pager = self.pager.lock();
-            self.write_snapshot_pages(&mut pager, new_snapshot_start_page as u32, &snapshot_buffer, header.page_size as usize)?;
+            let mut wal = self.wal_buffer.lock();
+            wal.write_record(&record, &mut pager)?;
         }
 
-        // Update header
+        let snapshot_version = self.next_version.load(Ordering::SeqCst);
+        let start_delta = self.delta.read().clone();
+        let start_ids = self.snapshot_id_allocators();
+        *current_tx = Some(TxState::new(
+            txid,
+            read_only,
+            snapshot_ts,
+            durability,
+            snapshot_version,
+            start_delta,
+            start_ids,
+        ));
+        self.metrics.record_tx_begin();
+        Ok(txid)
+    }
+
+    /// Commit the current transaction.
+    ///
+    /// If this thread has an open read-only transaction (from `begin(true)`
+    /// on this same thread), that's what gets committed -- a no-op besides
+    /// running its queued `on_commit` callbacks, since reads never touch
+    /// the WAL. Otherwise this commits the one in-progress write
+    /// transaction, if any.
+    ///
+    /// Before writing the WAL `Commit` record, this optimistically checks
+    /// every node the transaction wrote against `committed_versions`: if one
+    /// was committed by another transaction after this one's snapshot was
+    /// taken, the commit is rejected with `RayError::Conflict` rather than
+    /// silently overwriting that write.
+    pub fn commit(&self) -> Result<()> {
+        let commit_started = std::time::Instant::now();
+
+        if let Some(txid) = ACTIVE_READ_TX.with(|active| active.take()) {
+            let tx = self
+                .read_txs
+                .lock()
+                .remove(&txid)
+                .ok_or(RayError::NoTransaction)?;
+            self.metrics.record_tx_commit();
+            self.perf.record_commit(commit_started.elapsed(), 0);
+            for callback in tx.on_commit {
+                callback();
+            }
+            return Ok(());
+        }
+
+        // Take the transaction and release the lock immediately
+        let tx = {
+            let mut current_tx = self.current_tx.lock();
+            current_tx.take().ok_or(RayError::NoTransaction)?
+        };
+
+        if tx.read_only {
+            // Read-only transactions don't need WAL
+            self.metrics.record_tx_commit();
+            self.perf.record_commit(commit_started.elapsed(), 0);
+            for callback in tx.on_commit {
+                callback();
+            }
+            return Ok(());
+        }
+
+        // Optimistic conflict check: fail if any node this transaction wrote
+        // was committed after this transaction began.
+        {
+            let committed_versions = self.committed_versions.read();
+            for &node_id in &tx.write_set {
+                if let Some(&committed_at) = committed_versions.get(&node_id) {
+                    if committed_at >= tx.snapshot_version {
+                        return Err(RayError::Conflict(node_id));
+                    }
+                }
+            }
+        }
+
+        // Write COMMIT record to WAL
+        let record = WalRecord::new(WalRecordType::Commit, tx.txid, build_commit_payload());
         {
             let mut pager = self.pager.lock();
-            let mut wal_buffer = self.wal_buffer.lock();
-            let mut header = self.header.write();
+            let mut wal = self.wal_buffer.lock();
+            wal.write_record(&record, &mut pager)?;
+            self.metrics.record_wal_write(record.payload.len());
+
+            // How hard we push this commit to disk before returning depends on
+            // the transaction's durability level. Because `flush`/`sync` act on
+            // the WAL buffer's *entire* pending contents (not just this
+            // record), an `Immediate` commit following a run of `Eventual`/
+            // `None` ones still flushes and fsyncs everything they left
+            // buffered -- durability levels never reorder or split a prefix of
+            // already-issued commits.
+            //
+            // `Immediate`'s group-commit join happens below, *outside* this
+            // block, specifically so it isn't still holding these two locks
+            // while it runs: since every commit needs them just to get this
+            // far, a follower blocked on `pager`/`wal_buffer` could never
+            // reach `join()` to actually coalesce with a leader that's
+            // holding them through its coalesce window -- it would just
+            // queue up to lead its own redundant round right behind it.
+            if tx.durability == Durability::Eventual {
+                wal.flush(&mut pager)?;
+                self.metrics.record_wal_flush();
+            }
+        }
 
-            // Update header fields
-            header.active_snapshot_gen = new_gen;
-            header.snapshot_start_page = new_snapshot_start_page;
-            header.snapshot_page_count = new_snapshot_page_count;
-            header.db_size_pages = new_snapshot_start_page + new_snapshot_page_count;
-            header.max_node_id = self.next_node_id.load(Ordering::SeqCst).saturating_sub(1);
-            header.next_tx_id = self.next_tx_id.load(Ordering::SeqCst);
+        if tx.durability == Durability::Immediate {
+            // Group commit: if another thread is already leading a
+            // coalescing round, this just waits for its result instead of
+            // running a redundant flush + fsync. Only the thread that ends
+            // up leading a round acquires `pager`/`wal_buffer` (inside the
+            // closure below) -- followers wait on a condvar without taking
+            // either lock, so they're actually free to pile up behind a
+            // leader's coalesce window instead of contending with it for
+            // the same mutexes it needs to run the sync.
+            self.group_commit.join(GROUP_COMMIT_COALESCE_WINDOW, || {
+                let mut pager = self.pager.lock();
+                let mut wal = self.wal_buffer.lock();
+                wal.flush(&mut pager)?;
+                self.metrics.record_wal_flush();
+                pager.sync()
+            })?;
+        }
 
-            // Reset WAL
-            header.wal_head = 0;
-            header.wal_tail = 0;
-            wal_buffer.reset();
+        // Stamp every node this transaction touched with a fresh commit
+        // version so later transactions' conflict checks can see it.
+        let commit_version = self.next_version.fetch_add(1, Ordering::SeqCst);
+        {
+            let mut committed_versions = self.committed_versions.write();
+            for &node_id in &tx.write_set {
+                committed_versions.insert(node_id, commit_version);
+            }
+        }
 
-            // Increment change counter
-            header.change_counter += 1;
+        // Replay this transaction's buffered vector sets/deletes into the
+        // live vector stores now that its COMMIT record is durable. See
+        // `TxState::pending_vectors` for why these are buffered raw instead
+        // of staged through `delta` like node/edge props are.
+        if !tx.pending_vectors.is_empty() {
+            self.apply_pending_vectors(&tx.pending_vectors)?;
+        }
 
-            // Write header to disk
-            let header_bytes = header.serialize_to_page();
-            pager.write_page(0, &header_bytes)?;
-            pager.sync()?;
+        // File this commit's delta into the bounded history log so
+        // `unrecord` can undo it later. Transactions with no ops (e.g. only
+        // schema defines, which aren't tracked per-node) still get an entry
+        // with an empty op list -- harmless, `unrecord` just has nothing to
+        // invert.
+        {
+            let mut history = self.history.lock();
+            history.push_back(CommitHistoryEntry {
+                txid: tx.txid,
+                ops: tx.ops.clone(),
+                touched: tx.write_set.clone(),
+            });
+            while history.len() > MAX_COMMIT_HISTORY {
+                history.pop_front();
+            }
         }
 
-        // Clear delta
-        self.delta.write().clear();
+        // Check if auto-checkpoint should be triggered
+        // Note: We release all locks above first to avoid deadlock during checkpoint
+        if self.auto_checkpoint && self.should_checkpoint(self.checkpoint_threshold) {
+            // Don't trigger if checkpoint is already running
+            if !self.is_checkpoint_running() {
+                // checkpoint_incremental decides for itself whether this
+                // crossing warrants a real checkpoint or just another
+                // pending layer marker.
+                if let Err(e) = self.checkpoint_incremental() {
+                    eprintln!("Warning: Auto-checkpoint failed: {}", e);
+                }
+            }
+        }
 
-        // Reload the new snapshot
-        self.reload_snapshot()?;
+        self.metrics.record_tx_commit();
+        self.perf.record_commit(commit_started.elapsed(), record.payload.len() as u64);
+
+        // Run after every internal lock above has been released, so a
+        // callback is free to call back into `self` (e.g. to begin its own
+        // transaction) without deadlocking against the auto-checkpoint that
+        // just ran lock-free above.
+        for callback in tx.on_commit {
+            callback();
+        }
 
         Ok(())
     }
 
-    /// Reload snapshot from disk after checkpoint
-    fn reload_snapshot(&self) -> Result<()> {
-        let header = self.header.read();
-        
-        if header.snapshot_page_count == 0 {
-            // No snapshot to load
-            *self.snapshot.write() = None;
+    /// Rollback the current transaction. Just like `commit`, this rolls
+    /// back this thread's own read-only transaction if it has one open,
+    /// otherwise the one in-progress write transaction.
+    pub fn rollback(&self) -> Result<()> {
+        if let Some(txid) = ACTIVE_READ_TX.with(|active| active.take()) {
+            self.read_txs
+                .lock()
+                .remove(&txid)
+                .ok_or(RayError::NoTransaction)?;
+            self.metrics.record_tx_rollback();
             return Ok(());
         }
 
-        // Calculate snapshot offset in bytes
-        let snapshot_offset = (header.snapshot_start_page * header.page_size as u64) as usize;
-        
-        // Re-mmap the file and parse snapshot
-        let pager = self.pager.lock();
-        let new_snapshot = SnapshotData::parse_at_offset(
-            std::sync::Arc::new(unsafe {
-                // Safety: We're creating an owned Mmap from the file
-                // This is safe because the pager keeps the file open
-                memmap2::Mmap::map(pager.file())?
-            }),
-            snapshot_offset,
-            &crate::core::snapshot::reader::ParseSnapshotOptions::default(),
-        )?;
+        let mut current_tx = self.current_tx.lock();
+        let tx = current_tx.take().ok_or(RayError::NoTransaction)?;
 
-        // Update the snapshot
-        *self.snapshot.write() = Some(new_snapshot);
+        if tx.read_only {
+            // Read-only transactions don't need WAL
+            self.metrics.record_tx_rollback();
+            return Ok(());
+        }
+
+        // Undo every delta mutation and id allocation the transaction made,
+        // restoring exactly what `begin` captured. This runs before the WAL
+        // `Rollback` record below so a crash mid-rollback still replays to
+        // the same (unmodified) state on recovery.
+        *self.delta.write() = tx.start_delta;
+        self.restore_id_allocators(tx.start_ids);
+
+        // Write ROLLBACK record to WAL
+        let record = WalRecord::new(WalRecordType::Rollback, tx.txid, build_rollback_payload());
+        let mut pager = self.pager.lock();
+        let mut wal = self.wal_buffer.lock();
+        wal.write_record(&record, &mut pager)?;
+        self.metrics.record_wal_write(record.payload.len());
+
+        // Discard pending writes (rollback doesn't need to be durable)
+        wal.discard_pending();
 
+        self.metrics.record_tx_rollback();
         Ok(())
     }
 
-    // ========================================================================
-    // Background Checkpoint (Non-Blocking)
-    // ========================================================================
-
-    /// Check if a background checkpoint is currently running
-    pub fn is_checkpoint_running(&self) -> bool {
-        let status = *self.checkpoint_status.lock();
-        matches!(status, CheckpointStatus::Running | CheckpointStatus::Completing)
+    /// Check if there's an active transaction -- either this thread's own
+    /// read-only transaction, or the one in-progress write transaction
+    /// (shared across all threads, since there can only be one).
+    pub fn has_transaction(&self) -> bool {
+        ACTIVE_READ_TX.with(|active| active.get().is_some()) || self.current_tx.lock().is_some()
     }
 
-    /// Get current checkpoint status
-    pub fn checkpoint_status(&self) -> CheckpointStatus {
-        *self.checkpoint_status.lock()
+    /// Get the current transaction ID (if any), preferring this thread's own
+    /// read-only transaction over the in-progress write transaction.
+    pub fn current_txid(&self) -> Option<TxId> {
+        if let Some(txid) = ACTIVE_READ_TX.with(|active| active.get()) {
+            return Some(txid);
+        }
+        self.current_tx.lock().as_ref().map(|tx| tx.txid)
     }
 
-    /// Trigger a background checkpoint (non-blocking)
+    /// Queue `f` to run once the current transaction's commit is durable --
+    /// after its WAL `Commit` record is written (and flushed/synced per its
+    /// [`Durability`]) and every internal lock (`pager`, `wal_buffer`,
+    /// `current_tx`) has been released, so `f` is free to call back into
+    /// `self` without deadlocking. Queued callbacks run in registration
+    /// order; if the transaction rolls back instead, they're dropped unrun.
     ///
-    /// This switches writes to secondary WAL region immediately and starts
-    /// the checkpoint process. Writes can continue while checkpoint is running.
+    /// Meant for secondary-index maintenance, cache invalidation, or
+    /// reindexing that should happen atomically with durability -- anything
+    /// that only makes sense once the write it depends on can't disappear.
+    /// Returns `RayError::NoTransaction` if there's no transaction open.
     ///
-    /// Steps:
-    /// 1. Switch writes to secondary WAL region
-    /// 2. Set checkpointInProgress flag (for crash recovery)
-    /// 3. Build new snapshot from primary WAL + current snapshot + delta
-    /// 4. Write new snapshot to disk
-    /// 5. Merge secondary into primary, update header
-    /// 6. Clear checkpointInProgress flag
-    pub fn background_checkpoint(&self) -> Result<()> {
-        if self.read_only {
-            return Err(RayError::ReadOnly);
+    /// Queues against this thread's own read-only transaction if it has one
+    /// open, otherwise the in-progress write transaction.
+    pub fn on_commit(&self, f: impl FnOnce() + Send + 'static) -> Result<()> {
+        if let Some(txid) = ACTIVE_READ_TX.with(|active| active.get()) {
+            let mut read_txs = self.read_txs.lock();
+            let tx = read_txs.get_mut(&txid).ok_or(RayError::NoTransaction)?;
+            tx.on_commit.push(Box::new(f));
+            return Ok(());
         }
+        let mut current_tx = self.current_tx.lock();
+        let tx = current_tx.as_mut().ok_or(RayError::NoTransaction)?;
+        tx.on_commit.push(Box::new(f));
+        Ok(())
+    }
 
-        // Check if already running
-        {
-            let mut status = self.checkpoint_status.lock();
-            match *status {
-                CheckpointStatus::Running => {
-                    // Already running, just return
-                    return Ok(());
-                }
-                CheckpointStatus::Completing => {
-                    // Wait for completion by returning
-                    return Ok(());
-                }
-                CheckpointStatus::Idle => {
-                    *status = CheckpointStatus::Running;
-                }
+    // ========================================================================
+    // Scoped Transactions
+    // ========================================================================
+
+    /// Run `f` in a write transaction started with the database's default
+    /// durability, committing on `Ok` and rolling back on `Err` or panic. See
+    /// [`with_write_tx_durability`](Self::with_write_tx_durability) for full
+    /// behavior.
+    pub fn with_write_tx<T>(&self, f: impl FnOnce(&TxHandle) -> Result<T>) -> Result<T> {
+        self.with_write_tx_durability(self.default_durability, f)
+    }
+
+    /// Run `f` in a write transaction, guaranteeing the single `current_tx`
+    /// slot is released no matter how `f` exits -- `?` inside the closure
+    /// works exactly as it would in a normal function body.
+    ///
+    /// `f` gets a [`TxHandle`] rather than `&self` directly so call sites
+    /// read as "this runs inside a transaction" without needing a separate
+    /// manual `begin`. On `Ok(value)`, the transaction is committed and
+    /// `value` is returned; a plain `Err` rolls back and propagates the
+    /// error; an unwinding panic is caught just long enough to roll back
+    /// the transaction (so it doesn't leave `current_tx` permanently
+    /// occupied -- every later `begin` would otherwise fail with
+    /// `TransactionInProgress`) before being resumed.
+    pub fn with_write_tx_durability<T>(
+        &self,
+        durability: Durability,
+        f: impl FnOnce(&TxHandle) -> Result<T>,
+    ) -> Result<T> {
+        self.begin_with_durability(false, durability)?;
+        self.run_scoped_tx(f)
+    }
+
+    /// Run `f` in a read-only transaction, committing on `Ok` and rolling
+    /// back on `Err` or panic -- otherwise identical to
+    /// [`with_write_tx`](Self::with_write_tx). Like every read-only
+    /// transaction it pins no view: reads inside `f` see the live delta and
+    /// snapshot. [`snapshot`](Self::snapshot) gives a pinned view.
+    pub fn with_read_tx<T>(&self, f: impl FnOnce(&TxHandle) -> Result<T>) -> Result<T> {
+        self.begin(true)?;
+        self.run_scoped_tx(f)
+    }
+
+    /// Shared tail of `with_write_tx*`/`with_read_tx`: assumes `begin` has
+    /// already opened the transaction `f` runs against, and takes it from
+    /// there through commit/rollback.
+    fn run_scoped_tx<T>(&self, f: impl FnOnce(&TxHandle) -> Result<T>) -> Result<T> {
+        let handle = TxHandle { db: self };
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&handle))) {
+            Ok(Ok(value)) => {
+                self.commit()?;
+                Ok(value)
+            }
+            Ok(Err(e)) => {
+                let _ = self.rollback();
+                Err(e)
+            }
+            Err(payload) => {
+                let _ = self.rollback();
+                std::panic::resume_unwind(payload);
             }
         }
+    }
 
-        // Step 1: Switch writes to secondary region
-        {
-            let mut pager = self.pager.lock();
-            let mut wal_buffer = self.wal_buffer.lock();
-            let mut header = self.header.write();
+    // ========================================================================
+    // History / Unrecord
+    // ========================================================================
 
-            // Switch WAL to secondary region
-            wal_buffer.switch_to_secondary();
+    /// Revert a past committed transaction by computing and applying its
+    /// inverse delta, pulled from the bounded log `commit` files every
+    /// transaction into (see [`MAX_COMMIT_HISTORY`]).
+    ///
+    /// Before touching anything, checks every *later* committed transaction
+    /// still in the log: if one of them wrote to a node `txid` created, it
+    /// depends on `txid`'s existence, and `unrecord` refuses with
+    /// `RayError::ChangeIsDependedUpon(txid)`. Pass `cascade: true` to
+    /// instead unrecord those dependents first, newest to oldest, before
+    /// reverting `txid` itself.
+    ///
+    /// The revert itself runs as an ordinary write transaction (oldest op
+    /// undone last), so it produces its own WAL records and its own history
+    /// entry -- undoing a commit is itself a commit, and can in turn be
+    /// unrecorded.
+    pub fn unrecord(&self, txid: TxId, cascade: bool) -> Result<()> {
+        if self.has_transaction() {
+            return Err(RayError::TransactionInProgress);
+        }
 
-            // Update header to reflect the switch
-            header.active_wal_region = 1;
-            header.checkpoint_in_progress = 1;
-            header.wal_primary_head = wal_buffer.primary_head();
-            header.wal_secondary_head = wal_buffer.secondary_head();
-            header.change_counter += 1;
+        let index = {
+            let history = self.history.lock();
+            history
+                .iter()
+                .position(|entry| entry.txid == txid)
+                .ok_or(RayError::UnknownTransaction(txid))?
+        };
 
-            // Write header to disk
-            let header_bytes = header.serialize_to_page();
-            pager.write_page(0, &header_bytes)?;
-            pager.sync()?;
-        }
+        let created: std::collections::HashSet<NodeId> = {
+            let history = self.history.lock();
+            history[index]
+                .ops
+                .iter()
+                .filter_map(|op| match op {
+                    HistoryOp::CreateNode(id) => Some(*id),
+                    _ => None,
+                })
+                .collect()
+        };
 
-        // Step 2-4: Build and write snapshot, get the info
-        let snapshot_info = match self.build_and_write_snapshot() {
-            Ok(info) => info,
-            Err(e) => {
-                // On error, try to recover
-                self.recover_from_checkpoint_error();
-                return Err(e);
+        let dependents: Vec<TxId> = {
+            let history = self.history.lock();
+            history
+                .iter()
+                .skip(index + 1)
+                .filter(|entry| entry.touched.iter().any(|id| created.contains(id)))
+                .map(|entry| entry.txid)
+                .collect()
+        };
+
+        if !dependents.is_empty() {
+            if !cascade {
+                return Err(RayError::ChangeIsDependedUpon(txid));
+            }
+            // Newest first, so each dependent is still present in the log
+            // (and itself dependency-checked) when we get to it.
+            for dependent in dependents.into_iter().rev() {
+                self.unrecord(dependent, true)?;
             }
+        }
+
+        // Re-locate `txid`: a cascaded unrecord above may have shifted or
+        // removed entries around it (though never `txid` itself, which no
+        // dependent can depend on).
+        let ops = {
+            let history = self.history.lock();
+            let index = history
+                .iter()
+                .position(|entry| entry.txid == txid)
+                .ok_or(RayError::UnknownTransaction(txid))?;
+            history[index].ops.clone()
         };
 
-        // Step 5: Complete the checkpoint
-        self.complete_background_checkpoint(snapshot_info)?;
+        self.begin_with_durability(false, Durability::Immediate)?;
+        for op in ops.iter().rev() {
+            let result = match op {
+                &HistoryOp::CreateNode(node_id) => self.delete_node(node_id),
+                &HistoryOp::DeleteNode(node_id) => self.undelete_node(node_id),
+                &HistoryOp::AddEdge(src, etype, dst) => self.delete_edge(src, etype, dst),
+                &HistoryOp::DeleteEdge(src, etype, dst) => self.add_edge(src, etype, dst),
+                &HistoryOp::SetNodeProp(node_id, key_id, ref prior) => match prior {
+                    Some(value) => self.set_node_prop(node_id, key_id, value.clone()),
+                    None => self.delete_node_prop(node_id, key_id),
+                },
+                &HistoryOp::SetEdgeProp(src, etype, dst, key_id, ref prior) => match prior {
+                    Some(value) => self.set_edge_prop(src, etype, dst, key_id, value.clone()),
+                    None => self.delete_edge_prop(src, etype, dst, key_id),
+                },
+            };
+            if let Err(e) = result {
+                let _ = self.rollback();
+                return Err(e);
+            }
+        }
+        self.commit()?;
 
+        self.history.lock().retain(|entry| entry.txid != txid);
         Ok(())
     }
 
-    /// Build and write the snapshot (called during background checkpoint)
-    /// Returns (new_gen, new_snapshot_start_page, new_snapshot_page_count)
-    fn build_and_write_snapshot(&self) -> Result<(u64, u64, u64)> {
-        // Collect all graph data (reads from snapshot + delta)
-        let (nodes, edges, labels, etypes, propkeys) = self.collect_graph_data();
+    // ========================================================================
+    // Savepoints
+    // ========================================================================
 
-        // Get current header state
-        let header = self.header.read().clone();
-        let new_gen = header.active_snapshot_gen + 1;
+    /// Record a savepoint at the current point in the active transaction.
+    ///
+    /// Savepoints nest: rolling back to an outer one implicitly invalidates
+    /// every savepoint taken after it.
+    pub fn savepoint(&self) -> Result<SavepointId> {
+        let mut current_tx = self.current_tx.lock();
+        let tx = current_tx.as_mut().ok_or(RayError::NoTransaction)?;
+        if tx.read_only {
+            return Err(RayError::ReadOnly);
+        }
 
-        // Build new snapshot in memory
-        let snapshot_buffer = build_snapshot_to_memory(SnapshotBuildInput {
-            generation: new_gen,
-            nodes,
-            edges,
-            labels,
-            etypes,
-            propkeys,
-            compression: None,
-        })?;
-
-        // Calculate where to place new snapshot (after WAL)
-        let wal_end_page = header.wal_start_page + header.wal_page_count;
-        let new_snapshot_start_page = wal_end_page;
-        let new_snapshot_page_count = pages_to_store(snapshot_buffer.len(), header.page_size as usize) as u64;
+        let id = SavepointId(tx.next_savepoint_id);
+        tx.next_savepoint_id += 1;
+        let delta_mark = self.delta.write().savepoint();
+        let ids = self.snapshot_id_allocators();
+        tx.savepoints.push(Savepoint { id, delta_mark, ids });
+        Ok(id)
+    }
 
-        // Write snapshot to file
-        {
-            let mut pager = self.pager.lock();
-            self.write_snapshot_pages(&mut pager, new_snapshot_start_page as u32, &snapshot_buffer, header.page_size as usize)?;
-        }
+    /// Discard all delta mutations and id allocations recorded after `id`,
+    /// restoring the delta and allocators to exactly how they looked when
+    /// `id` was taken, except for edge properties: edge-property writes made
+    /// after `id`, and edge properties dropped by an edge deletion after
+    /// it, are not undone. `id` remains valid afterwards (it can be rolled back
+    /// to again), but any savepoint nested under it is invalidated.
+    pub fn rollback_to(&self, id: SavepointId) -> Result<()> {
+        let mut current_tx = self.current_tx.lock();
+        let tx = current_tx.as_mut().ok_or(RayError::NoTransaction)?;
 
-        Ok((new_gen, new_snapshot_start_page, new_snapshot_page_count))
+        let index = tx
+            .savepoints
+            .iter()
+            .position(|sp| sp.id == id)
+            .ok_or(RayError::InvalidSavepoint)?;
+
+        // Undo the delta's journal back to this savepoint's mark and restore
+        // the allocators, then drop every savepoint recorded after it --
+        // they described states that no longer exist. `DeltaState::rollback_to`
+        // also drops its own marks past this point, so the two stay in sync.
+        let delta_mark = tx.savepoints[index].delta_mark;
+        let ids = tx.savepoints[index].ids;
+        tx.savepoints.truncate(index + 1);
+        self.delta.write().rollback_to(delta_mark);
+        self.restore_id_allocators(ids);
+        Ok(())
     }
 
-    /// Complete the background checkpoint
-    fn complete_background_checkpoint(&self, snapshot_info: (u64, u64, u64)) -> Result<()> {
-        let (new_gen, new_snapshot_start_page, new_snapshot_page_count) = snapshot_info;
-        
-        // Mark as completing (brief lock period)
-        *self.checkpoint_status.lock() = CheckpointStatus::Completing;
+    /// Release a savepoint without rolling back to it. Once released, `id`
+    /// (and anything nested under it) can no longer be rolled back to.
+    pub fn release(&self, id: SavepointId) -> Result<()> {
+        let mut current_tx = self.current_tx.lock();
+        let tx = current_tx.as_mut().ok_or(RayError::NoTransaction)?;
 
-        // Merge secondary records into primary and update header
-        {
-            let mut pager = self.pager.lock();
-            let mut wal_buffer = self.wal_buffer.lock();
-            let mut header = self.header.write();
+        let index = tx
+            .savepoints
+            .iter()
+            .position(|sp| sp.id == id)
+            .ok_or(RayError::InvalidSavepoint)?;
+        let delta_mark = tx.savepoints[index].delta_mark;
+        tx.savepoints.truncate(index);
+        self.delta.write().release(delta_mark);
+        Ok(())
+    }
 
-            // Merge secondary WAL records into primary
-            wal_buffer.merge_secondary_into_primary(&mut pager)?;
-            wal_buffer.flush(&mut pager)?;
+    // ========================================================================
+    // Write Methods (require active transaction)
+    // ========================================================================
 
-            // Update header with new snapshot location
-            header.active_snapshot_gen = new_gen;
-            header.snapshot_start_page = new_snapshot_start_page;
-            header.snapshot_page_count = new_snapshot_page_count;
-            header.db_size_pages = new_snapshot_start_page + new_snapshot_page_count;
-            header.max_node_id = self.next_node_id.load(Ordering::SeqCst).saturating_sub(1);
-            header.next_tx_id = self.next_tx_id.load(Ordering::SeqCst);
+    /// Write a WAL record (internal helper)
+    fn write_wal(&self, record: WalRecord) -> Result<()> {
+        let mut pager = self.pager.lock();
+        let mut wal = self.wal_buffer.lock();
+        wal.write_record(&record, &mut pager)?;
+        self.metrics.record_wal_write(record.payload.len());
+        Ok(())
+    }
 
-            // Update WAL state
-            header.wal_head = wal_buffer.head();
-            header.wal_tail = wal_buffer.tail();
-            header.wal_primary_head = wal_buffer.primary_head();
-            header.wal_secondary_head = wal_buffer.secondary_head();
-            header.active_wal_region = 0;
-            header.checkpoint_in_progress = 0;
-            header.change_counter += 1;
+    /// Get current transaction ID or error
+    fn require_write_tx(&self) -> Result<TxId> {
+        let current_tx = self.current_tx.lock();
+        match current_tx.as_ref() {
+            Some(tx) if !tx.read_only => Ok(tx.txid),
+            Some(_) => Err(RayError::ReadOnly),
+            None => Err(RayError::NoTransaction),
+        }
+    }
 
-            // Write header to disk
-            let header_bytes = header.serialize_to_page();
-            pager.write_page(0, &header_bytes)?;
-            pager.sync()?;
+    /// Record that the active transaction touched `node_id`, so `commit` can
+    /// detect a write-write conflict against it, and that it's dirty since
+    /// the last `checkpoint` for [`SingleFileDB::checkpoint_incremental`].
+    fn record_write(&self, node_id: NodeId) {
+        let mut current_tx = self.current_tx.lock();
+        if let Some(tx) = current_tx.as_mut() {
+            tx.write_set.insert(node_id);
         }
+        drop(current_tx);
+        self.dirty_since_checkpoint.lock().insert(node_id);
+    }
 
-        // Clear delta
-        self.delta.write().clear();
+    /// Append `op` to the active transaction's op log, so `commit` can file
+    /// its inverse into [`SingleFileDB::history`] for [`SingleFileDB::unrecord`].
+    fn record_op(&self, op: HistoryOp) {
+        let mut current_tx = self.current_tx.lock();
+        if let Some(tx) = current_tx.as_mut() {
+            tx.ops.push(op);
+        }
+    }
 
-        // Reload the new snapshot
-        self.reload_snapshot()?;
+    /// Create a node
+    pub fn create_node(&self, key: Option<&str>) -> Result<NodeId> {
+        let node_id = self.alloc_node_id();
+        self.create_node_with_id(node_id, key)?;
+        Ok(node_id)
+    }
 
-        // Mark as idle
-        *self.checkpoint_status.lock() = CheckpointStatus::Idle;
+    /// Create a node under an id the caller already reserved, bypassing this
+    /// method's own `alloc_node_id` call. `create_node` itself is just this
+    /// plus a fresh id; [`OptimisticTxn::commit`] also replays a buffered
+    /// `CreateNode` op through this entry point so the node lands under the
+    /// same id the optimistic transaction allocated (and referenced from
+    /// other buffered ops) while it was still being built.
+    fn create_node_with_id(&self, node_id: NodeId, key: Option<&str>) -> Result<()> {
+        let txid = self.require_write_tx()?;
+
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::CreateNode,
+            txid,
+            build_create_node_payload(node_id, key),
+        );
+        self.write_wal(record)?;
+
+        // Update delta
+        self.delta.write().create_node(node_id, key);
+        self.record_write(node_id);
+        self.record_op(HistoryOp::CreateNode(node_id));
 
         Ok(())
     }
 
-    /// Recover from a checkpoint error
-    fn recover_from_checkpoint_error(&self) {
-        // Try to switch back to primary region and clear the checkpoint flag
-        if let Some(mut pager) = self.pager.try_lock() {
-            if let Some(mut wal_buffer) = self.wal_buffer.try_lock() {
-                if let Some(mut header) = self.header.try_write() {
-                    // Switch back to primary
-                    wal_buffer.switch_to_primary(false);
+    /// Delete a node
+    pub fn delete_node(&self, node_id: NodeId) -> Result<()> {
+        let txid = self.require_write_tx()?;
 
-                    // Clear checkpoint flag
-                    header.active_wal_region = 0;
-                    header.checkpoint_in_progress = 0;
+        // Capture the key before it's gone so the key index can tombstone
+        // it below; `delete_node` itself has no way to recover a committed
+        // node's key once the delta marks it deleted.
+        let key = self.get_node_key(node_id);
 
-                    // Try to write header
-                    let header_bytes = header.serialize_to_page();
-                    let _ = pager.write_page(0, &header_bytes);
-                    let _ = pager.sync();
-                }
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::DeleteNode,
+            txid,
+            build_delete_node_payload(node_id),
+        );
+        self.write_wal(record)?;
+
+        // Update delta. Only tombstone the key if it resolves through the
+        // committed store -- a node created and deleted within the same
+        // delta never reached the hard side, so `delete_node` already
+        // dropped its `key_index` entry outright and there's nothing to
+        // suppress.
+        let mut delta = self.delta.write();
+        let was_delta_created = delta.is_node_created(node_id);
+        delta.delete_node(node_id);
+        if !was_delta_created {
+            if let Some(key) = key {
+                delta.tombstone_key(&key);
             }
         }
+        drop(delta);
+        self.record_write(node_id);
+        self.record_op(HistoryOp::DeleteNode(node_id));
 
-        // Mark as idle
-        *self.checkpoint_status.lock() = CheckpointStatus::Idle;
+        Ok(())
     }
 
-    /// Write snapshot buffer to file pages
-    fn write_snapshot_pages(
-        &self,
-        pager: &mut FilePager,
-        start_page: u32,
-        buffer: &[u8],
-        page_size: usize,
-    ) -> Result<()> {
-        let num_pages = pages_to_store(buffer.len(), page_size);
+    /// Un-delete a node previously removed by `delete_node`, restoring it
+    /// (and whatever props/edges it still has in the snapshot or an earlier
+    /// delta) to visibility under the same id. Distinct from `CreateNode` on
+    /// the WAL so replay removes the tombstone rather than inserting a fresh,
+    /// propertyless node delta -- only meaningful for
+    /// [`SingleFileDB::unrecord`] inverting a past `DeleteNode`.
+    fn undelete_node(&self, node_id: NodeId) -> Result<()> {
+        let txid = self.require_write_tx()?;
 
-        // Ensure file is large enough
-        let required_pages = start_page + num_pages;
-        let current_pages = (pager.file_size() as usize + page_size - 1) / page_size;
+        let record = WalRecord::new(
+            WalRecordType::UndeleteNode,
+            txid,
+            build_undelete_node_payload(node_id),
+        );
+        self.write_wal(record)?;
 
-        if required_pages as usize > current_pages {
-            pager.allocate_pages(required_pages - current_pages as u32)?;
-        }
+        self.delta.write().undelete_node(node_id);
+        self.record_write(node_id);
+        self.record_op(HistoryOp::CreateNode(node_id));
 
-        // Write pages
-        for i in 0..num_pages {
-            let mut page_data = vec![0u8; page_size];
-            let src_offset = i as usize * page_size;
-            let src_end = std::cmp::min(src_offset + page_size, buffer.len());
-            page_data[..src_end - src_offset].copy_from_slice(&buffer[src_offset..src_end]);
-            pager.write_page(start_page + i, &page_data)?;
-        }
+        Ok(())
+    }
 
-        // Sync to disk
-        pager.sync()?;
+    /// Add an edge
+    pub fn add_edge(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> Result<()> {
+        let txid = self.require_write_tx()?;
+
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::AddEdge,
+            txid,
+            build_add_edge_payload(src, etype, dst),
+        );
+        self.write_wal(record)?;
+
+        // Update delta
+        self.delta.write().add_edge(src, etype, dst);
+        self.record_write(src);
+        self.record_write(dst);
+        self.record_op(HistoryOp::AddEdge(src, etype, dst));
 
         Ok(())
     }
 
-    /// Collect all graph data from snapshot + delta
-    fn collect_graph_data(&self) -> (
-        Vec<NodeData>,
-        Vec<EdgeData>,
-        HashMap<LabelId, String>,
-        HashMap<ETypeId, String>,
-        HashMap<PropKeyId, String>,
-    ) {
-        let mut nodes = Vec::new();
-        let mut edges = Vec::new();
-        let mut labels = HashMap::new();
-        let mut etypes = HashMap::new();
-        let mut propkeys = HashMap::new();
+    /// Add an edge by type name
+    pub fn add_edge_by_name(&self, src: NodeId, etype_name: &str, dst: NodeId) -> Result<()> {
+        let etype = self.get_or_create_etype(etype_name);
+        self.add_edge(src, etype, dst)
+    }
 
-        let delta = self.delta.read();
+    /// Delete an edge
+    pub fn delete_edge(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> Result<()> {
+        let txid = self.require_write_tx()?;
 
-        // First, copy schema from our in-memory maps
-        for (&id, name) in self.label_ids.read().iter() {
-            labels.insert(id, name.clone());
-        }
-        for (&id, name) in self.etype_ids.read().iter() {
-            etypes.insert(id, name.clone());
-        }
-        for (&id, name) in self.propkey_ids.read().iter() {
-            propkeys.insert(id, name.clone());
-        }
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::DeleteEdge,
+            txid,
+            build_delete_edge_payload(src, etype, dst),
+        );
+        self.write_wal(record)?;
 
-        // Collect nodes from snapshot
-        if let Some(ref snapshot) = *self.snapshot.read() {
-            let num_nodes = snapshot.header.num_nodes as usize;
+        // Update delta
+        self.delta.write().delete_edge(src, etype, dst);
+        self.record_write(src);
+        self.record_write(dst);
+        self.record_op(HistoryOp::DeleteEdge(src, etype, dst));
 
-            for phys in 0..num_nodes {
-                let node_id = match snapshot.get_node_id(phys as u32) {
-                    Some(id) => id,
-                    None => continue,
-                };
+        Ok(())
+    }
 
-                // Skip deleted nodes
-                if delta.is_node_deleted(node_id) {
-                    continue;
-                }
+    /// Set a node property
+    pub fn set_node_prop(&self, node_id: NodeId, key_id: PropKeyId, value: PropValue) -> Result<()> {
+        let txid = self.require_write_tx()?;
+        let prior = self.get_node_prop(node_id, key_id);
 
-                // Get key
-                let key = snapshot.get_node_key(phys as u32);
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::SetNodeProp,
+            txid,
+            build_set_node_prop_payload(node_id, key_id, &value),
+        );
+        self.write_wal(record)?;
 
-                // Get properties from snapshot
-                let mut props = HashMap::new();
-                if let Some(snapshot_props) = snapshot.get_node_props(phys as u32) {
-                    for (key_id, value) in snapshot_props {
-                        props.insert(key_id, value);
-                    }
-                }
+        // Update delta
+        self.delta.write().set_node_prop(node_id, key_id, value);
+        self.record_write(node_id);
+        self.record_op(HistoryOp::SetNodeProp(node_id, key_id, prior));
 
-                // Apply delta modifications
-                if let Some(node_delta) = delta.get_node_delta(node_id) {
-                    if let Some(ref delta_props) = node_delta.props {
-                        for (&key_id, value) in delta_props {
-                            match value {
-                                Some(v) => { props.insert(key_id, v.clone()); }
-                                None => { props.remove(&key_id); }
-                            }
-                        }
-                    }
-                }
+        Ok(())
+    }
 
-                // Collect node labels (simplified - labels handled differently in real impl)
-                let node_labels = Vec::new();
+    /// Set a node property by key name
+    pub fn set_node_prop_by_name(&self, node_id: NodeId, key_name: &str, value: PropValue) -> Result<()> {
+        let key_id = self.get_or_create_propkey(key_name);
+        self.set_node_prop(node_id, key_id, value)
+    }
 
-                nodes.push(NodeData {
-                    node_id,
-                    key,
-                    labels: node_labels,
-                    props,
-                });
+    /// Delete a node property
+    pub fn delete_node_prop(&self, node_id: NodeId, key_id: PropKeyId) -> Result<()> {
+        let txid = self.require_write_tx()?;
+        let prior = self.get_node_prop(node_id, key_id);
 
-                // Collect edges from this node
-                for edge_info in snapshot.get_out_edges(phys as u32) {
-                    let dst_node_id = match snapshot.get_node_id(edge_info.dst) {
-                        Some(id) => id,
-                        None => continue,
-                    };
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::DelNodeProp,
+            txid,
+            build_del_node_prop_payload(node_id, key_id),
+        );
+        self.write_wal(record)?;
 
-                    // Skip edges to deleted nodes
-                    if delta.is_node_deleted(dst_node_id) {
-                        continue;
-                    }
+        // Update delta
+        self.delta.write().delete_node_prop(node_id, key_id);
+        self.record_write(node_id);
+        self.record_op(HistoryOp::SetNodeProp(node_id, key_id, prior));
 
-                    // Skip deleted edges
-                    if delta.is_edge_deleted(node_id, edge_info.etype, dst_node_id) {
-                        continue;
-                    }
+        Ok(())
+    }
 
-                    // Get edge props (simplified)
-                    let edge_props = HashMap::new();
+    /// Set an edge property
+    pub fn set_edge_prop(&self, src: NodeId, etype: ETypeId, dst: NodeId, key_id: PropKeyId, value: PropValue) -> Result<()> {
+        let txid = self.require_write_tx()?;
+        let prior = self.get_edge_prop(src, etype, dst, key_id);
 
-                    edges.push(EdgeData {
-                        src: node_id,
-                        etype: edge_info.etype,
-                        dst: dst_node_id,
-                        props: edge_props,
-                    });
-                }
-            }
-        }
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::SetEdgeProp,
+            txid,
+            build_set_edge_prop_payload(src, etype, dst, key_id, &value),
+        );
+        self.write_wal(record)?;
 
-        // Add nodes created in delta
-        for (&node_id, node_delta) in &delta.created_nodes {
-            let mut props = HashMap::new();
-            if let Some(ref delta_props) = node_delta.props {
-                for (&key_id, value) in delta_props {
-                    if let Some(v) = value {
-                        props.insert(key_id, v.clone());
-                    }
-                }
-            }
+        // Update delta
+        self.delta.write().set_edge_prop(src, etype, dst, key_id, value);
+        self.record_write(src);
+        self.record_write(dst);
+        self.record_op(HistoryOp::SetEdgeProp(src, etype, dst, key_id, prior));
 
-            nodes.push(NodeData {
-                node_id,
-                key: node_delta.key.clone(),
-                labels: Vec::new(),
-                props,
-            });
-        }
+        Ok(())
+    }
 
-        // Add edges from delta
-        for (&src, patches) in &delta.out_add {
-            // Skip edges from deleted nodes
-            if delta.is_node_deleted(src) {
-                continue;
-            }
+    /// Set an edge property by key name
+    pub fn set_edge_prop_by_name(&self, src: NodeId, etype: ETypeId, dst: NodeId, key_name: &str, value: PropValue) -> Result<()> {
+        let key_id = self.get_or_create_propkey(key_name);
+        self.set_edge_prop(src, etype, dst, key_id, value)
+    }
 
-            for patch in patches {
-                // Skip edges to deleted nodes
-                if delta.is_node_deleted(patch.other) {
-                    continue;
-                }
+    /// Delete an edge property
+    pub fn delete_edge_prop(&self, src: NodeId, etype: ETypeId, dst: NodeId, key_id: PropKeyId) -> Result<()> {
+        let txid = self.require_write_tx()?;
+        let prior = self.get_edge_prop(src, etype, dst, key_id);
 
-                edges.push(EdgeData {
-                    src,
-                    etype: patch.etype,
-                    dst: patch.other,
-                    props: HashMap::new(),
-                });
-            }
-        }
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::DelEdgeProp,
+            txid,
+            build_del_edge_prop_payload(src, etype, dst, key_id),
+        );
+        self.write_wal(record)?;
 
-        (nodes, edges, labels, etypes, propkeys)
-    }
+        // Update delta
+        self.delta.write().delete_edge_prop(src, etype, dst, key_id);
+        self.record_write(src);
+        self.record_write(dst);
+        self.record_op(HistoryOp::SetEdgeProp(src, etype, dst, key_id, prior));
 
-    /// Check if checkpoint is recommended based on WAL usage
-    pub fn should_checkpoint(&self, threshold: f64) -> bool {
-        let stats = self.wal_stats();
-        stats.used as f64 / stats.capacity as f64 >= threshold
+        Ok(())
     }
 
-    // ========================================================================
-    // Query / Read Operations
-    // ========================================================================
-
-    /// Get all properties for a node
-    /// 
-    /// Returns None if the node doesn't exist or is deleted.
-    /// Merges properties from snapshot with delta modifications.
-    pub fn get_node_props(&self, node_id: NodeId) -> Option<HashMap<PropKeyId, PropValue>> {
-        let delta = self.delta.read();
+    /// Define a new label (writes to WAL for durability)
+    pub fn define_label(&self, name: &str) -> Result<LabelId> {
+        let txid = self.require_write_tx()?;
 
-        // Check if node is deleted
-        if delta.is_node_deleted(node_id) {
-            return None;
+        // Check if already exists
+        if let Some(id) = self.get_label_id(name) {
+            return Ok(id);
         }
 
-        let mut props = HashMap::new();
-        let snapshot = self.snapshot.read();
+        let label_id = self.alloc_label_id();
 
-        // Get properties from snapshot first
-        if let Some(ref snap) = *snapshot {
-            if let Some(phys) = snap.get_phys_node(node_id) {
-                if let Some(snapshot_props) = snap.get_node_props(phys) {
-                    props = snapshot_props;
-                }
-            }
-        }
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::DefineLabel,
+            txid,
+            build_define_label_payload(label_id, name),
+        );
+        self.write_wal(record)?;
 
-        // Apply delta modifications
-        if let Some(node_delta) = delta.get_node_delta(node_id) {
-            if let Some(ref delta_props) = node_delta.props {
-                for (&key_id, value) in delta_props {
-                    match value {
-                        Some(v) => { props.insert(key_id, v.clone()); }
-                        None => { props.remove(&key_id); }
-                    }
-                }
-            }
+        // Update schema maps
+        {
+            let mut names = self.label_names.write();
+            let mut ids = self.label_ids.write();
+            names.insert(name.to_string(), label_id);
+            ids.insert(label_id, name.to_string());
         }
 
-        // Check if node exists at all
-        let node_exists_in_delta = delta.is_node_created(node_id) 
-            || delta.get_node_delta(node_id).is_some();
-        
-        if !node_exists_in_delta {
-            if let Some(ref snap) = *snapshot {
-                if snap.get_phys_node(node_id).is_none() {
-                    return None;
-                }
-            } else {
-                // No snapshot and node not in delta
-                return None;
-            }
-        }
+        // Update delta
+        self.delta.write().define_label(label_id, name);
 
-        Some(props)
+        Ok(label_id)
     }
 
-    /// Get a specific property for a node
-    /// 
-    /// Returns None if the node doesn't exist, is deleted, or doesn't have the property.
-    pub fn get_node_prop(&self, node_id: NodeId, key_id: PropKeyId) -> Option<PropValue> {
-        let delta = self.delta.read();
+    /// Define a new edge type (writes to WAL for durability)
+    pub fn define_etype(&self, name: &str) -> Result<ETypeId> {
+        let txid = self.require_write_tx()?;
 
-        // Check if node is deleted
-        if delta.is_node_deleted(node_id) {
-            return None;
+        // Check if already exists
+        if let Some(id) = self.get_etype_id(name) {
+            return Ok(id);
         }
 
-        // Check delta first (for modifications)
-        if let Some(node_delta) = delta.get_node_delta(node_id) {
-            if let Some(ref delta_props) = node_delta.props {
-                if let Some(value) = delta_props.get(&key_id) {
-                    // None means explicitly deleted
-                    return value.clone();
-                }
-            }
-        }
+        let etype_id = self.alloc_etype_id();
 
-        // Fall back to snapshot
-        let snapshot = self.snapshot.read();
-        if let Some(ref snap) = *snapshot {
-            if let Some(phys) = snap.get_phys_node(node_id) {
-                return snap.get_node_prop(phys, key_id);
-            }
-        }
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::DefineEtype,
+            txid,
+            build_define_etype_payload(etype_id, name),
+        );
+        self.write_wal(record)?;
 
-        // Check if node exists at all (in delta as created)
-        if delta.is_node_created(node_id) {
-            // Node exists but doesn't have this property
-            return None;
+        // Update schema maps
+        {
+            let mut names = self.etype_names.write();
+            let mut ids = self.etype_ids.write();
+            names.insert(name.to_string(), etype_id);
+            ids.insert(etype_id, name.to_string());
         }
 
-        None
+        // Update delta
+        self.delta.write().define_etype(etype_id, name);
+
+        Ok(etype_id)
     }
 
-    /// Get outgoing edges for a node
-    /// 
-    /// Returns edges as (edge_type_id, destination_node_id) pairs.
-    /// Merges edges from snapshot with delta additions/deletions.
-    /// Filters out edges to deleted nodes.
-    pub fn get_out_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
-        let delta = self.delta.read();
+    /// Define a new property key (writes to WAL for durability)
+    pub fn define_propkey(&self, name: &str) -> Result<PropKeyId> {
+        let txid = self.require_write_tx()?;
 
-        // If node is deleted, no edges
-        if delta.is_node_deleted(node_id) {
-            return Vec::new();
+        // Check if already exists
+        if let Some(id) = self.get_propkey_id(name) {
+            return Ok(id);
         }
 
-        let mut edges = Vec::new();
-        let snapshot = self.snapshot.read();
+        let propkey_id = self.alloc_propkey_id();
 
-        // Get edges from snapshot
-        if let Some(ref snap) = *snapshot {
-            if let Some(phys) = snap.get_phys_node(node_id) {
-                for (dst_phys, etype) in snap.iter_out_edges(phys) {
-                    // Convert physical dst to NodeId
-                    if let Some(dst_node_id) = snap.get_node_id(dst_phys) {
-                        // Skip edges to deleted nodes
-                        if delta.is_node_deleted(dst_node_id) {
-                            continue;
-                        }
-                        // Skip edges deleted in delta
-                        if delta.is_edge_deleted(node_id, etype, dst_node_id) {
-                            continue;
-                        }
-                        edges.push((etype, dst_node_id));
-                    }
-                }
-            }
+        // Write WAL record
+        let record = WalRecord::new(
+            WalRecordType::DefinePropkey,
+            txid,
+            build_define_propkey_payload(propkey_id, name),
+        );
+        self.write_wal(record)?;
+
+        // Update schema maps
+        {
+            let mut names = self.propkey_names.write();
+            let mut ids = self.propkey_ids.write();
+            names.insert(name.to_string(), propkey_id);
+            ids.insert(propkey_id, name.to_string());
         }
 
-        // Add edges from delta
-        if let Some(added_edges) = delta.out_add.get(&node_id) {
-            for edge_patch in added_edges {
-                // Skip edges to deleted nodes
-                if delta.is_node_deleted(edge_patch.other) {
-                    continue;
-                }
-                edges.push((edge_patch.etype, edge_patch.other));
+        // Update delta
+        self.delta.write().define_propkey(propkey_id, name);
+
+        Ok(propkey_id)
+    }
+
+    // ========================================================================
+    // WAL Statistics
+    // ========================================================================
+
+    /// Get WAL buffer statistics
+    pub fn wal_stats(&self) -> crate::core::wal::buffer::WalBufferStats {
+        self.wal_buffer.lock().stats()
+    }
+
+    // ========================================================================
+    // Checkpoint / Compaction
+    // ========================================================================
+
+    /// Map the local open-time [`SnapshotCompression`] choice to the codec
+    /// the snapshot writer understands.
+    fn writer_compression(c: SnapshotCompression) -> crate::core::snapshot::writer::SnapshotCompression {
+        match c {
+            SnapshotCompression::Lz4 => crate::core::snapshot::writer::SnapshotCompression::Lz4,
+            SnapshotCompression::Zstd { level } => {
+                crate::core::snapshot::writer::SnapshotCompression::Zstd { level }
             }
         }
+    }
 
-        // Sort by (etype, dst) for consistent ordering
-        edges.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+    /// Serialize flat graph data into a snapshot buffer, applying
+    /// `default_compression` and recording the resulting [`CompressionStats`]
+    /// (read back via [`SingleFileDB::compression_stats`]).
+    fn build_snapshot_buffer(
+        &self,
+        generation: u64,
+        nodes: Vec<NodeData>,
+        edges: Vec<EdgeData>,
+        labels: HashMap<LabelId, String>,
+        etypes: HashMap<ETypeId, String>,
+        propkeys: HashMap<PropKeyId, String>,
+    ) -> Result<Vec<u8>> {
+        let started = std::time::Instant::now();
+        let output = build_snapshot_to_memory(SnapshotBuildInput {
+            generation,
+            nodes,
+            edges,
+            labels,
+            etypes,
+            propkeys,
+            compression: self.default_compression.map(Self::writer_compression),
+        })?;
 
-        edges
+        *self.last_compression_stats.write() = Some(CompressionStats {
+            uncompressed_bytes: output.uncompressed_len as u64,
+            compressed_bytes: output.bytes.len() as u64,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        });
+
+        Ok(output.bytes)
     }
 
-    /// Get incoming edges for a node
-    /// 
-    /// Returns edges as (edge_type_id, source_node_id) pairs.
-    /// Merges edges from snapshot with delta additions/deletions.
-    /// Filters out edges from deleted nodes.
-    pub fn get_in_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+    /// Size/timing metrics from the most recent snapshot build (`checkpoint`,
+    /// `background_checkpoint`, or `compact`). `None` until the first one
+    /// completes.
+    pub fn compression_stats(&self) -> Option<CompressionStats> {
+        *self.last_compression_stats.read()
+    }
+
+    /// Snapshot of the structured counters accumulated across the WAL,
+    /// checkpoint, and transaction subsystems, plus gauges on current memory
+    /// usage (delta size, resident snapshot pages, WAL utilization, pending
+    /// incremental-checkpoint layers). The gauges are sampled fresh on every
+    /// call; the counters are running totals since this `SingleFileDB` was
+    /// opened (all zero when the `metrics` feature is disabled).
+    pub fn metrics(&self) -> DbMetricsSnapshot {
         let delta = self.delta.read();
+        let wal_stats = self.wal_stats();
+        let gauges = DbMetricsGauges {
+            delta_usage: delta.usage(),
+            delta_bytes_estimate: delta.estimated_bytes(),
+            checkpoint_phase: self.checkpoint_progress.phase(),
+            snapshot_resident_pages: self.header.read().snapshot_page_count,
+            wal_used_bytes: wal_stats.used,
+            wal_capacity_bytes: wal_stats.capacity,
+            pending_layers: self.pending_layers.lock().len(),
+        };
+        drop(delta);
+        self.metrics.snapshot(gauges)
+    }
 
-        // If node is deleted, no edges
-        if delta.is_node_deleted(node_id) {
-            return Vec::new();
+    /// Opt-in per-operation timing for `commit`, `checkpoint`, and
+    /// `background_checkpoint` -- see [`PerfContext`] for what is (and
+    /// isn't) tracked. All zero when the `metrics` feature is disabled.
+    pub fn perf_context(&self) -> PerfContextSnapshot {
+        self.perf.snapshot()
+    }
+
+    /// Perform a checkpoint - merge snapshot + delta into new snapshot
+    ///
+    /// This:
+    /// 1. Collects all graph data from snapshot + delta
+    /// 2. Builds a new snapshot in memory
+    /// 3. Writes the new snapshot to disk (after WAL)
+    /// 4. Updates header to point to new snapshot
+    /// 5. Clears WAL and delta
+    pub fn checkpoint(&self) -> Result<()> {
+        if self.read_only {
+            return Err(RayError::ReadOnly);
         }
 
-        let mut edges = Vec::new();
-        let snapshot = self.snapshot.read();
+        // Don't checkpoint with active transaction
+        if self.has_transaction() {
+            return Err(RayError::TransactionInProgress);
+        }
 
-        // Get edges from snapshot
-        if let Some(ref snap) = *snapshot {
-            if let Some(phys) = snap.get_phys_node(node_id) {
-                for (src_phys, etype, _out_index) in snap.iter_in_edges(phys) {
-                    // Convert physical src to NodeId
-                    if let Some(src_node_id) = snap.get_node_id(src_phys) {
-                        // Skip edges from deleted nodes
-                        if delta.is_node_deleted(src_node_id) {
-                            continue;
-                        }
-                        // Skip edges deleted in delta
-                        if delta.is_edge_deleted(src_node_id, etype, node_id) {
-                            continue;
-                        }
-                        edges.push((etype, src_node_id));
-                    }
-                }
-            }
+        self.require_no_pinned_snapshot()?;
+
+        let checkpoint_started = std::time::Instant::now();
+
+        // Collect all graph data
+        let (nodes, edges, labels, etypes, propkeys) = self.collect_graph_data();
+
+        // Get current header state
+        let header = self.header.read().clone();
+        let new_gen = header.active_snapshot_gen + 1;
+
+        // Build new snapshot in memory
+        let snapshot_buffer =
+            self.build_snapshot_buffer(new_gen, nodes, edges, labels, etypes, propkeys)?;
+
+        // Calculate where to place new snapshot (after WAL)
+        let wal_end_page = header.wal_start_page + header.wal_page_count;
+        let new_snapshot_start_page = wal_end_page;
+        let new_snapshot_page_count = pages_to_store(snapshot_buffer.len(), header.page_size as usize) as u64;
+
+        // Write snapshot to file
+        {
+            let mut pager = self.pager.lock();
+            self.write_snapshot_pages(&mut pager, new_snapshot_start_page as u32, &snapshot_buffer, header.page_size as usize)?;
+        }
+
+        // Update header
+        {
+            let mut pager = self.pager.lock();
+            let mut wal_buffer = self.wal_buffer.lock();
+            let mut header = self.header.write();
+
+            // Update header fields
+            header.active_snapshot_gen = new_gen;
+            header.snapshot_start_page = new_snapshot_start_page;
+            header.snapshot_page_count = new_snapshot_page_count;
+            header.db_size_pages = new_snapshot_start_page + new_snapshot_page_count;
+            header.max_node_id = self.next_node_id.load(Ordering::SeqCst).saturating_sub(1);
+            header.next_tx_id = self.next_tx_id.load(Ordering::SeqCst);
+
+            // Reset WAL
+            header.wal_head = 0;
+            header.wal_tail = 0;
+            wal_buffer.reset();
+
+            // Increment change counter
+            header.change_counter += 1;
+
+            // Write header to disk
+            let header_bytes = header.serialize_to_page();
+            pager.write_page(0, &header_bytes)?;
+            pager.sync()?;
+        }
+
+        // Clear delta
+        self.delta.write().clear();
+
+        // Reload the new snapshot
+        self.reload_snapshot()?;
+
+        self.record_namespace_checkpoints(new_snapshot_page_count, new_gen);
+
+        // A full checkpoint folds in everything any pending layer was
+        // standing in for, so the batching state resets along with it.
+        self.dirty_since_checkpoint.lock().clear();
+        self.pending_layers.lock().clear();
+
+        let stats = self.compression_stats().unwrap_or_default();
+        self.metrics.record_checkpoint(
+            checkpoint_started.elapsed().as_millis() as u64,
+            stats.uncompressed_bytes,
+            stats.compressed_bytes,
+        );
+        self.perf.record_checkpoint(checkpoint_started.elapsed(), stats.uncompressed_bytes);
+
+        Ok(())
+    }
+
+    /// Reload snapshot from disk after checkpoint
+    fn reload_snapshot(&self) -> Result<()> {
+        let header = self.header.read();
+        
+        if header.snapshot_page_count == 0 {
+            // No snapshot to load
+            *self.snapshot.write() = None;
+            return Ok(());
+        }
+
+        // Calculate snapshot offset in bytes
+        let snapshot_offset = (header.snapshot_start_page * header.page_size as u64) as usize;
+        
+        // Re-mmap the file and parse snapshot
+        let pager = self.pager.lock();
+        let new_snapshot = SnapshotData::parse_at_offset(
+            std::sync::Arc::new(unsafe {
+                // Safety: We're creating an owned Mmap from the file
+                // This is safe because the pager keeps the file open
+                memmap2::Mmap::map(pager.file())?
+            }),
+            snapshot_offset,
+            &crate::core::snapshot::reader::ParseSnapshotOptions::default(),
+        )?;
+
+        // Physical node ids are only meaningful relative to the snapshot
+        // generation that produced them, so a stale cache entry could
+        // silently hand back a different node's properties.
+        self.node_prop_cache.lock().clear();
+        *self.snapshot_identity.write() = SnapshotIdentity::current(&pager).ok();
+
+        // Update the snapshot
+        *self.snapshot.write() = Some(new_snapshot);
+
+        Ok(())
+    }
+
+    /// Re-read this file's length and mtime and compare against the
+    /// fingerprint taken when the snapshot was last mapped. `false` means
+    /// the file changed out from under this handle (e.g. replaced by
+    /// another process) and mmap-derived offsets -- including
+    /// [`NodePropCache`] -- should not be trusted; reopen rather than keep
+    /// reading. Returns `true` when no snapshot is mapped, since there's
+    /// nothing to invalidate.
+    pub fn verify_snapshot_identity(&self) -> Result<bool> {
+        let stored = match *self.snapshot_identity.read() {
+            Some(stored) => stored,
+            None => return Ok(true),
+        };
+        let pager = self.pager.lock();
+        Ok(SnapshotIdentity::current(&pager)? == stored)
+    }
+
+    // ========================================================================
+    // Compaction / Vacuum
+    // ========================================================================
+
+    /// Write a defragmented, minimally-sized copy of this database to `path`.
+    ///
+    /// Unlike [`SingleFileDB::compact`], this leaves the currently-open file
+    /// untouched -- it merges the live snapshot with the replayed delta and
+    /// writes `[Header][empty WAL][new Snapshot]` fresh, with no superseded
+    /// snapshot generations or already-applied WAL records left over. Useful
+    /// on its own for exporting a clean copy; `compact` calls this against a
+    /// temp file and renames it over the original.
+    pub fn compact_into<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if self.has_transaction() {
+            return Err(RayError::TransactionInProgress);
+        }
+        let path = path.as_ref();
+
+        // Merge the live snapshot with the replayed delta into flat data.
+        let (nodes, edges, labels, etypes, propkeys) = self.collect_graph_data();
+
+        let old_header = self.header.read().clone();
+        let page_size = old_header.page_size as usize;
+        let wal_page_count = old_header.wal_page_count;
+
+        let snapshot_buffer =
+            self.build_snapshot_buffer(1, nodes, edges, labels, etypes, propkeys)?;
+
+        let mut new_pager = create_pager(path, page_size)?;
+        let mut new_header = DbHeaderV1::new(page_size as u32, wal_page_count);
+        new_header.next_tx_id = self.next_tx_id.load(Ordering::SeqCst);
+        new_header.max_node_id = self.next_node_id.load(Ordering::SeqCst).saturating_sub(1);
+
+        // Reserve the header page and an empty WAL region before writing the
+        // new snapshot, same layout `open_single_file` creates from scratch.
+        let header_bytes = new_header.serialize_to_page();
+        new_pager.write_page(0, &header_bytes)?;
+        new_pager.allocate_pages(wal_page_count as u32)?;
+
+        let snapshot_start_page = new_header.wal_start_page + wal_page_count;
+        self.write_snapshot_pages(&mut new_pager, snapshot_start_page as u32, &snapshot_buffer, page_size)?;
+
+        new_header.active_snapshot_gen = 1;
+        new_header.snapshot_start_page = snapshot_start_page;
+        new_header.snapshot_page_count = pages_to_store(snapshot_buffer.len(), page_size) as u64;
+        new_header.db_size_pages = snapshot_start_page + new_header.snapshot_page_count;
+        new_header.wal_head = 0;
+        new_header.wal_tail = 0;
+        new_header.change_counter = old_header.change_counter + 1;
+
+        let header_bytes = new_header.serialize_to_page();
+        new_pager.write_page(0, &header_bytes)?;
+        new_pager.sync()?;
+
+        Ok(())
+    }
+
+    /// Reclaim WAL and stale snapshot-generation space by rewriting this
+    /// database's file from scratch: a fresh minimal snapshot with an empty
+    /// WAL, replacing the accumulated superseded snapshot and already-applied
+    /// WAL records that `checkpoint` otherwise leaves behind.
+    pub fn compact(&self) -> Result<()> {
+        if self.read_only {
+            return Err(RayError::ReadOnly);
+        }
+        if self.has_transaction() {
+            return Err(RayError::TransactionInProgress);
         }
 
-        // Add edges from delta (in_add stores patches where other=src)
-        if let Some(added_edges) = delta.in_add.get(&node_id) {
-            for edge_patch in added_edges {
-                // Skip edges from deleted nodes
-                if delta.is_node_deleted(edge_patch.other) {
-                    continue;
-                }
-                edges.push((edge_patch.etype, edge_patch.other));
-            }
+        let tmp_path = self.path.with_extension("raydb.compact-tmp");
+        self.compact_into(&tmp_path)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        // Reopen against the compacted file and replace every piece of state
+        // that was derived from the old one -- the same derivation
+        // `open_single_file` performs for a fresh open, applied in place so
+        // existing references to this `SingleFileDB` keep working.
+        let reopened = open_single_file(
+            &self.path,
+            SingleFileOpenOptions::new()
+                .durability(self.default_durability)
+                .compression(self.default_compression),
+        )?;
+
+        *self.pager.lock() = reopened.pager.into_inner();
+        *self.header.write() = reopened.header.into_inner();
+        *self.wal_buffer.lock() = reopened.wal_buffer.into_inner();
+        *self.snapshot.write() = reopened.snapshot.into_inner();
+        *self.delta.write() = reopened.delta.into_inner();
+        self.next_node_id.store(reopened.next_node_id.load(Ordering::SeqCst), Ordering::SeqCst);
+        self.next_label_id.store(reopened.next_label_id.load(Ordering::SeqCst), Ordering::SeqCst);
+        self.next_etype_id.store(reopened.next_etype_id.load(Ordering::SeqCst), Ordering::SeqCst);
+        self.next_propkey_id.store(reopened.next_propkey_id.load(Ordering::SeqCst), Ordering::SeqCst);
+        self.next_tx_id.store(reopened.next_tx_id.load(Ordering::SeqCst), Ordering::SeqCst);
+        *self.label_names.write() = reopened.label_names.into_inner();
+        *self.label_ids.write() = reopened.label_ids.into_inner();
+        *self.etype_names.write() = reopened.etype_names.into_inner();
+        *self.etype_ids.write() = reopened.etype_ids.into_inner();
+        *self.propkey_names.write() = reopened.propkey_names.into_inner();
+        *self.propkey_ids.write() = reopened.propkey_ids.into_inner();
+        self.node_prop_cache.lock().clear();
+        *self.snapshot_identity.write() = reopened.snapshot_identity.into_inner();
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Checkpoint Export (Online Backup)
+    // ========================================================================
+
+    /// Write a consistent, fully self-contained `.raydb` copy of this
+    /// database's current committed state to `dest`, for an online backup or
+    /// to fork a copy to test against.
+    ///
+    /// Unlike [`checkpoint`](Self::checkpoint) and
+    /// [`background_checkpoint`](Self::background_checkpoint), which rewrite
+    /// *this* file in place, `dest` is a brand new file -- the currently open
+    /// one, and any writer using it, is left untouched. This is exactly
+    /// [`compact_into`](Self::compact_into)'s merge-live-snapshot-with-every-
+    /// committed-delta-record-into-one-fresh-snapshot behavior, under a name
+    /// that matches what callers reach for when they mean "checkpoint to a
+    /// standalone file" (à la RocksDB's `Checkpoint::create_checkpoint`).
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, dest: P) -> Result<()> {
+        self.compact_into(dest)
+    }
+
+    // ========================================================================
+    // Background Checkpoint (Non-Blocking)
+    // ========================================================================
+
+    /// Check if a background checkpoint is currently running
+    pub fn is_checkpoint_running(&self) -> bool {
+        let status = *self.checkpoint_status.lock();
+        matches!(status, CheckpointStatus::Running | CheckpointStatus::Completing)
+    }
+
+    /// Get current checkpoint status
+    pub fn checkpoint_status(&self) -> CheckpointStatus {
+        *self.checkpoint_status.lock()
+    }
+
+    /// Get a handle to the live progress counters for the currently (or most
+    /// recently) running background checkpoint.
+    pub fn checkpoint_progress(&self) -> std::sync::Arc<CheckpointProgress> {
+        self.checkpoint_progress.clone()
+    }
+
+    /// Ask a running background checkpoint to stop at its next opportunity.
+    /// `background_checkpoint` checks this between node/edge batches and, if
+    /// set, bails out with `RayError::CheckpointAborted` after reverting to
+    /// the primary WAL region.
+    pub fn request_checkpoint_abort(&self) {
+        self.checkpoint_progress.abort_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Trigger a background checkpoint (non-blocking)
+    ///
+    /// This switches writes to secondary WAL region immediately and starts
+    /// the checkpoint process. Writes can continue while checkpoint is running.
+    ///
+    /// Steps:
+    /// 1. Switch writes to secondary WAL region
+    /// 2. Set checkpointInProgress flag (for crash recovery)
+    /// 3. Build new snapshot from primary WAL + current snapshot + delta
+    /// 4. Write new snapshot to disk
+    /// 5. Merge secondary into primary, update header
+    /// 6. Clear checkpointInProgress flag
+    pub fn background_checkpoint(&self) -> Result<()> {
+        if self.read_only {
+            return Err(RayError::ReadOnly);
+        }
+
+        self.require_no_pinned_snapshot()?;
+
+        // Check if already running
+        {
+            let mut status = self.checkpoint_status.lock();
+            match *status {
+                CheckpointStatus::Running => {
+                    // Already running, just return
+                    return Ok(());
+                }
+                CheckpointStatus::Completing => {
+                    // Wait for completion by returning
+                    return Ok(());
+                }
+                CheckpointStatus::Idle => {
+                    *status = CheckpointStatus::Running;
+                }
+            }
+        }
+
+        let checkpoint_started = std::time::Instant::now();
+
+        // Step 1: Switch writes to secondary region
+        {
+            let mut pager = self.pager.lock();
+            let mut wal_buffer = self.wal_buffer.lock();
+            let mut header = self.header.write();
+
+            // Switch WAL to secondary region
+            wal_buffer.switch_to_secondary();
+
+            // Update header to reflect the switch
+            header.active_wal_region = 1;
+            header.checkpoint_in_progress = 1;
+            header.wal_primary_head = wal_buffer.primary_head();
+            header.wal_secondary_head = wal_buffer.secondary_head();
+            header.change_counter += 1;
+
+            // Write header to disk
+            let header_bytes = header.serialize_to_page();
+            pager.write_page(0, &header_bytes)?;
+            pager.sync()?;
+        }
+
+        // Step 2-4: Build and write snapshot, get the info
+        let snapshot_info = match self.build_and_write_snapshot() {
+            Ok(info) => info,
+            Err(e) => {
+                // On error, try to recover
+                self.recover_from_checkpoint_error();
+                return Err(e);
+            }
+        };
+
+        // Step 5: Complete the checkpoint
+        self.complete_background_checkpoint(snapshot_info)?;
+
+        let stats = self.compression_stats().unwrap_or_default();
+        self.metrics.record_checkpoint(
+            checkpoint_started.elapsed().as_millis() as u64,
+            stats.uncompressed_bytes,
+            stats.compressed_bytes,
+        );
+        self.perf.record_background_checkpoint(checkpoint_started.elapsed(), stats.uncompressed_bytes);
+
+        Ok(())
+    }
+
+    /// Build and write the snapshot (called during background checkpoint)
+    /// Returns (new_gen, new_snapshot_start_page, new_snapshot_page_count)
+    fn build_and_write_snapshot(&self) -> Result<(u64, u64, u64)> {
+        let progress = self.checkpoint_progress.clone();
+        progress.reset();
+        progress.set_phase(CheckpointPhase::Collecting);
+
+        let (labels, etypes, propkeys) = self.collect_schema_maps();
+
+        // Get current header state
+        let header = self.header.read().clone();
+        let new_gen = header.active_snapshot_gen + 1;
+
+        // Calculate where to place new snapshot (after WAL)
+        let wal_end_page = header.wal_start_page + header.wal_page_count;
+        let new_snapshot_start_page = wal_end_page;
+
+        progress.set_phase(CheckpointPhase::WritingSnapshot);
+
+        // Stream nodes/edges straight to the pager as they're merged from
+        // the snapshot + delta, rather than collecting them into `Vec`s
+        // first -- see `stream_snapshot_to_pager`.
+        let new_snapshot_page_count = {
+            let mut pager = self.pager.lock();
+            self.stream_snapshot_to_pager(
+                &mut pager,
+                new_snapshot_start_page as u32,
+                header.page_size as usize,
+                new_gen,
+                labels,
+                etypes,
+                propkeys,
+                &progress,
+            )?
+        };
+
+        Ok((new_gen, new_snapshot_start_page, new_snapshot_page_count))
+    }
+
+    /// Complete the background checkpoint
+    fn complete_background_checkpoint(&self, snapshot_info: (u64, u64, u64)) -> Result<()> {
+        let (new_gen, new_snapshot_start_page, new_snapshot_page_count) = snapshot_info;
+        
+        // Mark as completing (brief lock period)
+        *self.checkpoint_status.lock() = CheckpointStatus::Completing;
+        self.checkpoint_progress.set_phase(CheckpointPhase::MergingWal);
+
+        // Merge secondary records into primary and update header
+        {
+            let mut pager = self.pager.lock();
+            let mut wal_buffer = self.wal_buffer.lock();
+            let mut header = self.header.write();
+
+            // Merge secondary WAL records into primary
+            wal_buffer.merge_secondary_into_primary(&mut pager)?;
+            wal_buffer.flush(&mut pager)?;
+            self.metrics.record_wal_flush();
+
+            // Update header with new snapshot location
+            header.active_snapshot_gen = new_gen;
+            header.snapshot_start_page = new_snapshot_start_page;
+            header.snapshot_page_count = new_snapshot_page_count;
+            header.db_size_pages = new_snapshot_start_page + new_snapshot_page_count;
+            header.max_node_id = self.next_node_id.load(Ordering::SeqCst).saturating_sub(1);
+            header.next_tx_id = self.next_tx_id.load(Ordering::SeqCst);
+
+            // Update WAL state
+            header.wal_head = wal_buffer.head();
+            header.wal_tail = wal_buffer.tail();
+            header.wal_primary_head = wal_buffer.primary_head();
+            header.wal_secondary_head = wal_buffer.secondary_head();
+            header.active_wal_region = 0;
+            header.checkpoint_in_progress = 0;
+            header.change_counter += 1;
+
+            // Write header to disk
+            let header_bytes = header.serialize_to_page();
+            pager.write_page(0, &header_bytes)?;
+            pager.sync()?;
+        }
+
+        self.checkpoint_progress.set_phase(CheckpointPhase::Completing);
+
+        // Clear delta
+        self.delta.write().clear();
+
+        // Reload the new snapshot
+        self.reload_snapshot()?;
+
+        // Same as the blocking `checkpoint` path: a completed checkpoint
+        // folds in everything any pending layer was standing in for.
+        self.dirty_since_checkpoint.lock().clear();
+        self.pending_layers.lock().clear();
+
+        // Mark as idle
+        *self.checkpoint_status.lock() = CheckpointStatus::Idle;
+        self.checkpoint_progress.set_phase(CheckpointPhase::Idle);
+
+        Ok(())
+    }
+
+    /// Recover from a checkpoint error
+    fn recover_from_checkpoint_error(&self) {
+        // Try to switch back to primary region and clear the checkpoint flag
+        if let Some(mut pager) = self.pager.try_lock() {
+            if let Some(mut wal_buffer) = self.wal_buffer.try_lock() {
+                if let Some(mut header) = self.header.try_write() {
+                    // Switch back to primary
+                    wal_buffer.switch_to_primary(false);
+
+                    // Clear checkpoint flag
+                    header.active_wal_region = 0;
+                    header.checkpoint_in_progress = 0;
+
+                    // Try to write header
+                    let header_bytes = header.serialize_to_page();
+                    let _ = pager.write_page(0, &header_bytes);
+                    let _ = pager.sync();
+                }
+            }
+        }
+
+        // Mark as idle
+        *self.checkpoint_status.lock() = CheckpointStatus::Idle;
+        self.checkpoint_progress.set_phase(CheckpointPhase::Idle);
+        self.checkpoint_progress.abort_requested.store(false, Ordering::SeqCst);
+    }
+
+    /// Write snapshot buffer to file pages
+    fn write_snapshot_pages(
+        &self,
+        pager: &mut FilePager,
+        start_page: u32,
+        buffer: &[u8],
+        page_size: usize,
+    ) -> Result<()> {
+        self.write_snapshot_chunk(pager, start_page, buffer, page_size, true)?;
+        Ok(())
+    }
+
+    /// Write one chunk of a snapshot being assembled, optionally deferring
+    /// the `sync`. Used directly (with `sync = false`) by
+    /// `stream_snapshot_to_pager` to flush each page-sized chunk
+    /// `SnapshotStreamWriter` produces without fsyncing after every one;
+    /// `write_snapshot_pages` is just this with `sync = true` for the
+    /// existing single-buffer callers. Returns the number of pages written.
+    fn write_snapshot_chunk(
+        &self,
+        pager: &mut FilePager,
+        start_page: u32,
+        buffer: &[u8],
+        page_size: usize,
+        sync: bool,
+    ) -> Result<u32> {
+        let num_pages = pages_to_store(buffer.len(), page_size);
+
+        // Ensure file is large enough
+        let required_pages = start_page + num_pages;
+        let current_pages = (pager.file_size() as usize + page_size - 1) / page_size;
+
+        if required_pages as usize > current_pages {
+            pager.allocate_pages(required_pages - current_pages as u32)?;
+        }
+
+        // Write pages
+        for i in 0..num_pages {
+            let mut page_data = vec![0u8; page_size];
+            let src_offset = i as usize * page_size;
+            let src_end = std::cmp::min(src_offset + page_size, buffer.len());
+            page_data[..src_end - src_offset].copy_from_slice(&buffer[src_offset..src_end]);
+            pager.write_page(start_page + i, &page_data)?;
+        }
+
+        if sync {
+            pager.sync()?;
+        }
+        Ok(num_pages)
+    }
+
+    /// Copy the label/etype/propkey name maps, the schema half of
+    /// `collect_graph_data`. These are small (one entry per distinct name
+    /// ever used) so, unlike nodes and edges, there's no memory benefit to
+    /// streaming them.
+    fn collect_schema_maps(
+        &self,
+    ) -> (
+        HashMap<LabelId, String>,
+        HashMap<ETypeId, String>,
+        HashMap<PropKeyId, String>,
+    ) {
+        let mut labels = HashMap::new();
+        let mut etypes = HashMap::new();
+        let mut propkeys = HashMap::new();
+        for (&id, name) in self.label_ids.read().iter() {
+            labels.insert(id, name.clone());
+        }
+        for (&id, name) in self.etype_ids.read().iter() {
+            etypes.insert(id, name.clone());
+        }
+        for (&id, name) in self.propkey_ids.read().iter() {
+            propkeys.insert(id, name.clone());
+        }
+        (labels, etypes, propkeys)
+    }
+
+    /// Collect all graph data from snapshot + delta
+    fn collect_graph_data(&self) -> (
+        Vec<NodeData>,
+        Vec<EdgeData>,
+        HashMap<LabelId, String>,
+        HashMap<ETypeId, String>,
+        HashMap<PropKeyId, String>,
+    ) {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        let (labels, etypes, propkeys) = self.collect_schema_maps();
+
+        let delta = self.delta.read();
+
+        // Collect nodes from snapshot
+        if let Some(ref snapshot) = *self.snapshot.read() {
+            let num_nodes = snapshot.header.num_nodes as usize;
+
+            for phys in 0..num_nodes {
+                let node_id = match snapshot.get_node_id(phys as u32) {
+                    Some(id) => id,
+                    None => continue,
+                };
+
+                // Skip deleted nodes
+                if delta.is_node_deleted(node_id) {
+                    continue;
+                }
+
+                // Get key
+                let key = snapshot.get_node_key(phys as u32);
+
+                // Get properties from snapshot
+                let mut props = HashMap::new();
+                if let Some(snapshot_props) = snapshot.get_node_props(phys as u32) {
+                    for (key_id, value) in snapshot_props {
+                        props.insert(key_id, value);
+                    }
+                }
+
+                // Apply delta modifications
+                if let Some(node_delta) = delta.get_node_delta(node_id) {
+                    if let Some(ref delta_props) = node_delta.props {
+                        for (&key_id, value) in delta_props {
+                            match value {
+                                Some(v) => { props.insert(key_id, v.clone()); }
+                                None => { props.remove(&key_id); }
+                            }
+                        }
+                    }
+                }
+
+                // Collect node labels (simplified - labels handled differently in real impl)
+                let node_labels = Vec::new();
+
+                nodes.push(NodeData {
+                    node_id,
+                    key,
+                    labels: node_labels,
+                    props,
+                });
+
+                // Collect edges from this node
+                for edge_info in snapshot.get_out_edges(phys as u32) {
+                    let dst_node_id = match snapshot.get_node_id(edge_info.dst) {
+                        Some(id) => id,
+                        None => continue,
+                    };
+
+                    // Skip edges to deleted nodes
+                    if delta.is_node_deleted(dst_node_id) {
+                        continue;
+                    }
+
+                    // Skip deleted edges
+                    if delta.is_edge_deleted(node_id, edge_info.etype, dst_node_id) {
+                        continue;
+                    }
+
+                    // Get edge props, snapshot-decoded then delta-patched
+                    let edge_props = snapshot
+                        .get_edge_props(phys as u32, edge_info.etype, edge_info.dst)
+                        .unwrap_or_default();
+                    let edge_props = merge_edge_props(edge_props, &delta, node_id, edge_info.etype, dst_node_id);
+
+                    edges.push(EdgeData {
+                        src: node_id,
+                        etype: edge_info.etype,
+                        dst: dst_node_id,
+                        props: edge_props,
+                    });
+                }
+            }
+        }
+
+        // Add nodes created in delta
+        for (&node_id, node_delta) in &delta.created_nodes {
+            let mut props = HashMap::new();
+            if let Some(ref delta_props) = node_delta.props {
+                for (&key_id, value) in delta_props {
+                    if let Some(v) = value {
+                        props.insert(key_id, v.clone());
+                    }
+                }
+            }
+
+            nodes.push(NodeData {
+                node_id,
+                key: node_delta.key.clone(),
+                labels: Vec::new(),
+                props,
+            });
+        }
+
+        // Add edges from delta
+        for (&src, patches) in &delta.out_add {
+            // Skip edges from deleted nodes
+            if delta.is_node_deleted(src) {
+                continue;
+            }
+
+            for patch in patches {
+                // Skip edges to deleted nodes
+                if delta.is_node_deleted(patch.other) {
+                    continue;
+                }
+
+                edges.push(EdgeData {
+                    src,
+                    etype: patch.etype,
+                    dst: patch.other,
+                    props: merge_edge_props(HashMap::new(), &delta, src, patch.etype, patch.other),
+                });
+            }
+        }
+
+        (nodes, edges, labels, etypes, propkeys)
+    }
+
+    /// Stream-build and write a new snapshot directly to `pager`, one
+    /// page-sized chunk at a time, instead of materializing the whole
+    /// snapshot into one buffer like `build_snapshot_buffer` does -- so
+    /// checkpoint memory stays proportional to a page buffer plus
+    /// `SnapshotStreamWriter`'s running index/offset tables, not to the size
+    /// of the graph being checkpointed. Used by `build_and_write_snapshot`
+    /// (the background-checkpoint path, where large-graph memory pressure
+    /// matters most); `checkpoint`/`compact_into` still build a single
+    /// buffer via `build_snapshot_buffer` and are reasonable next
+    /// candidates to switch over.
+    ///
+    /// Reports progress and checks for an abort request via `progress`
+    /// after every node/edge, returning `RayError::CheckpointAborted` early
+    /// if one comes in. Returns the number of pages written.
+    fn stream_snapshot_to_pager(
+        &self,
+        pager: &mut FilePager,
+        start_page: u32,
+        page_size: usize,
+        generation: u64,
+        labels: HashMap<LabelId, String>,
+        etypes: HashMap<ETypeId, String>,
+        propkeys: HashMap<PropKeyId, String>,
+        progress: &CheckpointProgress,
+    ) -> Result<u64> {
+        let started = std::time::Instant::now();
+        let mut writer = crate::core::snapshot::writer::SnapshotStreamWriter::new(
+            generation,
+            page_size,
+            self.default_compression.map(Self::writer_compression),
+        );
+        writer.set_schema(labels, etypes, propkeys);
+
+        let snapshot_guard = self.snapshot.read();
+        let delta_guard = self.delta.read();
+
+        // Rough up-front totals for progress reporting. The node total can
+        // differ slightly once deletions are applied below; the edge total
+        // counts only edges added in the delta, not the snapshot's own, so
+        // it can fall well short. Both are only used to report a completion
+        // fraction, not for correctness.
+        let estimated_nodes = snapshot_guard.as_ref().map(|s| s.header.num_nodes as u64).unwrap_or(0)
+            + delta_guard.created_nodes.len() as u64;
+        let estimated_edges: u64 = delta_guard.out_add.values().map(|patches| patches.len() as u64).sum();
+        progress.total_nodes.store(estimated_nodes, Ordering::SeqCst);
+        progress.total_edges.store(estimated_edges, Ordering::SeqCst);
+
+        let mut next_page = start_page;
+        let mut compressed_bytes: u64 = 0;
+        for entity in graph_entities_iter(&snapshot_guard, &delta_guard) {
+            if progress.abort_requested() {
+                return Err(RayError::CheckpointAborted);
+            }
+
+            match entity {
+                GraphEntity::Node(node) => {
+                    writer.push_node(node)?;
+                    progress.nodes_written.fetch_add(1, Ordering::SeqCst);
+                }
+                GraphEntity::Edge(edge) => {
+                    writer.push_edge(edge)?;
+                    progress.edges_written.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+
+            // Flush whatever whole pages the writer has ready rather than
+            // holding them in memory until the end.
+            let ready = writer.drain_ready_pages();
+            if !ready.is_empty() {
+                compressed_bytes += ready.len() as u64;
+                next_page += self.write_snapshot_chunk(pager, next_page, &ready, page_size, false)?;
+            }
+        }
+
+        // Final (possibly partial) page plus the footer/index the writer
+        // held back until every node/edge was seen.
+        let output = writer.finish()?;
+        compressed_bytes += output.tail.len() as u64;
+        next_page += self.write_snapshot_chunk(pager, next_page, &output.tail, page_size, false)?;
+        pager.sync()?;
+
+        *self.last_compression_stats.write() = Some(CompressionStats {
+            uncompressed_bytes: output.uncompressed_len as u64,
+            compressed_bytes,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        });
+
+        Ok((next_page - start_page) as u64)
+    }
+
+    /// Check if checkpoint is recommended based on WAL usage
+    pub fn should_checkpoint(&self, threshold: f64) -> bool {
+        let stats = self.wal_stats();
+        stats.used as f64 / stats.capacity as f64 >= threshold
+    }
+
+    // ========================================================================
+    // Batched Auto-Checkpoints
+    //
+    // Despite the name similarity, this is not LSM-style tiered storage or
+    // incremental (delta) snapshotting: `PendingLayer` markers hold no data
+    // and nothing ever reads a prior layer back out. See
+    // `checkpoint_incremental`'s doc comment below for exactly what this
+    // does and doesn't buy.
+    // ========================================================================
+
+    /// Auto-checkpoint entry point used in place of a direct `checkpoint()`
+    /// call: instead of rewriting the whole snapshot every time the WAL
+    /// crosses `checkpoint_threshold`, this cuts a cheap [`PendingLayer`]
+    /// marker recording how many nodes were touched since the last real
+    /// checkpoint, and only runs an actual `checkpoint()` once
+    /// [`MAX_PENDING_LAYERS`] of those have piled up.
+    ///
+    /// This amortizes the *frequency* of full snapshot rewrites under
+    /// steady write load, which is the main cost `should_checkpoint`
+    /// threshold-crossings otherwise pay on every crossing. It does not
+    /// make an individual checkpoint itself cheaper or proportional to
+    /// what changed -- doing that would mean writing snapshot generations
+    /// as true on-disk deltas against the previous one, which needs a
+    /// format this pager doesn't have. Call [`SingleFileDB::checkpoint`]
+    /// directly if you need a real checkpoint unconditionally, or
+    /// [`SingleFileDB::compact_layers`] to force any accumulated layers to
+    /// fold in right now.
+    pub fn checkpoint_incremental(&self) -> Result<()> {
+        let dirty_nodes = {
+            let mut dirty = self.dirty_since_checkpoint.lock();
+            if dirty.is_empty() {
+                return Ok(());
+            }
+            std::mem::take(&mut *dirty).len()
+        };
+
+        let layer_count = {
+            let mut layers = self.pending_layers.lock();
+            layers.push_back(PendingLayer {
+                generation: self.header.read().active_snapshot_gen,
+                dirty_nodes,
+            });
+            while layers.len() > MAX_PENDING_LAYERS {
+                layers.pop_front();
+            }
+            layers.len()
+        };
+
+        if layer_count >= MAX_PENDING_LAYERS {
+            self.run_batched_checkpoint()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Force any [`PendingLayer`] markers accumulated by
+    /// `checkpoint_incremental` to fold into one real `checkpoint` right
+    /// now, regardless of how many have piled up. A no-op (cheap `Ok(())`)
+    /// if nothing is pending.
+    pub fn compact_layers(&self) -> Result<()> {
+        if self.pending_layers.lock().is_empty() {
+            return Ok(());
+        }
+        self.run_batched_checkpoint()
+    }
+
+    /// Runs the real checkpoint a batch of pending layers folds into,
+    /// honoring the same `background_checkpoint` config switch the old
+    /// direct auto-checkpoint call site used to check itself.
+    fn run_batched_checkpoint(&self) -> Result<()> {
+        if self.background_checkpoint {
+            self.background_checkpoint()
+        } else {
+            self.checkpoint()
+        }
+    }
+
+    /// How many [`PendingLayer`] markers are currently batched up waiting
+    /// for `checkpoint_incremental` or `compact_layers` to fold them into
+    /// a real checkpoint.
+    pub fn pending_layer_count(&self) -> usize {
+        self.pending_layers.lock().len()
+    }
+
+    // ========================================================================
+    // MVCC Read Snapshots
+    // ========================================================================
+
+    /// Pin a consistent, repeatable-read view of the graph as of right now,
+    /// inspired by RocksDB's `Snapshot`. Unlike the normal read methods on
+    /// `SingleFileDB` (which always merge the live delta and currently
+    /// mapped snapshot, so a long sequence of reads can observe concurrent
+    /// commits and checkpoints), every read through the returned
+    /// [`ReadSnapshot`] sees exactly the state committed as of this call.
+    pub fn snapshot(&self) -> ReadSnapshot<'_> {
+        let generation = self.header.read().active_snapshot_gen;
+        let commit_seq = self.next_version.load(Ordering::SeqCst);
+        let snapshot = self.snapshot.read().clone();
+        // An open write transaction's mutations already sit in the live
+        // delta; take the delta as it stood at that transaction's `begin`.
+        let start_delta = self.current_tx.lock().as_ref().map(|tx| tx.start_delta.clone());
+        let delta = start_delta.unwrap_or_else(|| self.delta.read().clone());
+
+        *self.snapshot_refs.lock().entry(generation).or_insert(0) += 1;
+
+        ReadSnapshot {
+            db: self,
+            generation,
+            commit_seq,
+            snapshot,
+            delta,
+        }
+    }
+
+    /// Refuse to proceed if a live [`ReadSnapshot`] still pins the currently
+    /// mapped snapshot generation. Called by `checkpoint` and
+    /// `background_checkpoint` before they overwrite that generation's
+    /// pages -- without this, a pinned handle's mapped view would be
+    /// corrupted out from under it, since both the old and new snapshot
+    /// occupy the same file offset.
+    fn require_no_pinned_snapshot(&self) -> Result<()> {
+        let generation = self.header.read().active_snapshot_gen;
+        if self.snapshot_refs.lock().get(&generation).copied().unwrap_or(0) > 0 {
+            return Err(RayError::SnapshotPinned);
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Optimistic Transactions
+    // ========================================================================
+
+    /// Begin an [`OptimisticTxn`], following the OptimisticTransactionDB
+    /// pattern: unlike `begin`, several of these can be built concurrently
+    /// against this database -- conflicts are only detected when one of them
+    /// calls [`OptimisticTxn::commit`].
+    pub fn begin_optimistic(&self) -> OptimisticTxn<'_> {
+        self.begin_optimistic_with_durability(self.default_durability)
+    }
+
+    /// Begin an [`OptimisticTxn`] that commits with the given [`Durability`]
+    /// instead of the database's default.
+    pub fn begin_optimistic_with_durability(&self, durability: Durability) -> OptimisticTxn<'_> {
+        OptimisticTxn {
+            db: self,
+            durability,
+            local_delta: self.delta.read().clone(),
+            ops: Vec::new(),
+            read_set: HashMap::new(),
+            base_versions: self.committed_versions.read().clone(),
+        }
+    }
+
+    // ========================================================================
+    // Named Subgraphs ("Column Families")
+    // ========================================================================
+
+    /// Register a new named subgraph, analogous to a RocksDB column family:
+    /// its own node-id sequence and edge-type/property-key name tables,
+    /// isolated from the default namespace and every other named one while
+    /// still living in this same `.raydb` file and WAL. Returns
+    /// `RayError::Internal` if `name` is already registered, or once all
+    /// 2^16 - 1 namespace ids have been handed out.
+    pub fn create_graph(&self, name: &str) -> Result<NamespaceId> {
+        let mut namespaces = self.namespaces.write();
+        if namespaces.contains_key(name) {
+            return Err(RayError::Internal(format!(
+                "graph namespace '{}' already exists",
+                name
+            )));
+        }
+
+        let id = self.next_namespace_id.load(Ordering::SeqCst);
+        if id >= 1 << NAMESPACE_ID_BITS {
+            return Err(RayError::Internal("graph namespace ids exhausted".to_string()));
+        }
+        self.next_namespace_id.store(id + 1, Ordering::SeqCst);
+        namespaces.insert(
+            name.to_string(),
+            std::sync::Arc::new(GraphNamespace {
+                id,
+                next_node_seq: AtomicU64::new(1),
+                etype_names: RwLock::new(HashMap::new()),
+                etype_ids: RwLock::new(HashMap::new()),
+                propkey_names: RwLock::new(HashMap::new()),
+                propkey_ids: RwLock::new(HashMap::new()),
+                snapshot_page_count: AtomicU64::new(0),
+                active_snapshot_gen: AtomicU64::new(0),
+            }),
+        );
+        Ok(id)
+    }
+
+    /// Get a handle to a previously registered named subgraph, or `None` if
+    /// `name` hasn't been registered via `create_graph`.
+    pub fn graph_handle(&self, name: &str) -> Option<GraphHandle<'_>> {
+        let ns = self.namespaces.read().get(name)?.clone();
+        Some(GraphHandle { db: self, ns })
+    }
+
+    /// Record every registered namespace's current page count / snapshot
+    /// generation. Called at the end of `checkpoint`; see [`GraphNamespace`]
+    /// for why this is bookkeeping for a future scheduler rather than
+    /// something `checkpoint` itself consults yet.
+    fn record_namespace_checkpoints(&self, page_count: u64, generation: u64) {
+        for ns in self.namespaces.read().values() {
+            ns.snapshot_page_count.store(page_count, Ordering::SeqCst);
+            ns.active_snapshot_gen.store(generation, Ordering::SeqCst);
+        }
+    }
+
+    // ========================================================================
+    // Query / Read Operations
+    // ========================================================================
+
+    /// Get all properties for a node
+    ///
+    /// Returns None if the node doesn't exist or is deleted.
+    /// Merges properties from snapshot with delta modifications.
+    ///
+    /// The snapshot side is served through [`NodePropCache`], so repeat
+    /// reads of the same hot node skip re-decoding its page; the delta
+    /// overlay is cheap already and always applied fresh. `collect_graph_data`
+    /// deliberately bypasses this cache -- it decodes every node exactly
+    /// once on its way to being replaced by a fresh snapshot, so caching
+    /// would only add lock overhead for no reuse.
+    pub fn get_node_props(&self, node_id: NodeId) -> Option<HashMap<PropKeyId, PropValue>> {
+        let delta = self.delta.read();
+
+        // Check if node is deleted
+        if delta.is_node_deleted(node_id) {
+            return None;
+        }
+
+        let mut props = HashMap::new();
+        let snapshot = self.snapshot.read();
+
+        // Get properties from snapshot first, decoding the page only on a
+        // cache miss.
+        if let Some(ref snap) = *snapshot {
+            if let Some(phys) = snap.get_phys_node(node_id) {
+                let cached = self
+                    .node_prop_cache
+                    .lock()
+                    .get_or_decode(phys, || snap.get_node_props(phys));
+                if let Some(snapshot_props) = cached {
+                    props = snapshot_props;
+                }
+            }
+        }
+
+        // Apply delta modifications
+        if let Some(node_delta) = delta.get_node_delta(node_id) {
+            if let Some(ref delta_props) = node_delta.props {
+                for (&key_id, value) in delta_props {
+                    match value {
+                        Some(v) => { props.insert(key_id, v.clone()); }
+                        None => { props.remove(&key_id); }
+                    }
+                }
+            }
+        }
+
+        // Check if node exists at all
+        let node_exists_in_delta = delta.is_node_created(node_id) 
+            || delta.get_node_delta(node_id).is_some();
+        
+        if !node_exists_in_delta {
+            if let Some(ref snap) = *snapshot {
+                if snap.get_phys_node(node_id).is_none() {
+                    return None;
+                }
+            } else {
+                // No snapshot and node not in delta
+                return None;
+            }
+        }
+
+        Some(props)
+    }
+
+    /// Get a specific property for a node
+    /// 
+    /// Returns None if the node doesn't exist, is deleted, or doesn't have the property.
+    pub fn get_node_prop(&self, node_id: NodeId, key_id: PropKeyId) -> Option<PropValue> {
+        let delta = self.delta.read();
+
+        // Check if node is deleted
+        if delta.is_node_deleted(node_id) {
+            return None;
+        }
+
+        // Check delta first (for modifications)
+        if let Some(node_delta) = delta.get_node_delta(node_id) {
+            if let Some(ref delta_props) = node_delta.props {
+                if let Some(value) = delta_props.get(&key_id) {
+                    // None means explicitly deleted
+                    self.metrics.record_read_from_delta();
+                    return value.clone();
+                }
+            }
+        }
+
+        // Fall back to snapshot
+        let snapshot = self.snapshot.read();
+        if let Some(ref snap) = *snapshot {
+            if let Some(phys) = snap.get_phys_node(node_id) {
+                self.metrics.record_read_from_snapshot();
+                return snap.get_node_prop(phys, key_id);
+            }
+        }
+
+        // Check if node exists at all (in delta as created)
+        if delta.is_node_created(node_id) {
+            // Node exists but doesn't have this property
+            return None;
+        }
+
+        None
+    }
+
+    /// Get all properties for an edge
+    ///
+    /// Returns `None` if the edge doesn't exist (or either endpoint is
+    /// deleted). Merges properties decoded from the snapshot with delta
+    /// modifications, exactly like [`get_node_props`](Self::get_node_props).
+    pub fn get_edge_props(&self, src: NodeId, etype: ETypeId, dst: NodeId) -> Option<HashMap<PropKeyId, PropValue>> {
+        let delta = self.delta.read();
+
+        if delta.is_edge_deleted(src, etype, dst) {
+            return None;
+        }
+
+        let mut props = HashMap::new();
+        let snapshot = self.snapshot.read();
+        let mut found = delta.is_edge_added(src, etype, dst);
+        if let Some(ref snap) = *snapshot {
+            if let (Some(src_phys), Some(dst_phys)) =
+                (snap.get_phys_node(src), snap.get_phys_node(dst))
+            {
+                if snap.has_edge(src_phys, etype, dst_phys) {
+                    found = true;
+                }
+                if let Some(snapshot_props) = snap.get_edge_props(src_phys, etype, dst_phys) {
+                    props = snapshot_props;
+                }
+            }
+        }
+
+        if !found {
+            return None;
+        }
+
+        Some(merge_edge_props(props, &delta, src, etype, dst))
+    }
+
+    /// Get a specific property for an edge
+    ///
+    /// Returns `None` if the edge doesn't exist or doesn't have the property.
+    pub fn get_edge_prop(&self, src: NodeId, etype: ETypeId, dst: NodeId, key_id: PropKeyId) -> Option<PropValue> {
+        let delta = self.delta.read();
+
+        // Check delta first (for modifications)
+        if let Some(value) = delta.get_edge_prop(src, etype, dst, key_id) {
+            // None means explicitly deleted
+            return value.cloned();
+        }
+
+        // Fall back to snapshot
+        let snapshot = self.snapshot.read();
+        if let Some(ref snap) = *snapshot {
+            if let (Some(src_phys), Some(dst_phys)) =
+                (snap.get_phys_node(src), snap.get_phys_node(dst))
+            {
+                return snap.get_edge_prop(src_phys, etype, dst_phys, key_id);
+            }
+        }
+
+        None
+    }
+
+    /// Get outgoing edges for a node
+    /// 
+    /// Returns edges as (edge_type_id, destination_node_id) pairs.
+    /// Merges edges from snapshot with delta additions/deletions.
+    /// Filters out edges to deleted nodes.
+    pub fn get_out_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+        let delta = self.delta.read();
+
+        // If node is deleted, no edges
+        if delta.is_node_deleted(node_id) {
+            return Vec::new();
+        }
+
+        let mut edges = Vec::new();
+        let snapshot = self.snapshot.read();
+
+        // Get edges from snapshot
+        if let Some(ref snap) = *snapshot {
+            if let Some(phys) = snap.get_phys_node(node_id) {
+                for (dst_phys, etype) in snap.iter_out_edges(phys) {
+                    // Convert physical dst to NodeId
+                    if let Some(dst_node_id) = snap.get_node_id(dst_phys) {
+                        // Skip edges to deleted nodes
+                        if delta.is_node_deleted(dst_node_id) {
+                            continue;
+                        }
+                        // Skip edges deleted in delta
+                        if delta.is_edge_deleted(node_id, etype, dst_node_id) {
+                            continue;
+                        }
+                        edges.push((etype, dst_node_id));
+                    }
+                }
+            }
+        }
+
+        // Add edges from delta
+        if let Some(added_edges) = delta.out_add.get(&node_id) {
+            for edge_patch in added_edges {
+                // Skip edges to deleted nodes
+                if delta.is_node_deleted(edge_patch.other) {
+                    continue;
+                }
+                edges.push((edge_patch.etype, edge_patch.other));
+            }
+        }
+
+        // Sort by (etype, dst) for consistent ordering
+        edges.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+        edges
+    }
+
+    /// Get incoming edges for a node
+    /// 
+    /// Returns edges as (edge_type_id, source_node_id) pairs.
+    /// Merges edges from snapshot with delta additions/deletions.
+    /// Filters out edges from deleted nodes.
+    pub fn get_in_edges(&self, node_id: NodeId) -> Vec<(ETypeId, NodeId)> {
+        let delta = self.delta.read();
+
+        // If node is deleted, no edges
+        if delta.is_node_deleted(node_id) {
+            return Vec::new();
+        }
+
+        let mut edges = Vec::new();
+        let snapshot = self.snapshot.read();
+
+        // Get edges from snapshot
+        if let Some(ref snap) = *snapshot {
+            if let Some(phys) = snap.get_phys_node(node_id) {
+                for (src_phys, etype, _out_index) in snap.iter_in_edges(phys) {
+                    // Convert physical src to NodeId
+                    if let Some(src_node_id) = snap.get_node_id(src_phys) {
+                        // Skip edges from deleted nodes
+                        if delta.is_node_deleted(src_node_id) {
+                            continue;
+                        }
+                        // Skip edges deleted in delta
+                        if delta.is_edge_deleted(src_node_id, etype, node_id) {
+                            continue;
+                        }
+                        edges.push((etype, src_node_id));
+                    }
+                }
+            }
+        }
+
+        // Add edges from delta (in_add stores patches where other=src)
+        if let Some(added_edges) = delta.in_add.get(&node_id) {
+            for edge_patch in added_edges {
+                // Skip edges from deleted nodes
+                if delta.is_node_deleted(edge_patch.other) {
+                    continue;
+                }
+                edges.push((edge_patch.etype, edge_patch.other));
+            }
+        }
+
+        // Sort by (etype, src) for consistent ordering
+        edges.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+        edges
+    }
+
+    /// Get out-degree (number of outgoing edges) for a node
+    pub fn get_out_degree(&self, node_id: NodeId) -> usize {
+        self.get_out_edges(node_id).len()
+    }
+
+    /// Get in-degree (number of incoming edges) for a node
+    pub fn get_in_degree(&self, node_id: NodeId) -> usize {
+        self.get_in_edges(node_id).len()
+    }
+
+    /// Look up a node by its key
+    /// 
+    /// Returns the NodeId if found, None otherwise.
+    /// Checks delta key index first, then falls back to snapshot.
+    pub fn get_node_by_key(&self, key: &str) -> Option<NodeId> {
+        let delta = self.delta.read();
+
+        // Check the delta's key index first; a tombstone short-circuits
+        // straight to "not found" without even consulting the snapshot.
+        match delta.key_index.get(key) {
+            Some(&node_id) if node_id == KEY_TOMBSTONE => return None,
+            Some(&node_id) if !delta.is_node_deleted(node_id) => return Some(node_id),
+            _ => {}
+        }
+
+        // Fall back to snapshot
+        let snapshot = self.snapshot.read();
+        if let Some(ref snap) = *snapshot {
+            if let Some(node_id) = snap.lookup_by_key(key) {
+                // Verify node isn't deleted in delta
+                if !delta.is_node_deleted(node_id) {
+                    return Some(node_id);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Find all live node keys starting with `prefix`, in key order.
+    /// HashMap-and-scan-backed, not FST-backed: despite the request that
+    /// added this method being titled for an FST-backed index, neither
+    /// side of the union is an actual finite-state-transducer today -- the
+    /// hard side is a per-call sort of a full node scan and the soft side
+    /// is a sorted `HashMap` snapshot, not an `fst::Map`. Serializing a
+    /// real FST into the snapshot (`build_snapshot_buffer`/
+    /// `write_snapshot_pages` on the write side, `SnapshotData::
+    /// parse_at_offset` on the read side) so the hard side stops needing a
+    /// per-call scan is still unbuilt follow-up work, not something this
+    /// or the `key_union_matching` commit delivers.
+    ///
+    /// Resolves the same way `get_node_by_key` does: a key present in both
+    /// the delta (the "soft" map) and the snapshot (the "hard" map) yields
+    /// the delta's node id, and a soft [`crate::core::delta::KEY_TOMBSTONE`]
+    /// suppresses the hard entry entirely rather than both being returned.
+    ///
+    /// The soft side of the merge is naturally small and cheap to sort on
+    /// each call. The snapshot side doesn't persist an ordered key
+    /// structure in this tree yet -- `SnapshotData` only exposes point
+    /// lookups via `lookup_by_key` -- so this still scans every live node
+    /// once to build the hard side, then sorts it and walks both sides as a
+    /// proper two-pointer merge (see `key_union_matching`).
+    pub fn get_nodes_by_prefix(&self, prefix: &str) -> Vec<NodeId> {
+        self.key_union_matching(|key| key.starts_with(prefix))
+    }
+
+    /// Find all live node keys in the inclusive range `[lo, hi]`, in key
+    /// order. See [`SingleFileDB::get_nodes_by_prefix`] for how the union
+    /// and tombstones are resolved.
+    pub fn get_nodes_in_range(&self, lo: &str, hi: &str) -> Vec<NodeId> {
+        self.key_union_matching(|key| key >= lo && key <= hi)
+    }
+
+    /// Shared implementation for [`SingleFileDB::get_nodes_by_prefix`] and
+    /// [`SingleFileDB::get_nodes_in_range`]: a sorted-union merge of the
+    /// snapshot's ("hard") keys and the delta's ("soft") keys, in the style
+    /// MeiliSearch uses for its soft/hard external-id maps -- whichever
+    /// side is lexically behind advances on its own, a key shared by both
+    /// lets the soft entry win, and a soft tombstone drops the key even
+    /// though the hard side still has it.
+    fn key_union_matching(&self, matches: impl Fn(&str) -> bool) -> Vec<NodeId> {
+        let delta = self.delta.read();
+
+        let mut hard: Vec<(String, NodeId)> = Vec::new();
+        if let Some(ref snapshot) = *self.snapshot.read() {
+            let num_nodes = snapshot.header.num_nodes as usize;
+            for phys in 0..num_nodes {
+                let Some(node_id) = snapshot.get_node_id(phys as u32) else { continue };
+                if delta.is_node_deleted(node_id) {
+                    continue;
+                }
+                let Some(key) = snapshot.get_node_key(phys as u32) else { continue };
+                if matches(&key) {
+                    hard.push((key, node_id));
+                }
+            }
+            hard.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        let soft = delta.soft_keys_matching(&matches);
+
+        let mut result = Vec::with_capacity(hard.len() + soft.len());
+        let mut hi = 0;
+        let mut si = 0;
+        while hi < hard.len() || si < soft.len() {
+            match (hard.get(hi), soft.get(si)) {
+                (Some((hk, hv)), Some((sk, sv))) => match hk.cmp(sk) {
+                    std::cmp::Ordering::Less => {
+                        result.push(*hv);
+                        hi += 1;
+                    }
+                    std::cmp::Ordering::Greater => {
+                        if let Some(node_id) = sv {
+                            result.push(*node_id);
+                        }
+                        si += 1;
+                    }
+                    std::cmp::Ordering::Equal => {
+                        if let Some(node_id) = sv {
+                            result.push(*node_id);
+                        }
+                        hi += 1;
+                        si += 1;
+                    }
+                },
+                (Some((_, hv)), None) => {
+                    result.push(*hv);
+                    hi += 1;
+                }
+                (None, Some((_, sv))) => {
+                    if let Some(node_id) = sv {
+                        result.push(*node_id);
+                    }
+                    si += 1;
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+        result
+    }
+
+    /// Get the key for a node
+    /// 
+    /// Returns the key string if the node has one, None otherwise.
+    pub fn get_node_key(&self, node_id: NodeId) -> Option<String> {
+        let delta = self.delta.read();
+
+        // Check if node is deleted
+        if delta.is_node_deleted(node_id) {
+            return None;
+        }
+
+        // Check created nodes in delta first
+        if let Some(node_delta) = delta.created_nodes.get(&node_id) {
+            return node_delta.key.clone();
+        }
+
+        // Fall back to snapshot
+        let snapshot = self.snapshot.read();
+        if let Some(ref snap) = *snapshot {
+            if let Some(phys) = snap.get_phys_node(node_id) {
+                return snap.get_node_key(phys);
+            }
+        }
+
+        None
+    }
+
+    /// Get neighbors via outgoing edges of a specific type
+    /// 
+    /// Returns destination node IDs for edges of the given type.
+    pub fn get_out_neighbors(&self, node_id: NodeId, etype: ETypeId) -> Vec<NodeId> {
+        self.get_out_edges(node_id)
+            .into_iter()
+            .filter(|(e, _)| *e == etype)
+            .map(|(_, dst)| dst)
+            .collect()
+    }
+
+    /// Get neighbors via incoming edges of a specific type
+    /// 
+    /// Returns source node IDs for edges of the given type.
+    pub fn get_in_neighbors(&self, node_id: NodeId, etype: ETypeId) -> Vec<NodeId> {
+        self.get_in_edges(node_id)
+            .into_iter()
+            .filter(|(e, _)| *e == etype)
+            .map(|(_, src)| src)
+            .collect()
+    }
+
+    // ========================================================================
+    // Graph Traversal
+    // ========================================================================
+
+    /// All nodes transitively reachable from `from` by following edges whose
+    /// type is in `etypes`, evaluated with semi-naive epoch expansion on top
+    /// of `get_out_edges`: each epoch expands only the nodes discovered in
+    /// the previous one, so no edge is walked more than once. `from` itself
+    /// is never included in the result. Stops once an epoch discovers
+    /// nothing new, or once `max_depth` epochs have run if given. Returned
+    /// in ascending `NodeId` order.
+    pub fn reachable(&self, from: NodeId, etypes: &[ETypeId], max_depth: Option<usize>) -> Vec<NodeId> {
+        let mut scratch = self.traversal_scratch.lock();
+        scratch.result.clear();
+        scratch.frontier.clear();
+        scratch.next_frontier.clear();
+        scratch.result.insert(from);
+        scratch.frontier.push(from);
+
+        let mut depth = 0;
+        while !scratch.frontier.is_empty() && max_depth.map_or(true, |max| depth < max) {
+            for i in 0..scratch.frontier.len() {
+                let node = scratch.frontier[i];
+                for (etype, dst) in self.get_out_edges(node) {
+                    if etypes.contains(&etype) && scratch.result.insert(dst) {
+                        scratch.next_frontier.push(dst);
+                    }
+                }
+            }
+            std::mem::swap(&mut scratch.frontier, &mut scratch.next_frontier);
+            scratch.next_frontier.clear();
+            depth += 1;
+        }
+
+        scratch.result.remove(&from);
+        let mut out: Vec<NodeId> = scratch.result.iter().copied().collect();
+        out.sort_unstable();
+        out
+    }
+
+    /// Shortest path (by edge count) from `from` to `to` following edge
+    /// types in `etypes`, via the same semi-naive epoch expansion as
+    /// [`SingleFileDB::reachable`]: each node records the predecessor it was
+    /// first discovered from, so once `to` is reached the path is
+    /// reconstructed by walking predecessors back to `from`. Returns `None`
+    /// if `to` isn't reachable at all.
+    pub fn shortest_path(&self, from: NodeId, to: NodeId, etypes: &[ETypeId]) -> Option<Vec<NodeId>> {
+        if from == to {
+            return Some(vec![from]);
+        }
+
+        let mut scratch = self.traversal_scratch.lock();
+        scratch.result.clear();
+        scratch.frontier.clear();
+        scratch.next_frontier.clear();
+        scratch.result.insert(from);
+        scratch.frontier.push(from);
+
+        let mut predecessors: HashMap<NodeId, NodeId> = HashMap::new();
+
+        while !scratch.frontier.is_empty() {
+            for i in 0..scratch.frontier.len() {
+                let node = scratch.frontier[i];
+                for (etype, dst) in self.get_out_edges(node) {
+                    if !etypes.contains(&etype) {
+                        continue;
+                    }
+                    if scratch.result.insert(dst) {
+                        predecessors.insert(dst, node);
+                        if dst == to {
+                            return Some(reconstruct_traversal_path(&predecessors, from, to));
+                        }
+                        scratch.next_frontier.push(dst);
+                    }
+                }
+            }
+            std::mem::swap(&mut scratch.frontier, &mut scratch.next_frontier);
+            scratch.next_frontier.clear();
+        }
+
+        None
+    }
+
+    /// Check if there are any outgoing edges of a specific type
+    pub fn has_out_edges(&self, node_id: NodeId, etype: ETypeId) -> bool {
+        self.get_out_edges(node_id)
+            .iter()
+            .any(|(e, _)| *e == etype)
+    }
+
+    /// Check if there are any incoming edges of a specific type
+    pub fn has_in_edges(&self, node_id: NodeId, etype: ETypeId) -> bool {
+        self.get_in_edges(node_id)
+            .iter()
+            .any(|(e, _)| *e == etype)
+    }
+}
+
+// ============================================================================
+// Utility Functions
+// ============================================================================
+
+/// Check if a path is a single-file database
+pub fn is_single_file_path<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .extension()
+        .map(|ext| ext == "raydb")
+        .unwrap_or(false)
+}
+
+/// Get the single-file extension
+pub fn single_file_extension() -> &'static str {
+    EXT_RAYDB
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_open_new_single_file_db() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        assert!(!db.read_only);
+        assert_eq!(db.header.read().page_size, DEFAULT_PAGE_SIZE as u32);
+
+        close_single_file(db).unwrap();
+
+        // Clean up
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_reopen_single_file_db() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        // Create database
+        {
+            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+            close_single_file(db).unwrap();
+        }
+
+        // Reopen database
+        {
+            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+            assert!(!db.read_only);
+            close_single_file(db).unwrap();
+        }
+
+        // Clean up
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_id_allocation() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        let id1 = db.alloc_node_id();
+        let id2 = db.alloc_node_id();
+        assert_eq!(id2, id1 + 1);
+
+        let label1 = db.alloc_label_id();
+        let label2 = db.alloc_label_id();
+        assert_eq!(label2, label1 + 1);
+
+        close_single_file(db).unwrap();
+
+        // Clean up
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_schema_operations() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        // Create label
+        let label_id = db.get_or_create_label("Person");
+        assert!(label_id >= INITIAL_LABEL_ID);
+
+        // Should return same ID
+        let label_id2 = db.get_or_create_label("Person");
+        assert_eq!(label_id, label_id2);
+
+        // Lookup
+        assert_eq!(db.get_label_id("Person"), Some(label_id));
+        assert_eq!(db.get_label_name(label_id), Some("Person".to_string()));
+        assert_eq!(db.get_label_id("Unknown"), None);
+
+        close_single_file(db).unwrap();
+
+        // Clean up
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_is_single_file_path() {
+        assert!(is_single_file_path("test.raydb"));
+        assert!(is_single_file_path("/path/to/db.raydb"));
+        assert!(!is_single_file_path("test.db"));
+        assert!(!is_single_file_path("/path/to/directory"));
+    }
+
+    #[test]
+    fn test_custom_options() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let options = SingleFileOpenOptions::new()
+            .page_size(8192)
+            .wal_size(2 * 1024 * 1024);
+
+        let db = open_single_file(&path, options).unwrap();
+
+        assert_eq!(db.header.read().page_size, 8192);
+
+        close_single_file(db).unwrap();
+
+        // Clean up
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_transaction_begin_commit() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        // Begin transaction
+        let txid = db.begin(false).unwrap();
+        assert!(txid > 0);
+        assert!(db.has_transaction());
+
+        // Commit transaction
+        db.commit().unwrap();
+        assert!(!db.has_transaction());
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_transaction_begin_rollback() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        // Begin transaction
+        db.begin(false).unwrap();
+        assert!(db.has_transaction());
+
+        // Rollback transaction
+        db.rollback().unwrap();
+        assert!(!db.has_transaction());
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_create_node_in_transaction() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        // Begin transaction
+        db.begin(false).unwrap();
+
+        // Create node
+        let node_id = db.create_node(Some("user:1")).unwrap();
+        assert!(node_id >= INITIAL_NODE_ID);
+
+        // Node should exist
+        assert!(db.node_exists(node_id));
+
+        // Commit
+        db.commit().unwrap();
+
+        // Node should still exist after commit
+        assert!(db.node_exists(node_id));
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_add_edge_in_transaction() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+
+        // Create nodes
+        let node1 = db.create_node(None).unwrap();
+        let node2 = db.create_node(None).unwrap();
+
+        // Add edge
+        let etype = db.get_or_create_etype("KNOWS");
+        db.add_edge(node1, etype, node2).unwrap();
+
+        // Edge should exist
+        assert!(db.edge_exists(node1, etype, node2));
+
+        db.commit().unwrap();
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_set_node_prop_in_transaction() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+
+        let node_id = db.create_node(None).unwrap();
+        
+        // Set property
+        db.set_node_prop_by_name(node_id, "name", PropValue::String("Alice".to_string())).unwrap();
+
+        db.commit().unwrap();
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_no_write_without_transaction() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        // Try to create node without transaction
+        let result = db.create_node(None);
+        assert!(result.is_err());
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_no_write_in_readonly_transaction() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        // Begin read-only transaction
+        db.begin(true).unwrap();
+
+        // Try to create node
+        let result = db.create_node(None);
+        assert!(result.is_err());
+
+        db.rollback().unwrap();
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_with_write_tx_commits_and_returns_value() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        let node_id = db
+            .with_write_tx(|tx| tx.create_node(Some("user:1")))
+            .unwrap();
+
+        // Transaction slot is released and the write is durable.
+        assert!(!db.has_transaction());
+        assert!(db.node_exists(node_id));
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_with_write_tx_rolls_back_on_err() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        let result: Result<()> = db.with_write_tx(|tx| {
+            tx.create_node(None)?;
+            Err(RayError::Internal("deliberate failure".to_string()))
+        });
+        assert!(result.is_err());
+
+        // The slot must be released even though the closure errored, or a
+        // later begin would fail with TransactionInProgress.
+        assert!(!db.has_transaction());
+        assert!(db.begin(false).is_ok());
+        db.rollback().unwrap();
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_with_write_tx_rolls_back_on_panic() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            db.with_write_tx(|tx| {
+                tx.create_node(None)?;
+                panic!("deliberate panic inside with_write_tx");
+            })
+        }));
+        assert!(result.is_err());
+
+        // The panic is caught just long enough to roll back, then resumed --
+        // the slot must not be left permanently occupied.
+        assert!(!db.has_transaction());
+        assert!(db.begin(false).is_ok());
+        db.rollback().unwrap();
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_with_read_tx_returns_value_without_committing_writes() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let node_id = db.create_node(None).unwrap();
+        db.commit().unwrap();
+
+        let exists = db.with_read_tx(|tx| Ok(tx.node_exists(node_id))).unwrap();
+        assert!(exists);
+        assert!(!db.has_transaction());
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_on_commit_runs_after_commit() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        let ran = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let ran_clone = ran.clone();
+
+        db.begin(false).unwrap();
+        db.create_node(None).unwrap();
+        db.on_commit(move || {
+            ran_clone.store(true, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        assert!(!ran.load(Ordering::SeqCst));
+        db.commit().unwrap();
+        assert!(ran.load(Ordering::SeqCst));
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_on_commit_does_not_run_on_rollback() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        let ran = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let ran_clone = ran.clone();
+
+        db.begin(false).unwrap();
+        db.create_node(None).unwrap();
+        db.on_commit(move || {
+            ran_clone.store(true, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        db.rollback().unwrap();
+        assert!(!ran.load(Ordering::SeqCst));
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_read_tx_not_blocked_by_in_progress_write_tx() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = std::sync::Arc::new(open_single_file(&path, SingleFileOpenOptions::new()).unwrap());
+
+        // Writer stays open on the main thread for the whole test.
+        db.begin(false).unwrap();
+        let node_id = db.create_node(None).unwrap();
+
+        // A read-only transaction on another thread must not be rejected
+        // with TransactionInProgress just because a writer is open.
+        let reader_db = db.clone();
+        std::thread::spawn(move || {
+            reader_db.begin(true).unwrap();
+            assert!(reader_db.node_exists(node_id));
+            reader_db.commit().unwrap();
+        })
+        .join()
+        .unwrap();
+
+        // The writer is still open and unaffected by the reader's commit.
+        assert!(db.has_transaction());
+        db.commit().unwrap();
+
+        close_single_file(std::sync::Arc::try_unwrap(db).unwrap()).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_second_write_tx_still_rejected_while_one_in_progress() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = std::sync::Arc::new(open_single_file(&path, SingleFileOpenOptions::new()).unwrap());
+
+        db.begin(false).unwrap();
+
+        // Writers still serialize -- they all mutate the same shared delta
+        // overlay, so a second one (even from another thread) must fail.
+        let other_db = db.clone();
+        let result = std::thread::spawn(move || other_db.begin(false))
+            .join()
+            .unwrap();
+        assert!(matches!(result, Err(RayError::TransactionInProgress)));
+
+        db.rollback().unwrap();
+
+        close_single_file(std::sync::Arc::try_unwrap(db).unwrap()).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_write_tx_rejected_while_same_thread_has_read_tx_open() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        // A thread that opens a read-only transaction and never commits or
+        // rolls it back must not be able to also take the write slot --
+        // otherwise `commit`/`rollback` would find this thread's
+        // `ACTIVE_READ_TX` set and service the stale read transaction
+        // instead, leaving `current_tx` wedged forever.
+        db.begin(true).unwrap();
+        let result = db.begin(false);
+        assert!(matches!(result, Err(RayError::TransactionInProgress)));
+
+        // Clearing the read transaction frees this thread to take the write
+        // slot, and a normal write commit afterwards is fully durable.
+        db.commit().unwrap();
+
+        db.begin(false).unwrap();
+        let node_id = db.create_node(None).unwrap();
+        db.commit().unwrap();
+        assert!(!db.has_transaction());
+        assert!(db.node_exists(node_id));
+
+        // The writer slot is free again for the next writer.
+        db.begin(false).unwrap();
+        db.rollback().unwrap();
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_immediate_commits_actually_coalesce_through_db_commit() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let options = SingleFileOpenOptions::new().durability(Durability::Immediate);
+        let db = std::sync::Arc::new(open_single_file(&path, options).unwrap());
+
+        // If `group_commit.join` were still called while holding `pager`/
+        // `wal_buffer` (the regression this guards against), every commit
+        // below would have to wait for the current leader's locks to free up
+        // before it could even reach `join`, so it would just end up
+        // leading its own round instead of coalescing -- one `wal_flush`
+        // per commit. With the coordinator reachable lock-free, several of
+        // these racing, rapid-fire commits land inside the same coalescing
+        // window and share a flush.
+        const THREADS: usize = 8;
+        const COMMITS_PER_THREAD: usize = 5;
+        let barrier = std::sync::Arc::new(std::sync::Barrier::new(THREADS));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let db = db.clone();
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    for _ in 0..COMMITS_PER_THREAD {
+                        db.begin(false).unwrap();
+                        db.create_node(None).unwrap();
+                        db.commit().unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
         }
 
-        // Sort by (etype, src) for consistent ordering
-        edges.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        let flush_count = db.metrics().wal_flush_count;
+        assert!(
+            flush_count < (THREADS * COMMITS_PER_THREAD) as u64,
+            "expected at least one coalesced round, got {flush_count} flushes for {} commits",
+            THREADS * COMMITS_PER_THREAD
+        );
 
-        edges
+        close_single_file(std::sync::Arc::try_unwrap(db).unwrap()).unwrap();
+        let _ = fs::remove_file(&path);
     }
 
-    /// Get out-degree (number of outgoing edges) for a node
-    pub fn get_out_degree(&self, node_id: NodeId) -> usize {
-        self.get_out_edges(node_id).len()
-    }
+    #[test]
+    fn test_group_commit_batches_concurrent_joiners() {
+        let coordinator = std::sync::Arc::new(GroupCommitCoordinator::new());
+        let sync_calls = std::sync::Arc::new(AtomicU64::new(0));
+
+        // A handful of threads all arrive inside the coalescing window and
+        // should share a single sync call between them.
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let coordinator = coordinator.clone();
+                let sync_calls = sync_calls.clone();
+                std::thread::spawn(move || {
+                    coordinator
+                        .join(std::time::Duration::from_millis(50), || {
+                            sync_calls.fetch_add(1, Ordering::SeqCst);
+                            Ok(())
+                        })
+                        .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
 
-    /// Get in-degree (number of incoming edges) for a node
-    pub fn get_in_degree(&self, node_id: NodeId) -> usize {
-        self.get_in_edges(node_id).len()
+        assert_eq!(sync_calls.load(Ordering::SeqCst), 1);
     }
 
-    /// Look up a node by its key
-    /// 
-    /// Returns the NodeId if found, None otherwise.
-    /// Checks delta key index first, then falls back to snapshot.
-    pub fn get_node_by_key(&self, key: &str) -> Option<NodeId> {
-        let delta = self.delta.read();
+    #[test]
+    fn test_group_commit_propagates_leader_failure_to_followers() {
+        let coordinator = std::sync::Arc::new(GroupCommitCoordinator::new());
+        let barrier = std::sync::Arc::new(std::sync::Barrier::new(2));
+
+        let leader_coordinator = coordinator.clone();
+        let leader_barrier = barrier.clone();
+        let leader = std::thread::spawn(move || {
+            leader_coordinator.join(std::time::Duration::from_millis(100), || {
+                leader_barrier.wait();
+                Err(RayError::Internal("disk full".to_string()))
+            })
+        });
+
+        // Make sure the leader has claimed the round (and is still inside
+        // its 100ms coalescing window, before its `sync` closure runs) before
+        // the follower joins, so
+        // it actually waits on this round instead of electing itself.
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let follower = std::thread::spawn(move || {
+            coordinator.join(std::time::Duration::from_millis(0), || Ok(()))
+        });
+
+        barrier.wait();
+        let leader_result = leader.join().unwrap();
+        let follower_result = follower.join().unwrap();
+
+        assert!(leader_result.is_err());
+        assert!(follower_result.is_err());
+    }
 
-        // Check delta key index first
-        if delta.key_index_deleted.contains(key) {
-            return None;
-        }
+    #[test]
+    fn test_on_commit_without_transaction_errs() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
 
-        if let Some(&node_id) = delta.key_index.get(key) {
-            // Verify node isn't deleted
-            if !delta.is_node_deleted(node_id) {
-                return Some(node_id);
-            }
-        }
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-        // Fall back to snapshot
-        let snapshot = self.snapshot.read();
-        if let Some(ref snap) = *snapshot {
-            if let Some(node_id) = snap.lookup_by_key(key) {
-                // Verify node isn't deleted in delta
-                if !delta.is_node_deleted(node_id) {
-                    return Some(node_id);
-                }
-            }
-        }
+        let result = db.on_commit(|| {});
+        assert!(result.is_err());
 
-        None
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
     }
 
-    /// Get the key for a node
-    /// 
-    /// Returns the key string if the node has one, None otherwise.
-    pub fn get_node_key(&self, node_id: NodeId) -> Option<String> {
-        let delta = self.delta.read();
+    #[test]
+    fn test_open_single_file_read_only_rejects_writes() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
 
-        // Check if node is deleted
-        if delta.is_node_deleted(node_id) {
-            return None;
+        // Create the database (with a committed node) before reopening read-only.
+        {
+            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+            db.begin(false).unwrap();
+            db.create_node(Some("alice")).unwrap();
+            db.commit().unwrap();
+            db.checkpoint().unwrap();
+            close_single_file(db).unwrap();
         }
 
-        // Check created nodes in delta first
-        if let Some(node_delta) = delta.created_nodes.get(&node_id) {
-            return node_delta.key.clone();
-        }
+        let reader = open_single_file_read_only(&path, SingleFileOpenOptions::new()).unwrap();
+        assert!(reader.read_only);
+        assert!(reader.get_node_by_key("alice").is_some());
 
-        // Fall back to snapshot
-        let snapshot = self.snapshot.read();
-        if let Some(ref snap) = *snapshot {
-            if let Some(phys) = snap.get_phys_node(node_id) {
-                return snap.get_node_key(phys);
-            }
-        }
+        assert!(reader.begin(false).is_err());
+        assert!(reader.create_node(None).is_err());
 
-        None
+        close_single_file(reader).unwrap();
+        let _ = fs::remove_file(&path);
     }
 
-    /// Get neighbors via outgoing edges of a specific type
-    /// 
-    /// Returns destination node IDs for edges of the given type.
-    pub fn get_out_neighbors(&self, node_id: NodeId, etype: ETypeId) -> Vec<NodeId> {
-        self.get_out_edges(node_id)
-            .into_iter()
-            .filter(|(e, _)| *e == etype)
-            .map(|(_, dst)| dst)
-            .collect()
-    }
+    #[test]
+    fn test_open_single_file_read_only_error_if_wal_nonempty() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
 
-    /// Get neighbors via incoming edges of a specific type
-    /// 
-    /// Returns source node IDs for edges of the given type.
-    pub fn get_in_neighbors(&self, node_id: NodeId, etype: ETypeId) -> Vec<NodeId> {
-        self.get_in_edges(node_id)
-            .into_iter()
-            .filter(|(e, _)| *e == etype)
-            .map(|(_, src)| src)
-            .collect()
-    }
+        {
+            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+            db.begin(false).unwrap();
+            db.create_node(Some("alice")).unwrap();
+            db.commit().unwrap();
+            // Deliberately left uncheckpointed so the WAL is non-empty.
+            close_single_file(db).unwrap();
+        }
 
-    /// Check if there are any outgoing edges of a specific type
-    pub fn has_out_edges(&self, node_id: NodeId, etype: ETypeId) -> bool {
-        self.get_out_edges(node_id)
-            .iter()
-            .any(|(e, _)| *e == etype)
-    }
+        let result = open_single_file_read_only(
+            &path,
+            SingleFileOpenOptions::new().error_if_wal_nonempty(true),
+        );
+        assert!(result.is_err());
 
-    /// Check if there are any incoming edges of a specific type
-    pub fn has_in_edges(&self, node_id: NodeId, etype: ETypeId) -> bool {
-        self.get_in_edges(node_id)
-            .iter()
-            .any(|(e, _)| *e == etype)
-    }
-}
+        // Without the flag, the same file opens fine and still sees the
+        // committed write via WAL replay.
+        let reader = open_single_file_read_only(&path, SingleFileOpenOptions::new()).unwrap();
+        assert!(reader.get_node_by_key("alice").is_some());
+        close_single_file(reader).unwrap();
 
-// ============================================================================
-// Utility Functions
-// ============================================================================
+        let _ = fs::remove_file(&path);
+    }
 
-/// Check if a path is a single-file database
-pub fn is_single_file_path<P: AsRef<Path>>(path: P) -> bool {
-    path.as_ref()
-        .extension()
-        .map(|ext| ext == "raydb")
-        .unwrap_or(false)
-}
+    #[test]
+    fn test_wal_persistence() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+        let node_id;
 
-/// Get the single-file extension
-pub fn single_file_extension() -> &'static str {
-    EXT_RAYDB
-}
+        // Create database and write some data
+        {
+            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+            db.begin(false).unwrap();
+            node_id = db.create_node(Some("test:key")).unwrap();
+            db.commit().unwrap();
+            close_single_file(db).unwrap();
+        }
 
-// ============================================================================
-// Tests
-// ============================================================================
+        // Reopen and verify data persisted
+        {
+            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+            // After replay, node should exist
+            assert!(db.node_exists(node_id));
+            close_single_file(db).unwrap();
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::NamedTempFile;
+        let _ = fs::remove_file(&path);
+    }
 
     #[test]
-    fn test_open_new_single_file_db() {
+    fn test_wal_recovers_across_wraparound_with_fragmented_records() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
-        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+        // A tiny WAL and auto-checkpoint disabled force the circular buffer
+        // to wrap several times over -- and, since most individual commits
+        // are smaller than a page but a few (the long string props) aren't,
+        // some of those commits land as a `First`/`Middle`/`Last` fragment
+        // run straddling the physical wrap point rather than a single
+        // `Full` frame.
+        let options = SingleFileOpenOptions::new()
+            .wal_size(8 * 1024)
+            .auto_checkpoint(false);
 
-        assert!(!db.read_only);
-        assert_eq!(db.header.read().page_size, DEFAULT_PAGE_SIZE as u32);
+        let mut node_ids = Vec::new();
+        {
+            let db = open_single_file(&path, options).unwrap();
+            for i in 0..200 {
+                db.begin(false).unwrap();
+                let node_id = db.create_node(Some(&format!("node:{i}"))).unwrap();
+                db.set_node_prop_by_name(
+                    node_id,
+                    "bio",
+                    PropValue::String(format!("a long-ish property value to pad this record out past a page boundary, iteration {i}")),
+                )
+                .unwrap();
+                db.commit().unwrap();
+                node_ids.push(node_id);
+            }
+            close_single_file(db).unwrap();
+        }
 
-        close_single_file(db).unwrap();
+        // Reopening replays the WAL from scratch through `scan_wal_records`,
+        // which must reassemble every fragmented record exactly as it was
+        // written, including the ones whose fragments wrapped past the end
+        // of the buffer.
+        {
+            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+            for node_id in &node_ids {
+                assert!(db.node_exists(*node_id));
+            }
+            close_single_file(db).unwrap();
+        }
 
-        // Clean up
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_reopen_single_file_db() {
+    fn test_checkpoint() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
+        let node1;
+        let node2;
+        let etype;
 
-        // Create database
+        // Create database and write data
         {
             let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+            // Create some nodes and edges
+            db.begin(false).unwrap();
+            node1 = db.create_node(Some("user:alice")).unwrap();
+            node2 = db.create_node(Some("user:bob")).unwrap();
+            etype = db.get_or_create_etype("KNOWS");
+            db.add_edge(node1, etype, node2).unwrap();
+            db.set_node_prop_by_name(node1, "name", PropValue::String("Alice".to_string())).unwrap();
+            db.commit().unwrap();
+
+            // Check WAL has data
+            let stats_before = db.wal_stats();
+            assert!(stats_before.used > 0);
+
+            // Checkpoint
+            db.checkpoint().unwrap();
+
+            // After checkpoint, WAL should be cleared
+            let stats_after = db.wal_stats();
+            assert_eq!(stats_after.head, 0);
+            assert_eq!(stats_after.tail, 0);
+
+            // Snapshot should have data
+            assert!(db.header.read().snapshot_page_count > 0);
+            assert_eq!(db.header.read().active_snapshot_gen, 1);
+
             close_single_file(db).unwrap();
         }
 
-        // Reopen database
+        // Reopen and verify data is in snapshot (not just WAL)
         {
             let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
-            assert!(!db.read_only);
+            
+            // WAL should be empty (data is in snapshot)
+            assert_eq!(db.header.read().wal_head, 0);
+            assert_eq!(db.header.read().wal_tail, 0);
+            
+            // Snapshot should exist
+            assert!(db.header.read().snapshot_page_count > 0);
+            assert!(db.snapshot.read().is_some());
+            
+            // Data should be accessible from snapshot
+            assert!(db.node_exists(node1));
+            assert!(db.node_exists(node2));
+            assert!(db.edge_exists(node1, etype, node2));
+
             close_single_file(db).unwrap();
         }
 
-        // Clean up
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_id_allocation() {
+    fn test_should_checkpoint() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
-        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+        // Create database with small WAL
+        let options = SingleFileOpenOptions::new()
+            .wal_size(16 * 1024); // 16KB WAL
 
-        let id1 = db.alloc_node_id();
-        let id2 = db.alloc_node_id();
-        assert_eq!(id2, id1 + 1);
+        let db = open_single_file(&path, options).unwrap();
 
-        let label1 = db.alloc_label_id();
-        let label2 = db.alloc_label_id();
-        assert_eq!(label2, label1 + 1);
+        // Initially shouldn't need checkpoint
+        assert!(!db.should_checkpoint(0.8));
 
-        close_single_file(db).unwrap();
+        // Write a bunch of data
+        for i in 0..50 {
+            db.begin(false).unwrap();
+            db.create_node(Some(&format!("node:{}", i))).unwrap();
+            db.commit().unwrap();
+        }
 
-        // Clean up
+        // Now might need checkpoint (depending on WAL size)
+        // With very small WAL, this should trigger
+        let usage = db.wal_stats().used as f64 / db.wal_stats().capacity as f64;
+        if usage >= 0.5 {
+            assert!(db.should_checkpoint(0.5));
+        }
+
+        close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_schema_operations() {
+    fn test_auto_checkpoint() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
-        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+        // Create database with small WAL and auto-checkpoint enabled
+        // Use very small WAL (8KB) and low threshold (30%) to trigger checkpoint quickly
+        let options = SingleFileOpenOptions::new()
+            .wal_size(8 * 1024)        // 8KB WAL (very small)
+            .auto_checkpoint(true)
+            .checkpoint_threshold(0.3); // 30% threshold
 
-        // Create label
-        let label_id = db.get_or_create_label("Person");
-        assert!(label_id >= INITIAL_LABEL_ID);
+        let db = open_single_file(&path, options).unwrap();
 
-        // Should return same ID
-        let label_id2 = db.get_or_create_label("Person");
-        assert_eq!(label_id, label_id2);
+        // Initially, no snapshot
+        assert_eq!(db.header.read().snapshot_page_count, 0);
+        assert_eq!(db.header.read().active_snapshot_gen, 0);
 
-        // Lookup
-        assert_eq!(db.get_label_id("Person"), Some(label_id));
-        assert_eq!(db.get_label_name(label_id), Some("Person".to_string()));
-        assert_eq!(db.get_label_id("Unknown"), None);
+        // Write enough data to trigger auto-checkpoint
+        // Each node write + commit is ~50-100 bytes, so 30 nodes should fill ~3KB
+        let mut node_ids = Vec::new();
+        for i in 0..30 {
+            db.begin(false).unwrap();
+            let node_id = db.create_node(Some(&format!("user:{}", i))).unwrap();
+            node_ids.push(node_id);
+            db.commit().unwrap();
+            
+            // Check if checkpoint happened
+            if db.header.read().active_snapshot_gen >= 1 {
+                break;
+            }
+        }
+
+        // Auto-checkpoint should have been triggered
+        let header = db.header.read();
+        
+        // At least one checkpoint should have happened
+        assert!(header.active_snapshot_gen >= 1, 
+            "Expected at least one checkpoint, got gen {}", header.active_snapshot_gen);
+        
+        // Snapshot should exist
+        assert!(header.snapshot_page_count > 0,
+            "Expected snapshot_page_count > 0, got {}", header.snapshot_page_count);
+
+        drop(header);
+
+        // All nodes should still exist
+        for &node_id in &node_ids {
+            assert!(db.node_exists(node_id), "Node {} should exist", node_id);
+        }
 
         close_single_file(db).unwrap();
 
-        // Clean up
-        let _ = fs::remove_file(&path);
-    }
+        // Reopen and verify data persisted correctly
+        {
+            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+            
+            // All nodes should still exist after reopen
+            for &node_id in &node_ids {
+                assert!(db.node_exists(node_id), "Node {} should exist after reopen", node_id);
+            }
+            
+            close_single_file(db).unwrap();
+        }
 
-    #[test]
-    fn test_is_single_file_path() {
-        assert!(is_single_file_path("test.raydb"));
-        assert!(is_single_file_path("/path/to/db.raydb"));
-        assert!(!is_single_file_path("test.db"));
-        assert!(!is_single_file_path("/path/to/directory"));
+        let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_custom_options() {
+    fn test_auto_checkpoint_disabled_by_default() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
+        // Create database with default options (auto-checkpoint disabled)
         let options = SingleFileOpenOptions::new()
-            .page_size(8192)
-            .wal_size(2 * 1024 * 1024);
+            .wal_size(16 * 1024); // 16KB WAL
 
         let db = open_single_file(&path, options).unwrap();
 
-        assert_eq!(db.header.read().page_size, 8192);
+        // Write a bunch of data
+        for i in 0..100 {
+            db.begin(false).unwrap();
+            db.create_node(Some(&format!("node:{}", i))).unwrap();
+            db.commit().unwrap();
+        }
 
-        close_single_file(db).unwrap();
+        // Auto-checkpoint should NOT have happened (disabled by default)
+        // WAL should still have data
+        let stats = db.wal_stats();
+        assert!(stats.used > 0, "WAL should have data since auto-checkpoint is disabled");
+        
+        // No snapshot should have been created
+        assert_eq!(db.header.read().active_snapshot_gen, 0,
+            "No checkpoint should have occurred with auto-checkpoint disabled");
 
-        // Clean up
+        close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_transaction_begin_commit() {
+    fn test_checkpoint_reloads_snapshot() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
         let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-        // Begin transaction
-        let txid = db.begin(false).unwrap();
-        assert!(txid > 0);
-        assert!(db.has_transaction());
-
-        // Commit transaction
+        // Create nodes
+        db.begin(false).unwrap();
+        let node1 = db.create_node(Some("alice")).unwrap();
+        let node2 = db.create_node(Some("bob")).unwrap();
+        let etype = db.get_or_create_etype("KNOWS");
+        db.add_edge(node1, etype, node2).unwrap();
         db.commit().unwrap();
-        assert!(!db.has_transaction());
 
-        close_single_file(db).unwrap();
-        let _ = fs::remove_file(&path);
-    }
+        // Verify nodes exist (from delta)
+        assert!(db.node_exists(node1));
+        assert!(db.node_exists(node2));
+        assert!(db.edge_exists(node1, etype, node2));
 
-    #[test]
-    fn test_transaction_begin_rollback() {
-        let temp_file = NamedTempFile::new().unwrap();
-        let path = temp_file.path().with_extension("raydb");
+        // Checkpoint
+        db.checkpoint().unwrap();
 
-        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+        // After checkpoint, delta should be cleared and snapshot should be loaded
+        // Nodes should still exist (now from snapshot)
+        assert!(db.node_exists(node1), "Node1 should exist after checkpoint");
+        assert!(db.node_exists(node2), "Node2 should exist after checkpoint");
+        assert!(db.edge_exists(node1, etype, node2), "Edge should exist after checkpoint");
 
-        // Begin transaction
+        // Snapshot should be loaded
+        assert!(db.snapshot.read().is_some(), "Snapshot should be loaded after checkpoint");
+
+        // Can continue to write after checkpoint
         db.begin(false).unwrap();
-        assert!(db.has_transaction());
+        let node3 = db.create_node(Some("charlie")).unwrap();
+        db.add_edge(node2, etype, node3).unwrap();
+        db.commit().unwrap();
 
-        // Rollback transaction
-        db.rollback().unwrap();
-        assert!(!db.has_transaction());
+        // All nodes should exist
+        assert!(db.node_exists(node1));
+        assert!(db.node_exists(node2));
+        assert!(db.node_exists(node3));
+        assert!(db.edge_exists(node1, etype, node2));
+        assert!(db.edge_exists(node2, etype, node3));
 
         close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_create_node_in_transaction() {
+    fn test_read_snapshot_repeatable_read() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
         let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-        // Begin transaction
         db.begin(false).unwrap();
+        let alice = db.create_node(Some("alice")).unwrap();
+        db.set_node_prop_by_name(alice, "age", PropValue::I64(30)).unwrap();
+        db.commit().unwrap();
 
-        // Create node
-        let node_id = db.create_node(Some("user:1")).unwrap();
-        assert!(node_id >= INITIAL_NODE_ID);
-
-        // Node should exist
-        assert!(db.node_exists(node_id));
+        let view = db.snapshot();
+        assert!(view.node_exists(alice));
+        assert_eq!(view.get_node_props(alice).unwrap().get(&db.get_propkey_id("age").unwrap()), Some(&PropValue::I64(30)));
 
-        // Commit
+        // Committing further writes against the live db must not change
+        // what an already-captured view sees.
+        db.begin(false).unwrap();
+        let bob = db.create_node(Some("bob")).unwrap();
+        db.set_node_prop_by_name(alice, "age", PropValue::I64(31)).unwrap();
         db.commit().unwrap();
 
-        // Node should still exist after commit
-        assert!(db.node_exists(node_id));
+        assert!(!view.node_exists(bob));
+        assert_eq!(
+            view.get_node_props(alice).unwrap().get(&db.get_propkey_id("age").unwrap()),
+            Some(&PropValue::I64(30))
+        );
+
+        // The live db sees the new write.
+        assert!(db.node_exists(bob));
+        assert_eq!(db.get_node_prop(alice, db.get_propkey_id("age").unwrap()), Some(PropValue::I64(31)));
 
+        drop(view);
         close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_add_edge_in_transaction() {
+    fn test_checkpoint_blocked_while_snapshot_pinned() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
         let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
         db.begin(false).unwrap();
+        db.create_node(Some("alice")).unwrap();
+        db.commit().unwrap();
 
-        // Create nodes
-        let node1 = db.create_node(None).unwrap();
-        let node2 = db.create_node(None).unwrap();
-
-        // Add edge
-        let etype = db.get_or_create_etype("KNOWS");
-        db.add_edge(node1, etype, node2).unwrap();
-
-        // Edge should exist
-        assert!(db.edge_exists(node1, etype, node2));
+        let view = db.snapshot();
+        assert!(matches!(db.checkpoint(), Err(RayError::SnapshotPinned)));
 
-        db.commit().unwrap();
+        // Dropping the only live handle for that generation unblocks it.
+        drop(view);
+        db.checkpoint().unwrap();
 
         close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_set_node_prop_in_transaction() {
+    fn test_optimistic_txn_disjoint_nodes_both_commit() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
         let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
         db.begin(false).unwrap();
+        let alice = db.create_node(Some("alice")).unwrap();
+        let bob = db.create_node(Some("bob")).unwrap();
+        db.commit().unwrap();
 
-        let node_id = db.create_node(None).unwrap();
-        
-        // Set property
-        db.set_node_prop_by_name(node_id, "name", PropValue::String("Alice".to_string())).unwrap();
+        let mut txn_a = db.begin_optimistic();
+        txn_a.set_node_prop_by_name(alice, "age", PropValue::I64(30));
 
-        db.commit().unwrap();
+        let mut txn_b = db.begin_optimistic();
+        txn_b.set_node_prop_by_name(bob, "age", PropValue::I64(40));
+
+        txn_a.commit().unwrap();
+        txn_b.commit().unwrap();
+
+        assert_eq!(db.get_node_prop(alice, db.get_propkey_id("age").unwrap()), Some(PropValue::I64(30)));
+        assert_eq!(db.get_node_prop(bob, db.get_propkey_id("age").unwrap()), Some(PropValue::I64(40)));
 
         close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_no_write_without_transaction() {
+    fn test_optimistic_txn_conflicting_write_rejected() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
         let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-        // Try to create node without transaction
-        let result = db.create_node(None);
-        assert!(result.is_err());
+        db.begin(false).unwrap();
+        let alice = db.create_node(Some("alice")).unwrap();
+        db.commit().unwrap();
+
+        // Both transactions read `alice` before either commits.
+        let mut txn_a = db.begin_optimistic();
+        txn_a.set_node_prop_by_name(alice, "age", PropValue::I64(30));
+
+        let mut txn_b = db.begin_optimistic();
+        txn_b.set_node_prop_by_name(alice, "age", PropValue::I64(31));
+
+        txn_a.commit().unwrap();
+        // txn_b's read set is now stale -- alice was committed at a newer
+        // version than txn_b observed.
+        assert!(matches!(txn_b.commit(), Err(RayError::Conflict(id)) if id == alice));
+
+        assert_eq!(db.get_node_prop(alice, db.get_propkey_id("age").unwrap()), Some(PropValue::I64(30)));
 
         close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_no_write_in_readonly_transaction() {
+    fn test_optimistic_txn_detects_conflict_on_first_late_touch() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
         let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-        // Begin read-only transaction
-        db.begin(true).unwrap();
+        db.begin(false).unwrap();
+        let alice = db.create_node(Some("alice")).unwrap();
+        db.commit().unwrap();
 
-        // Try to create node
-        let result = db.create_node(None);
-        assert!(result.is_err());
+        // Built before the concurrent write below, but this transaction
+        // never references `alice` until after that write has committed --
+        // its read-set baseline must still come from *this* moment, not
+        // from whatever's live in `committed_versions` the first time the
+        // transaction body happens to touch the node.
+        let mut txn = db.begin_optimistic();
 
-        db.rollback().unwrap();
+        db.begin(false).unwrap();
+        db.set_node_prop_by_name(alice, "age", PropValue::I64(99));
+        db.commit().unwrap();
+
+        txn.set_node_prop_by_name(alice, "age", PropValue::I64(30));
+        assert!(matches!(txn.commit(), Err(RayError::Conflict(id)) if id == alice));
+
+        assert_eq!(db.get_node_prop(alice, db.get_propkey_id("age").unwrap()), Some(PropValue::I64(99)));
 
         close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_wal_persistence() {
+    fn test_optimistic_txn_create_node_preserves_reserved_id() {
         let temp_file = NamedTempFile::new().unwrap();
-        let path = temp_file.path().with_extension("raydb");
-        let node_id;
-
-        // Create database and write some data
-        {
-            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
-            db.begin(false).unwrap();
-            node_id = db.create_node(Some("test:key")).unwrap();
-            db.commit().unwrap();
-            close_single_file(db).unwrap();
-        }
+        let path = temp_file.path().with_extension("raydb");
 
-        // Reopen and verify data persisted
-        {
-            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
-            // After replay, node should exist
-            assert!(db.node_exists(node_id));
-            close_single_file(db).unwrap();
-        }
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        let mut txn = db.begin_optimistic();
+        let carol = txn.create_node(Some("carol"));
+        txn.set_node_prop_by_name(carol, "age", PropValue::I64(25));
+        txn.commit().unwrap();
 
+        assert!(db.node_exists(carol));
+        assert_eq!(db.get_node_by_key("carol"), Some(carol));
+        assert_eq!(db.get_node_prop(carol, db.get_propkey_id("age").unwrap()), Some(PropValue::I64(25)));
+
+        close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_checkpoint() {
+    fn test_graph_handle_isolates_node_ids_and_etype_names() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
-        let node1;
-        let node2;
-        let etype;
 
-        // Create database and write data
-        {
-            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-            // Create some nodes and edges
-            db.begin(false).unwrap();
-            node1 = db.create_node(Some("user:alice")).unwrap();
-            node2 = db.create_node(Some("user:bob")).unwrap();
-            etype = db.get_or_create_etype("KNOWS");
-            db.add_edge(node1, etype, node2).unwrap();
-            db.set_node_prop_by_name(node1, "name", PropValue::String("Alice".to_string())).unwrap();
-            db.commit().unwrap();
+        db.create_graph("tenant_a").unwrap();
+        db.create_graph("tenant_b").unwrap();
+        let tenant_a = db.graph_handle("tenant_a").unwrap();
+        let tenant_b = db.graph_handle("tenant_b").unwrap();
 
-            // Check WAL has data
-            let stats_before = db.wal_stats();
-            assert!(stats_before.used > 0);
+        db.begin(false).unwrap();
+        let a_node = tenant_a.create_node(Some("a1")).unwrap();
+        let b_node = tenant_b.create_node(Some("b1")).unwrap();
+        assert_ne!(a_node, b_node);
 
-            // Checkpoint
-            db.checkpoint().unwrap();
+        tenant_a.add_edge_by_name(a_node, "knows", a_node).unwrap();
+        tenant_b.add_edge_by_name(b_node, "knows", b_node).unwrap();
+        db.commit().unwrap();
 
-            // After checkpoint, WAL should be cleared
-            let stats_after = db.wal_stats();
-            assert_eq!(stats_after.head, 0);
-            assert_eq!(stats_after.tail, 0);
+        // Same human-readable edge-type name in each namespace, but
+        // resolved to different underlying ids.
+        assert_ne!(
+            tenant_a.get_or_create_etype("knows"),
+            tenant_b.get_or_create_etype("knows")
+        );
 
-            // Snapshot should have data
-            assert!(db.header.read().snapshot_page_count > 0);
-            assert_eq!(db.header.read().active_snapshot_gen, 1);
+        assert_eq!(tenant_a.get_out_edges(a_node), vec![(tenant_a.get_or_create_etype("knows"), a_node)]);
+        assert_eq!(tenant_b.get_out_edges(b_node), vec![(tenant_b.get_or_create_etype("knows"), b_node)]);
 
-            close_single_file(db).unwrap();
-        }
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
 
-        // Reopen and verify data is in snapshot (not just WAL)
-        {
-            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
-            
-            // WAL should be empty (data is in snapshot)
-            assert_eq!(db.header.read().wal_head, 0);
-            assert_eq!(db.header.read().wal_tail, 0);
-            
-            // Snapshot should exist
-            assert!(db.header.read().snapshot_page_count > 0);
-            assert!(db.snapshot.read().is_some());
-            
-            // Data should be accessible from snapshot
-            assert!(db.node_exists(node1));
-            assert!(db.node_exists(node2));
-            assert!(db.edge_exists(node1, etype, node2));
+    #[test]
+    fn test_create_graph_rejects_duplicate_name() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
 
-            close_single_file(db).unwrap();
-        }
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
+        db.create_graph("tenant_a").unwrap();
+        assert!(db.create_graph("tenant_a").is_err());
+        assert!(db.graph_handle("unknown_tenant").is_none());
+
+        close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_should_checkpoint() {
+    fn test_metrics_gauges_track_delta_and_snapshot_state() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
-        // Create database with small WAL
-        let options = SingleFileOpenOptions::new()
-            .wal_size(16 * 1024); // 16KB WAL
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-        let db = open_single_file(&path, options).unwrap();
+        let before = db.metrics();
+        assert_eq!(before.delta_usage.nodes_created, 0);
+        assert_eq!(before.delta_bytes_estimate, 0);
 
-        // Initially shouldn't need checkpoint
-        assert!(!db.should_checkpoint(0.8));
+        db.begin(false).unwrap();
+        db.create_node(Some("alice")).unwrap();
+        db.commit().unwrap();
 
-        // Write a bunch of data
-        for i in 0..50 {
-            db.begin(false).unwrap();
-            db.create_node(Some(&format!("node:{}", i))).unwrap();
-            db.commit().unwrap();
-        }
+        let after = db.metrics();
+        assert_eq!(after.delta_usage.nodes_created, 1);
+        assert!(after.delta_bytes_estimate > 0);
+        assert_eq!(after.pending_layers, 0);
 
-        // Now might need checkpoint (depending on WAL size)
-        // With very small WAL, this should trigger
-        let usage = db.wal_stats().used as f64 / db.wal_stats().capacity as f64;
-        if usage >= 0.5 {
-            assert!(db.should_checkpoint(0.5));
-        }
+        db.checkpoint().unwrap();
+        let after_checkpoint = db.metrics();
+        assert_eq!(after_checkpoint.delta_usage.nodes_created, 0);
+        assert_eq!(after_checkpoint.delta_bytes_estimate, 0);
+        assert!(after_checkpoint.snapshot_resident_pages > 0);
 
         close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_auto_checkpoint() {
+    fn test_checkpoint_incremental_batches_until_threshold() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
-        // Create database with small WAL and auto-checkpoint enabled
-        // Use very small WAL (8KB) and low threshold (30%) to trigger checkpoint quickly
-        let options = SingleFileOpenOptions::new()
-            .wal_size(8 * 1024)        // 8KB WAL (very small)
-            .auto_checkpoint(true)
-            .checkpoint_threshold(0.3); // 30% threshold
-
-        let db = open_single_file(&path, options).unwrap();
-
-        // Initially, no snapshot
-        assert_eq!(db.header.read().snapshot_page_count, 0);
-        assert_eq!(db.header.read().active_snapshot_gen, 0);
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+        let gen_before = db.header.read().active_snapshot_gen;
 
-        // Write enough data to trigger auto-checkpoint
-        // Each node write + commit is ~50-100 bytes, so 30 nodes should fill ~3KB
-        let mut node_ids = Vec::new();
-        for i in 0..30 {
+        // Each commit dirties a node, so each call below cuts one more
+        // pending layer. Short of MAX_PENDING_LAYERS, no real checkpoint
+        // should run yet.
+        for i in 0..MAX_PENDING_LAYERS - 1 {
             db.begin(false).unwrap();
-            let node_id = db.create_node(Some(&format!("user:{}", i))).unwrap();
-            node_ids.push(node_id);
+            db.create_node(Some(&format!("n{i}"))).unwrap();
             db.commit().unwrap();
-            
-            // Check if checkpoint happened
-            if db.header.read().active_snapshot_gen >= 1 {
-                break;
-            }
+            db.checkpoint_incremental().unwrap();
+            assert_eq!(db.pending_layer_count(), i + 1);
         }
+        assert_eq!(db.header.read().active_snapshot_gen, gen_before);
 
-        // Auto-checkpoint should have been triggered
-        let header = db.header.read();
-        
-        // At least one checkpoint should have happened
-        assert!(header.active_snapshot_gen >= 1, 
-            "Expected at least one checkpoint, got gen {}", header.active_snapshot_gen);
-        
-        // Snapshot should exist
-        assert!(header.snapshot_page_count > 0,
-            "Expected snapshot_page_count > 0, got {}", header.snapshot_page_count);
-
-        drop(header);
-
-        // All nodes should still exist
-        for &node_id in &node_ids {
-            assert!(db.node_exists(node_id), "Node {} should exist", node_id);
-        }
+        // The layer that crosses the threshold folds everything into a
+        // real checkpoint and resets the batch.
+        db.begin(false).unwrap();
+        db.create_node(Some("last")).unwrap();
+        db.commit().unwrap();
+        db.checkpoint_incremental().unwrap();
+        assert_eq!(db.pending_layer_count(), 0);
+        assert!(db.header.read().active_snapshot_gen > gen_before);
 
         close_single_file(db).unwrap();
-
-        // Reopen and verify data persisted correctly
-        {
-            let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
-            
-            // All nodes should still exist after reopen
-            for &node_id in &node_ids {
-                assert!(db.node_exists(node_id), "Node {} should exist after reopen", node_id);
-            }
-            
-            close_single_file(db).unwrap();
-        }
-
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_auto_checkpoint_disabled_by_default() {
+    fn test_compact_layers_forces_pending_checkpoint() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
-        // Create database with default options (auto-checkpoint disabled)
-        let options = SingleFileOpenOptions::new()
-            .wal_size(16 * 1024); // 16KB WAL
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-        let db = open_single_file(&path, options).unwrap();
+        // A no-op when nothing is pending.
+        db.compact_layers().unwrap();
+        assert_eq!(db.pending_layer_count(), 0);
 
-        // Write a bunch of data
-        for i in 0..100 {
-            db.begin(false).unwrap();
-            db.create_node(Some(&format!("node:{}", i))).unwrap();
-            db.commit().unwrap();
-        }
+        db.begin(false).unwrap();
+        db.create_node(Some("alice")).unwrap();
+        db.commit().unwrap();
+        db.checkpoint_incremental().unwrap();
+        assert_eq!(db.pending_layer_count(), 1);
 
-        // Auto-checkpoint should NOT have happened (disabled by default)
-        // WAL should still have data
-        let stats = db.wal_stats();
-        assert!(stats.used > 0, "WAL should have data since auto-checkpoint is disabled");
-        
-        // No snapshot should have been created
-        assert_eq!(db.header.read().active_snapshot_gen, 0,
-            "No checkpoint should have occurred with auto-checkpoint disabled");
+        let gen_before = db.header.read().active_snapshot_gen;
+        db.compact_layers().unwrap();
+        assert_eq!(db.pending_layer_count(), 0);
+        assert!(db.header.read().active_snapshot_gen > gen_before);
 
         close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
     }
 
     #[test]
-    fn test_checkpoint_reloads_snapshot() {
+    fn test_create_checkpoint_produces_independent_copy() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
+        let backup_temp = NamedTempFile::new().unwrap();
+        let backup_path = backup_temp.path().with_extension("raydb");
 
         let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-        // Create nodes
         db.begin(false).unwrap();
         let node1 = db.create_node(Some("alice")).unwrap();
         let node2 = db.create_node(Some("bob")).unwrap();
@@ -2465,38 +7019,26 @@ mod tests {
         db.add_edge(node1, etype, node2).unwrap();
         db.commit().unwrap();
 
-        // Verify nodes exist (from delta)
-        assert!(db.node_exists(node1));
-        assert!(db.node_exists(node2));
-        assert!(db.edge_exists(node1, etype, node2));
-
-        // Checkpoint
-        db.checkpoint().unwrap();
-
-        // After checkpoint, delta should be cleared and snapshot should be loaded
-        // Nodes should still exist (now from snapshot)
-        assert!(db.node_exists(node1), "Node1 should exist after checkpoint");
-        assert!(db.node_exists(node2), "Node2 should exist after checkpoint");
-        assert!(db.edge_exists(node1, etype, node2), "Edge should exist after checkpoint");
-
-        // Snapshot should be loaded
-        assert!(db.snapshot.read().is_some(), "Snapshot should be loaded after checkpoint");
+        db.create_checkpoint(&backup_path).unwrap();
 
-        // Can continue to write after checkpoint
+        // The live database is untouched and keeps accepting writes.
         db.begin(false).unwrap();
         let node3 = db.create_node(Some("charlie")).unwrap();
-        db.add_edge(node2, etype, node3).unwrap();
         db.commit().unwrap();
-
-        // All nodes should exist
-        assert!(db.node_exists(node1));
-        assert!(db.node_exists(node2));
         assert!(db.node_exists(node3));
-        assert!(db.edge_exists(node1, etype, node2));
-        assert!(db.edge_exists(node2, etype, node3));
 
+        // The backup is a standalone, fully self-contained file reflecting
+        // exactly what was committed at the time of the checkpoint.
+        let backup = open_single_file(&backup_path, SingleFileOpenOptions::new()).unwrap();
+        assert!(backup.node_exists(node1));
+        assert!(backup.node_exists(node2));
+        assert!(backup.edge_exists(node1, etype, node2));
+        assert!(!backup.node_exists(node3));
+
+        close_single_file(backup).unwrap();
         close_single_file(db).unwrap();
         let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&backup_path);
     }
 
     #[test]
@@ -2694,39 +7236,104 @@ mod tests {
     }
 
     #[test]
-    fn test_get_node_props_merge_snapshot_and_delta() {
+    fn test_get_node_props_merge_snapshot_and_delta() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        // Create node with initial props
+        db.begin(false).unwrap();
+        let node_id = db.create_node(None).unwrap();
+        db.set_node_prop_by_name(node_id, "name", PropValue::String("Charlie".to_string())).unwrap();
+        db.set_node_prop_by_name(node_id, "age", PropValue::I64(25)).unwrap();
+        db.commit().unwrap();
+
+        // Checkpoint
+        db.checkpoint().unwrap();
+
+        // Modify one prop in new transaction
+        db.begin(false).unwrap();
+        db.set_node_prop_by_name(node_id, "age", PropValue::I64(26)).unwrap();
+        db.set_node_prop_by_name(node_id, "city", PropValue::String("NYC".to_string())).unwrap();
+        db.commit().unwrap();
+
+        // Props should merge snapshot + delta
+        let props = db.get_node_props(node_id).unwrap();
+        let name_key = db.get_propkey_id("name").unwrap();
+        let age_key = db.get_propkey_id("age").unwrap();
+        let city_key = db.get_propkey_id("city").unwrap();
+
+        assert_eq!(props.get(&name_key), Some(&PropValue::String("Charlie".to_string()))); // from snapshot
+        assert_eq!(props.get(&age_key), Some(&PropValue::I64(26))); // overwritten in delta
+        assert_eq!(props.get(&city_key), Some(&PropValue::String("NYC".to_string()))); // new in delta
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_get_node_props_repeat_reads_hit_the_prop_cache() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let node_id = db.create_node(None).unwrap();
+        db.set_node_prop_by_name(node_id, "name", PropValue::String("Dana".to_string())).unwrap();
+        db.commit().unwrap();
+        db.checkpoint().unwrap();
+
+        let name_key = db.get_propkey_id("name").unwrap();
+
+        // First read decodes the snapshot page and populates the cache;
+        // the second is served from it. Both must agree regardless.
+        let first = db.get_node_props(node_id).unwrap();
+        assert_eq!(db.node_prop_cache.lock().entries.len(), 1);
+        let second = db.get_node_props(node_id).unwrap();
+        assert_eq!(first.get(&name_key), second.get(&name_key));
+        assert_eq!(second.get(&name_key), Some(&PropValue::String("Dana".to_string())));
+
+        // A checkpoint maps a new snapshot generation, so the old cache
+        // entries (keyed by physical node id, not `NodeId`) must not survive.
+        db.begin(false).unwrap();
+        db.set_node_prop_by_name(node_id, "name", PropValue::String("Dana2".to_string())).unwrap();
+        db.commit().unwrap();
+        db.checkpoint().unwrap();
+        assert_eq!(db.node_prop_cache.lock().entries.len(), 0);
+        assert_eq!(
+            db.get_node_props(node_id).unwrap().get(&name_key),
+            Some(&PropValue::String("Dana2".to_string()))
+        );
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_verify_snapshot_identity_detects_file_truncation() {
         let temp_file = NamedTempFile::new().unwrap();
         let path = temp_file.path().with_extension("raydb");
 
         let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
 
-        // Create node with initial props
         db.begin(false).unwrap();
-        let node_id = db.create_node(None).unwrap();
-        db.set_node_prop_by_name(node_id, "name", PropValue::String("Charlie".to_string())).unwrap();
-        db.set_node_prop_by_name(node_id, "age", PropValue::I64(25)).unwrap();
+        db.create_node(None).unwrap();
         db.commit().unwrap();
-
-        // Checkpoint
         db.checkpoint().unwrap();
 
-        // Modify one prop in new transaction
-        db.begin(false).unwrap();
-        db.set_node_prop_by_name(node_id, "age", PropValue::I64(26)).unwrap();
-        db.set_node_prop_by_name(node_id, "city", PropValue::String("NYC".to_string())).unwrap();
-        db.commit().unwrap();
+        assert!(db.verify_snapshot_identity().unwrap());
 
-        // Props should merge snapshot + delta
-        let props = db.get_node_props(node_id).unwrap();
-        let name_key = db.get_propkey_id("name").unwrap();
-        let age_key = db.get_propkey_id("age").unwrap();
-        let city_key = db.get_propkey_id("city").unwrap();
+        // Truncate the file out from under the already-open handle, as if
+        // another process had rewritten it.
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(1).unwrap();
 
-        assert_eq!(props.get(&name_key), Some(&PropValue::String("Charlie".to_string()))); // from snapshot
-        assert_eq!(props.get(&age_key), Some(&PropValue::I64(26))); // overwritten in delta
-        assert_eq!(props.get(&city_key), Some(&PropValue::String("NYC".to_string()))); // new in delta
+        assert!(!db.verify_snapshot_identity().unwrap());
 
-        close_single_file(db).unwrap();
+        // Don't let `close_single_file` try to write back into the
+        // truncated file.
         let _ = fs::remove_file(&path);
     }
 
@@ -2966,6 +7573,156 @@ mod tests {
         let _ = fs::remove_file(&path);
     }
 
+    #[test]
+    fn test_set_and_get_edge_prop() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let node1 = db.create_node(None).unwrap();
+        let node2 = db.create_node(None).unwrap();
+        let knows = db.get_or_create_etype("KNOWS");
+        db.add_edge(node1, knows, node2).unwrap();
+        db.set_edge_prop_by_name(node1, knows, node2, "since", PropValue::I64(2020)).unwrap();
+        db.commit().unwrap();
+
+        let since_key = db.get_propkey_id("since").unwrap();
+        assert_eq!(db.get_edge_prop(node1, knows, node2, since_key), Some(PropValue::I64(2020)));
+        assert_eq!(
+            db.get_edge_props(node1, knows, node2),
+            Some(HashMap::from([(since_key, PropValue::I64(2020))]))
+        );
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_get_edge_props_merge_snapshot_and_delta() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let node1 = db.create_node(None).unwrap();
+        let node2 = db.create_node(None).unwrap();
+        let knows = db.get_or_create_etype("KNOWS");
+        db.add_edge(node1, knows, node2).unwrap();
+        db.set_edge_prop_by_name(node1, knows, node2, "since", PropValue::I64(2020)).unwrap();
+        db.commit().unwrap();
+
+        db.checkpoint().unwrap();
+
+        // Overwrite the snapshot-backed prop and add a new one via the delta
+        db.begin(false).unwrap();
+        db.set_edge_prop_by_name(node1, knows, node2, "since", PropValue::I64(2021)).unwrap();
+        db.set_edge_prop_by_name(node1, knows, node2, "weight", PropValue::I64(5)).unwrap();
+        db.commit().unwrap();
+
+        let since_key = db.get_propkey_id("since").unwrap();
+        let weight_key = db.get_propkey_id("weight").unwrap();
+        assert_eq!(db.get_edge_prop(node1, knows, node2, since_key), Some(PropValue::I64(2021)));
+        assert_eq!(db.get_edge_prop(node1, knows, node2, weight_key), Some(PropValue::I64(5)));
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_delete_edge_prop() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let node1 = db.create_node(None).unwrap();
+        let node2 = db.create_node(None).unwrap();
+        let knows = db.get_or_create_etype("KNOWS");
+        db.add_edge(node1, knows, node2).unwrap();
+        db.set_edge_prop_by_name(node1, knows, node2, "since", PropValue::I64(2020)).unwrap();
+        db.commit().unwrap();
+
+        let since_key = db.get_propkey_id("since").unwrap();
+
+        db.begin(false).unwrap();
+        db.delete_edge_prop(node1, knows, node2, since_key).unwrap();
+        db.commit().unwrap();
+
+        assert_eq!(db.get_edge_prop(node1, knows, node2, since_key), None);
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_unrecord_reverts_edge_prop_write() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let node1 = db.create_node(None).unwrap();
+        let node2 = db.create_node(None).unwrap();
+        let knows = db.get_or_create_etype("KNOWS");
+        db.add_edge(node1, knows, node2).unwrap();
+        db.set_edge_prop_by_name(node1, knows, node2, "since", PropValue::I64(2020)).unwrap();
+        db.commit().unwrap();
+
+        let since_key = db.get_propkey_id("since").unwrap();
+
+        db.begin(false).unwrap();
+        db.set_edge_prop(node1, knows, node2, since_key, PropValue::I64(2021)).unwrap();
+        db.commit().unwrap();
+        let txid = db.history.lock().back().unwrap().txid;
+
+        db.unrecord(txid, false).unwrap();
+        assert_eq!(db.get_edge_prop(node1, knows, node2, since_key), Some(PropValue::I64(2020)));
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_nested_savepoint_rollback_restores_delta_and_ids() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let name = db.get_or_create_propkey("name");
+        let outer = db.create_node(None).unwrap();
+        db.set_node_prop(outer, name, PropValue::String("alice".to_string())).unwrap();
+        let sp_outer = db.savepoint().unwrap();
+
+        let inner_node = db.create_node(None).unwrap();
+        let sp_inner = db.savepoint().unwrap();
+        db.set_node_prop(outer, name, PropValue::String("mallory".to_string())).unwrap();
+        let after_inner = db.create_node(None).unwrap();
+
+        // Rolling back to the outer savepoint must undo both the inner
+        // savepoint's mutations and everything recorded after it, and
+        // implicitly invalidate `sp_inner` along the way.
+        db.rollback_to(sp_outer).unwrap();
+        assert!(db.rollback_to(sp_inner).is_err());
+
+        assert!(!db.node_exists(inner_node));
+        assert!(!db.node_exists(after_inner));
+        assert_eq!(db.get_node_prop(outer, name), Some(PropValue::String("alice".to_string())));
+
+        db.commit().unwrap();
+        assert!(db.node_exists(outer));
+        assert!(!db.node_exists(inner_node));
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
     #[test]
     fn test_get_node_by_key_from_delta() {
         let temp_file = NamedTempFile::new().unwrap();
@@ -3030,6 +7787,92 @@ mod tests {
         let _ = fs::remove_file(&path);
     }
 
+    #[test]
+    fn test_get_nodes_by_prefix_across_snapshot_and_delta() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let alice = db.create_node(Some("user:alice")).unwrap();
+        let bob = db.create_node(Some("user:bob")).unwrap();
+        db.create_node(Some("post:1")).unwrap();
+        db.commit().unwrap();
+
+        db.checkpoint().unwrap();
+
+        // New key added after the checkpoint, only visible via delta.
+        db.begin(false).unwrap();
+        let carol = db.create_node(Some("user:carol")).unwrap();
+        db.commit().unwrap();
+
+        let mut users = db.get_nodes_by_prefix("user:");
+        users.sort();
+        let mut expected = vec![alice, bob, carol];
+        expected.sort();
+        assert_eq!(users, expected);
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_get_nodes_in_range_excludes_deleted_and_out_of_range() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let a = db.create_node(Some("a")).unwrap();
+        let b = db.create_node(Some("b")).unwrap();
+        let c = db.create_node(Some("c")).unwrap();
+        db.create_node(Some("d")).unwrap();
+        db.commit().unwrap();
+
+        db.checkpoint().unwrap();
+
+        db.begin(false).unwrap();
+        db.delete_node(b).unwrap();
+        db.commit().unwrap();
+
+        let mut in_range = db.get_nodes_in_range("a", "c");
+        in_range.sort();
+        let mut expected = vec![a, c];
+        expected.sort();
+        assert_eq!(in_range, expected);
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_get_node_by_key_tombstoned_after_checkpoint() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let alice = db.create_node(Some("user:alice")).unwrap();
+        db.commit().unwrap();
+
+        db.checkpoint().unwrap();
+
+        // The key now only resolves through the snapshot; deleting the
+        // node must tombstone it in the delta's key index rather than
+        // leaving the stale snapshot entry resolvable.
+        db.begin(false).unwrap();
+        db.delete_node(alice).unwrap();
+        db.commit().unwrap();
+
+        assert_eq!(db.get_node_by_key("user:alice"), None);
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
     #[test]
     fn test_get_node_key() {
         let temp_file = NamedTempFile::new().unwrap();
@@ -3114,6 +7957,141 @@ mod tests {
         let _ = fs::remove_file(&path);
     }
 
+    #[test]
+    fn test_reachable_multi_hop_filters_by_etype_and_depth() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let a = db.create_node(None).unwrap();
+        let b = db.create_node(None).unwrap();
+        let c = db.create_node(None).unwrap();
+        let d = db.create_node(None).unwrap();
+        let knows = db.get_or_create_etype("KNOWS");
+        let likes = db.get_or_create_etype("LIKES");
+        db.add_edge(a, knows, b).unwrap();
+        db.add_edge(b, knows, c).unwrap();
+        db.add_edge(c, knows, d).unwrap();
+        db.add_edge(a, likes, d).unwrap();
+        db.commit().unwrap();
+
+        // Only following KNOWS, a reaches b, c, d but not via the LIKES edge.
+        let mut reached = db.reachable(a, &[knows], None);
+        reached.sort();
+        let mut expected = vec![b, c, d];
+        expected.sort();
+        assert_eq!(reached, expected);
+
+        // Depth 1 only reaches the immediate neighbor.
+        assert_eq!(db.reachable(a, &[knows], Some(1)), vec![b]);
+
+        // LIKES alone only reaches d directly.
+        assert_eq!(db.reachable(a, &[likes], None), vec![d]);
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_shortest_path_reconstructs_and_handles_unreachable() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let a = db.create_node(None).unwrap();
+        let b = db.create_node(None).unwrap();
+        let c = db.create_node(None).unwrap();
+        let isolated = db.create_node(None).unwrap();
+        let knows = db.get_or_create_etype("KNOWS");
+        db.add_edge(a, knows, b).unwrap();
+        db.add_edge(b, knows, c).unwrap();
+        // A direct but longer-looking alternate route shouldn't beat the
+        // shorter one found by the epoch that reaches `c` first.
+        db.add_edge(a, knows, c).unwrap();
+        db.commit().unwrap();
+
+        let path_to_c = db.shortest_path(a, c, &[knows]).unwrap();
+        assert_eq!(path_to_c.first(), Some(&a));
+        assert_eq!(path_to_c.last(), Some(&c));
+        assert_eq!(path_to_c.len(), 2); // direct a -> c edge wins
+
+        assert_eq!(db.shortest_path(a, a, &[knows]), Some(vec![a]));
+        assert_eq!(db.shortest_path(a, isolated, &[knows]), None);
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_unrecord_reverts_node_creation_and_prop_writes() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let existing = db.create_node(None).unwrap();
+        let name = db.get_or_create_propkey("name");
+        db.set_node_prop_by_name(existing, "name", PropValue::String("alice".to_string())).unwrap();
+        db.commit().unwrap();
+
+        db.begin(false).unwrap();
+        let created = db.create_node(Some("bob")).unwrap();
+        db.set_node_prop(existing, name, PropValue::String("alicia".to_string())).unwrap();
+        db.commit().unwrap();
+        let txid = db.history.lock().back().unwrap().txid;
+
+        db.unrecord(txid, false).unwrap();
+
+        assert!(!db.node_exists(created));
+        assert_eq!(
+            db.get_node_prop(existing, name),
+            Some(PropValue::String("alice".to_string()))
+        );
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_unrecord_refuses_when_depended_upon_unless_cascaded() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().with_extension("raydb");
+
+        let db = open_single_file(&path, SingleFileOpenOptions::new()).unwrap();
+
+        db.begin(false).unwrap();
+        let a = db.create_node(None).unwrap();
+        db.commit().unwrap();
+        let first_txid = db.history.lock().back().unwrap().txid;
+
+        db.begin(false).unwrap();
+        let b = db.create_node(None).unwrap();
+        let knows = db.get_or_create_etype("KNOWS");
+        db.add_edge(a, knows, b).unwrap();
+        db.commit().unwrap();
+
+        // A later commit wrote to `a` (the edge endpoint), so reverting the
+        // commit that created `a` must be refused without cascade...
+        match db.unrecord(first_txid, false) {
+            Err(RayError::ChangeIsDependedUpon(txid)) => assert_eq!(txid, first_txid),
+            other => panic!("expected ChangeIsDependedUpon, got {:?}", other),
+        }
+        assert!(db.node_exists(a));
+
+        // ...and succeed, taking the dependent down with it, when cascaded.
+        db.unrecord(first_txid, true).unwrap();
+        assert!(!db.node_exists(a));
+        assert!(!db.node_exists(b));
+
+        close_single_file(db).unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
     #[test]
     fn test_degree_functions() {
         let temp_file = NamedTempFile::new().unwrap();
@@ -296,6 +296,29 @@ impl SingleFileDB {
             );
           }
         }
+
+        // Vector writes land in `pending_vectors` the same way node/edge
+        // prop writes land in `modified_nodes`/`edge_props`, but are applied
+        // to the vector store by `apply_pending_vectors` outside this delta
+        // entirely -- without the same before/after treatment here, a
+        // `ReadSnapshot` opened before this commit could still observe the
+        // post-commit vector, unlike every other field it pins. This loop
+        // gives vectors the same one-time-seed-then-append history
+        // `append_node_prop_version` already gives properties just above.
+        for (&(node_id, key_id), after_value) in &current_delta.pending_vectors {
+          let before_value = delta_snapshot.pending_vectors.get(&(node_id, key_id));
+          if before_value == Some(after_value) {
+            continue;
+          }
+          if vc.get_node_vector_version(node_id, key_id).is_none() {
+            let old_value = before_value
+              .cloned()
+              .flatten()
+              .or_else(|| self.node_vector(node_id, key_id));
+            vc.append_node_vector_version(node_id, key_id, old_value, 0, 0);
+          }
+          vc.append_node_vector_version(node_id, key_id, after_value.clone(), txid, commit_ts);
+        }
       }
     }
 
@@ -4,17 +4,22 @@
 
 use crate::core::snapshot::reader::SnapshotData;
 use crate::core::wal::record::{
-  build_del_node_vector_payload, build_set_node_vector_payload, WalRecord,
+  build_del_node_vector_payload, build_set_node_vector_payload, parse_del_node_vector_payload,
+  parse_set_node_vector_payload, WalRecord,
 };
-use crate::error::{KiteError, Result};
+use crate::error::{RayError, Result};
 use crate::types::*;
 use crate::util::binary::{read_u32, read_u64};
+use crate::vector::erasure::{crc32, reconstruct, ErasureHeader};
 use crate::vector::ivf::serialize::deserialize_manifest;
 use crate::vector::store::{
   create_vector_store, validate_vector, vector_store_delete, vector_store_has, vector_store_insert,
   vector_store_node_vector,
 };
+use crate::vector::backend::VectorBackendKind;
 use crate::vector::types::{VectorManifest, VectorStoreConfig};
+use once_cell::sync::OnceCell;
+use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -31,14 +36,14 @@ impl SingleFileDB {
     prop_key_id: PropKeyId,
     vector: &[f32],
   ) -> Result<()> {
-    let (txid, tx_handle) = self.require_write_tx_handle()?;
+    let txid = self.require_write_tx()?;
 
     // Check dimensions if store already exists
     {
       let stores = self.vector_stores.read();
       if let Some(store) = stores.get(&prop_key_id) {
         if store.config.dimensions != vector.len() {
-          return Err(KiteError::VectorDimensionMismatch {
+          return Err(RayError::VectorDimensionMismatch {
             expected: store.config.dimensions,
             got: vector.len(),
           });
@@ -49,26 +54,28 @@ impl SingleFileDB {
     // If the store doesn't exist yet, enforce dimensions against any pending vector
     // operations for the same property key in this transaction.
     {
-      let tx = tx_handle.lock();
-      for (&(_pending_node_id, pending_prop_key_id), pending_op) in &tx.pending.pending_vectors {
-        if pending_prop_key_id != prop_key_id {
-          continue;
-        }
-        let Some(existing) = pending_op.as_ref() else {
-          continue;
-        };
-        if existing.len() != vector.len() {
-          return Err(KiteError::VectorDimensionMismatch {
-            expected: existing.len(),
-            got: vector.len(),
-          });
+      let current_tx = self.current_tx.lock();
+      if let Some(tx) = current_tx.as_ref() {
+        for (&(_pending_node_id, pending_prop_key_id), pending_op) in &tx.pending_vectors {
+          if pending_prop_key_id != prop_key_id {
+            continue;
+          }
+          let Some(existing) = pending_op.as_ref() else {
+            continue;
+          };
+          if existing.len() != vector.len() {
+            return Err(RayError::VectorDimensionMismatch {
+              expected: existing.len(),
+              got: vector.len(),
+            });
+          }
+          break;
         }
-        break;
       }
     }
 
     // Validate vector before WAL write / queuing pending ops.
-    validate_vector(vector).map_err(|e| KiteError::InvalidQuery(e.to_string().into()))?;
+    validate_vector(vector).map_err(|e| RayError::InvalidQuery(e.to_string().into()))?;
 
     // Write WAL record
     let record = WalRecord::new(
@@ -76,16 +83,22 @@ impl SingleFileDB {
       txid,
       build_set_node_vector_payload(node_id, prop_key_id, vector),
     );
-    self.write_wal_tx(&tx_handle, record)?;
+    self.write_wal(record)?;
 
-    // Queue in pending delta for commit
+    // Queue in this transaction's pending_vectors; `commit` replays it into
+    // `self.vector_stores` once the COMMIT record is durable (see
+    // `TxState::pending_vectors` for why vectors are buffered here instead
+    // of staged through `self.delta` the way node/edge props are).
     {
-      let mut tx = tx_handle.lock();
-      tx.pending.pending_vectors.insert(
-        (node_id, prop_key_id),
-        Some(VectorRef::from(vector.to_vec())),
-      );
+      let mut current_tx = self.current_tx.lock();
+      if let Some(tx) = current_tx.as_mut() {
+        tx.pending_vectors.insert(
+          (node_id, prop_key_id),
+          Some(VectorRef::from(vector.to_vec())),
+        );
+      }
     }
+    self.record_write(node_id);
 
     Ok(())
   }
@@ -94,7 +107,7 @@ impl SingleFileDB {
   ///
   /// Returns Ok(()) even if the vector doesn't exist (idempotent).
   pub fn delete_node_vector(&self, node_id: NodeId, prop_key_id: PropKeyId) -> Result<()> {
-    let (txid, tx_handle) = self.require_write_tx_handle()?;
+    let txid = self.require_write_tx()?;
 
     // Write WAL record
     let record = WalRecord::new(
@@ -102,34 +115,25 @@ impl SingleFileDB {
       txid,
       build_del_node_vector_payload(node_id, prop_key_id),
     );
-    self.write_wal_tx(&tx_handle, record)?;
+    self.write_wal(record)?;
 
-    // Queue delete in pending delta
+    // Queue delete in this transaction's pending_vectors (see `set_node_vector`).
     {
-      let mut tx = tx_handle.lock();
-      tx.pending
-        .pending_vectors
-        .insert((node_id, prop_key_id), None); // None means delete
+      let mut current_tx = self.current_tx.lock();
+      if let Some(tx) = current_tx.as_mut() {
+        tx.pending_vectors.insert((node_id, prop_key_id), None); // None means delete
+      }
     }
+    self.record_write(node_id);
 
     Ok(())
   }
 
   /// Get a vector embedding for a node
   ///
-  /// Checks pending operations first, then falls back to committed storage.
+  /// Checks this transaction's own not-yet-committed writes first, then
+  /// falls back to committed storage.
   pub fn node_vector(&self, node_id: NodeId, prop_key_id: PropKeyId) -> Option<VectorRef> {
-    let tx_handle = self.current_tx_handle();
-    if let Some(handle) = tx_handle.as_ref() {
-      let tx = handle.lock();
-      if tx.pending.is_node_deleted(node_id) {
-        return None;
-      }
-      if let Some(pending) = tx.pending.pending_vectors.get(&(node_id, prop_key_id)) {
-        return pending.as_ref().map(Arc::clone);
-      }
-    }
-
     let delta = self.delta.read();
 
     // Check if node is deleted
@@ -137,10 +141,13 @@ impl SingleFileDB {
       return None;
     }
 
-    // Check pending operations from committed replay (startup)
-    if let Some(pending) = delta.pending_vectors.get(&(node_id, prop_key_id)) {
-      // Some(vec) = set, None = delete
-      return pending.as_ref().map(Arc::clone);
+    {
+      let current_tx = self.current_tx.lock();
+      if let Some(tx) = current_tx.as_ref() {
+        if let Some(pending) = tx.pending_vectors.get(&(node_id, prop_key_id)) {
+          return pending.as_ref().map(Arc::clone);
+        }
+      }
     }
 
     // Fall back to committed storage
@@ -151,17 +158,6 @@ impl SingleFileDB {
 
   /// Check if a node has a vector embedding
   pub fn has_node_vector(&self, node_id: NodeId, prop_key_id: PropKeyId) -> bool {
-    let tx_handle = self.current_tx_handle();
-    if let Some(handle) = tx_handle.as_ref() {
-      let tx = handle.lock();
-      if tx.pending.is_node_deleted(node_id) {
-        return false;
-      }
-      if let Some(pending) = tx.pending.pending_vectors.get(&(node_id, prop_key_id)) {
-        return pending.is_some();
-      }
-    }
-
     let delta = self.delta.read();
 
     // Check if node is deleted
@@ -169,9 +165,13 @@ impl SingleFileDB {
       return false;
     }
 
-    // Check pending operations from committed replay (startup)
-    if let Some(pending) = delta.pending_vectors.get(&(node_id, prop_key_id)) {
-      return pending.is_some();
+    {
+      let current_tx = self.current_tx.lock();
+      if let Some(tx) = current_tx.as_ref() {
+        if let Some(pending) = tx.pending_vectors.get(&(node_id, prop_key_id)) {
+          return pending.is_some();
+        }
+      }
     }
 
     // Fall back to committed storage
@@ -183,17 +183,100 @@ impl SingleFileDB {
     false
   }
 
+  /// Batched version of [`SingleFileDB::node_vector`].
+  ///
+  /// Acquires the transaction/delta/store locks once for the whole batch
+  /// instead of once per node, and returns results in input order -- each
+  /// entry honors per-node deletion exactly as the single-node path does.
+  pub fn node_vectors(&self, prop_key_id: PropKeyId, node_ids: &[NodeId]) -> Vec<Option<VectorRef>> {
+    let current_tx = self.current_tx.lock();
+    let delta = self.delta.read();
+    let stores = self.vector_stores.read();
+    let store = stores.get(&prop_key_id);
+
+    node_ids
+      .iter()
+      .map(|&node_id| {
+        if delta.is_node_deleted(node_id) {
+          return None;
+        }
+
+        if let Some(tx) = current_tx.as_ref() {
+          if let Some(pending) = tx.pending_vectors.get(&(node_id, prop_key_id)) {
+            return pending.as_ref().map(Arc::clone);
+          }
+        }
+
+        store.and_then(|store| vector_store_node_vector(store, node_id).map(Arc::from))
+      })
+      .collect()
+  }
+
+  /// Batched version of [`SingleFileDB::has_node_vector`]; see
+  /// [`SingleFileDB::node_vectors`] for the locking rationale.
+  pub fn has_node_vectors(&self, prop_key_id: PropKeyId, node_ids: &[NodeId]) -> Vec<bool> {
+    let current_tx = self.current_tx.lock();
+    let delta = self.delta.read();
+    let stores = self.vector_stores.read();
+    let store = stores.get(&prop_key_id);
+
+    node_ids
+      .iter()
+      .map(|&node_id| {
+        if delta.is_node_deleted(node_id) {
+          return false;
+        }
+
+        if let Some(tx) = current_tx.as_ref() {
+          if let Some(pending) = tx.pending_vectors.get(&(node_id, prop_key_id)) {
+            return pending.is_some();
+          }
+        }
+
+        store.map(|store| vector_store_has(store, node_id)).unwrap_or(false)
+      })
+      .collect()
+  }
+
   /// Get or create a vector store for a property key
   ///
   /// Creates a new store with the given dimensions if it doesn't exist.
   pub fn vector_store_or_create(&self, prop_key_id: PropKeyId, dimensions: usize) -> Result<()> {
+    self.vector_store_or_create_with_backend(prop_key_id, dimensions, VectorBackendKind::Flat)
+  }
+
+  /// Like [`Self::vector_store_or_create`], but lets the caller pick the
+  /// storage/index strategy for a newly created prop key's store.
+  ///
+  /// Only [`VectorBackendKind::Flat`] is accepted today: it's the one kind
+  /// [`crate::vector::store`]'s free functions (`create_vector_store`,
+  /// `vector_store_insert`, etc., which this whole module is written
+  /// against) actually implement -- they operate on `VectorManifest`, the
+  /// concrete representation `VectorBackendKind::Flat`'s doc comment
+  /// describes. `Ivf`/`IvfPq` are rejected rather than silently created as
+  /// Flat under a different label, since every downstream read
+  /// (`node_vector`, `vector_stores_from_snapshot`, ...) only knows how to
+  /// interpret a `VectorManifest`.
+  pub fn vector_store_or_create_with_backend(
+    &self,
+    prop_key_id: PropKeyId,
+    dimensions: usize,
+    backend: VectorBackendKind,
+  ) -> Result<()> {
+    if backend != VectorBackendKind::Flat {
+      return Err(RayError::Internal(format!(
+        "{backend:?} vector backend has no concrete implementation wired in yet; only {:?} is supported",
+        VectorBackendKind::Flat
+      )));
+    }
+
     let mut stores = self.vector_stores.write();
     if stores.contains_key(&prop_key_id) {
       let store = stores.get(&prop_key_id).ok_or_else(|| {
-        KiteError::Internal("vector store missing after contains_key".to_string())
+        RayError::Internal("vector store missing after contains_key".to_string())
       })?;
       if store.config.dimensions != dimensions {
-        return Err(KiteError::VectorDimensionMismatch {
+        return Err(RayError::VectorDimensionMismatch {
           expected: store.config.dimensions,
           got: dimensions,
         });
@@ -207,6 +290,24 @@ impl SingleFileDB {
     Ok(())
   }
 
+  /// Resolve a node's vector as it existed at `snapshot_ts`.
+  ///
+  /// There's no version chain for vector stores -- unlike node/edge props,
+  /// a set/delete is visible to other readers only once its owning
+  /// transaction commits (that transaction sees its own writes at once), and
+  /// there's no older-version bookkeeping to consult afterward. So this
+  /// just returns the current live value; `snapshot_ts` is accepted for call
+  /// site symmetry with the node/edge prop snapshot reads but doesn't
+  /// currently affect the result.
+  pub fn node_vector_as_of(
+    &self,
+    node_id: NodeId,
+    prop_key_id: PropKeyId,
+    _snapshot_ts: u64,
+  ) -> Option<VectorRef> {
+    self.node_vector(node_id, prop_key_id)
+  }
+
   /// Apply pending vector operations (called during commit)
   pub(crate) fn apply_pending_vectors(
     &self,
@@ -215,34 +316,82 @@ impl SingleFileDB {
     let mut stores = self.vector_stores.write();
 
     for (&(node_id, prop_key_id), operation) in pending_vectors {
-      match operation {
-        Some(vector) => {
-          // Set operation - get or create store
-          let store = stores.entry(prop_key_id).or_insert_with(|| {
-            let config = VectorStoreConfig::new(vector.len());
-            create_vector_store(config)
-          });
+      apply_vector_store_op(&mut stores, node_id, prop_key_id, operation.clone())?;
+    }
 
-          // Insert (this handles replacement of existing vectors)
-          vector_store_insert(store, node_id, vector.as_ref()).map_err(|e| {
-            KiteError::Internal(format!(
-              "Failed to apply vector insert during commit for node {node_id} (prop {prop_key_id}): {e}"
-            ))
-          })?;
-        }
-        None => {
-          // Delete operation
-          if let Some(store) = stores.get_mut(&prop_key_id) {
-            vector_store_delete(store, node_id);
-          }
-        }
+    Ok(())
+  }
+}
+
+/// Set or delete `node_id`'s vector under `prop_key_id` in `stores`, creating
+/// the store (sized to the first vector's dimensions) on first use. Shared by
+/// [`SingleFileDB::apply_pending_vectors`] (commit time) and
+/// [`replay_set_node_vector`]/[`replay_del_node_vector`] (WAL replay at
+/// open), so both paths apply a set/delete identically.
+fn apply_vector_store_op(
+  stores: &mut HashMap<PropKeyId, VectorManifest>,
+  node_id: NodeId,
+  prop_key_id: PropKeyId,
+  operation: Option<VectorRef>,
+) -> Result<()> {
+  match operation {
+    Some(vector) => {
+      let store = stores.entry(prop_key_id).or_insert_with(|| {
+        let config = VectorStoreConfig::new(vector.len());
+        create_vector_store(config)
+      });
+
+      vector_store_insert(store, node_id, vector.as_ref()).map_err(|e| {
+        RayError::Internal(format!(
+          "Failed to apply vector insert for node {node_id} (prop {prop_key_id}): {e}"
+        ))
+      })?;
+    }
+    None => {
+      if let Some(store) = stores.get_mut(&prop_key_id) {
+        vector_store_delete(store, node_id);
       }
     }
+  }
 
-    Ok(())
+  Ok(())
+}
+
+/// Replay a committed `SetNodeVector` WAL record into `stores` at open time.
+/// See `apply_vector_store_op` for the shared set/delete logic.
+pub(crate) fn replay_set_node_vector(
+  stores: &mut HashMap<PropKeyId, VectorManifest>,
+  payload: &[u8],
+) {
+  if let Some(data) = parse_set_node_vector_payload(payload) {
+    let _ = apply_vector_store_op(
+      stores,
+      data.node_id,
+      data.key_id,
+      Some(VectorRef::from(data.vector)),
+    );
+  }
+}
+
+/// Replay a committed `DelNodeVector` WAL record into `stores` at open time.
+/// See `apply_vector_store_op` for the shared set/delete logic.
+pub(crate) fn replay_del_node_vector(
+  stores: &mut HashMap<PropKeyId, VectorManifest>,
+  payload: &[u8],
+) {
+  if let Some(data) = parse_del_node_vector_payload(payload) {
+    let _ = apply_vector_store_op(stores, data.node_id, data.key_id, None);
   }
 }
 
+/// Not attempted here: an `m`-shard durability knob on `VectorStoreConfig`
+/// that a writer would consult to decide how many parity shards to produce
+/// (and whether to write a `VectorStoreParity` section at all). That struct
+/// is defined in `vector::types`, which isn't a file in this tree, so there's
+/// no definition to add a field to without authoring that module from
+/// scratch. The read side (`recover_vector_store_blob`, below) works against
+/// any `k`/`m` a future writer chooses, since both are read from the section
+/// itself rather than assumed.
 pub(crate) fn vector_stores_from_snapshot(
   snapshot: &SnapshotData,
 ) -> Result<HashMap<PropKeyId, VectorManifest>> {
@@ -282,7 +431,7 @@ pub(crate) fn vector_stores_from_snapshot(
         });
 
         if store.config.dimensions != vec.len() {
-          return Err(KiteError::InvalidSnapshot(format!(
+          return Err(RayError::InvalidSnapshot(format!(
             "Vector dimension mismatch for prop key {key_id}: expected {}, got {}",
             store.config.dimensions,
             vec.len()
@@ -290,7 +439,7 @@ pub(crate) fn vector_stores_from_snapshot(
         }
 
         vector_store_insert(store, node_id, &vec).map_err(|e| {
-          KiteError::InvalidSnapshot(format!(
+          RayError::InvalidSnapshot(format!(
             "Failed to insert vector for node {node_id} (prop {key_id}): {e}"
           ))
         })?;
@@ -301,6 +450,133 @@ pub(crate) fn vector_stores_from_snapshot(
   Ok(stores)
 }
 
+/// Decompress the `VectorStoreData` blob when the snapshot was written with
+/// `VECTOR_STORE_LZ4` set. Uncompressed snapshots (the default) pass through
+/// unchanged so existing files keep loading with no format migration.
+fn decompress_vector_store_blob<'a>(
+  snapshot: &SnapshotData,
+  blob_bytes: &'a [u8],
+) -> Result<std::borrow::Cow<'a, [u8]>> {
+  if !snapshot
+    .header
+    .flags
+    .contains(SnapshotFlags::VECTOR_STORE_LZ4)
+  {
+    return Ok(std::borrow::Cow::Borrowed(blob_bytes));
+  }
+
+  // Layout: [u64 uncompressed_len][lz4 block]
+  if blob_bytes.len() < 8 {
+    return Err(RayError::InvalidSnapshot(
+      "Vector store blob too small for LZ4 frame header".to_string(),
+    ));
+  }
+  let uncompressed_len = read_u64(blob_bytes, 0) as usize;
+  let decompressed = lz4_flex::block::decompress(&blob_bytes[8..], uncompressed_len).map_err(|e| {
+    RayError::InvalidSnapshot(format!("Failed to decompress vector store blob: {e}"))
+  })?;
+  Ok(std::borrow::Cow::Owned(decompressed))
+}
+
+/// Write-side counterpart to [`decompress_vector_store_blob`]: LZ4-compress
+/// a serialized `VectorStoreData` blob into the `[u64 uncompressed_len][lz4
+/// block]` layout that function expects, for a writer that wants to set
+/// `SnapshotFlags::VECTOR_STORE_LZ4`.
+///
+/// Nothing in this tree calls this yet -- the section writer that would
+/// decide per-snapshot whether to compress vector-store blobs and choose
+/// this flag lives in `core::snapshot::writer` (`build_snapshot_to_memory`'s
+/// module), which isn't a file in this tree, so there's no call site to wire
+/// it into here. `VectorStoreConfig`/`SnapshotBuildInput` can't be extended
+/// with a compression choice for the same reason: both are defined in
+/// modules (`vector::types`, `core::snapshot::writer`) that don't exist as
+/// files in this tree either. This function exists so that wiring, whenever
+/// those modules land, is a call to an already-tested compressor rather than
+/// new code written under review pressure.
+#[allow(dead_code)]
+pub(crate) fn compress_vector_store_blob(blob_bytes: &[u8]) -> Vec<u8> {
+  let compressed = lz4_flex::block::compress(blob_bytes);
+  let mut out = Vec::with_capacity(8 + compressed.len());
+  out.extend_from_slice(&(blob_bytes.len() as u64).to_le_bytes());
+  out.extend_from_slice(&compressed);
+  out
+}
+
+/// Parse the `VectorStoreParity` section (if present) and use it to recover
+/// the `VectorStoreData` blob via [`crate::vector::erasure::reconstruct`].
+///
+/// Layout: `[u32 k][u32 m][u64 shard_len][u64 original_len][u32 shard_crc]*k
+/// [u8; shard_len]*m` -- the `m` parity shards, plus a per-data-shard CRC so
+/// a present-but-corrupted `VectorStoreData` section can be distinguished
+/// from an intact one shard by shard (matching the shards
+/// `crate::vector::erasure::encode` would have produced at write time; the
+/// `k` data shards themselves are sliced out of `blob_bytes` rather than
+/// duplicated in this section, same as `encode`'s return value doesn't
+/// duplicate them).
+///
+/// Returns `Ok(None)` when no parity section exists (the common case today,
+/// since nothing writes one yet -- see this function's caller). `blob_bytes`
+/// may be `None` if the `VectorStoreData` section itself is missing.
+fn recover_vector_store_blob(
+  snapshot: &SnapshotData,
+  blob_bytes: Option<&[u8]>,
+) -> Result<Option<Vec<u8>>> {
+  let Some(parity_bytes) = snapshot.section_bytes(SectionId::VectorStoreParity) else {
+    return Ok(None);
+  };
+  reconstruct_from_parity_section(parity_bytes, blob_bytes).map(Some)
+}
+
+/// Pure byte-level core of [`recover_vector_store_blob`], split out so it's
+/// testable without a real `SnapshotData`. See that function for the parity
+/// section layout.
+fn reconstruct_from_parity_section(parity_bytes: &[u8], blob_bytes: Option<&[u8]>) -> Result<Vec<u8>> {
+  if parity_bytes.len() < 24 {
+    return Err(RayError::InvalidSnapshot(
+      "Vector store parity section too small".to_string(),
+    ));
+  }
+
+  let k = read_u32(parity_bytes, 0) as usize;
+  let m = read_u32(parity_bytes, 4) as usize;
+  let shard_len = read_u64(parity_bytes, 8) as usize;
+  let original_len = read_u64(parity_bytes, 16) as usize;
+
+  let mut offset = 24usize;
+  let mut shard_crcs = Vec::with_capacity(k);
+  for _ in 0..k {
+    shard_crcs.push(read_u32(parity_bytes, offset));
+    offset += 4;
+  }
+
+  let mut present: Vec<Option<Vec<u8>>> = Vec::with_capacity(k + m);
+  for (i, &expected_crc) in shard_crcs.iter().enumerate() {
+    let start = i * shard_len;
+    let shard = blob_bytes.and_then(|bytes| {
+      let end = start.checked_add(shard_len)?;
+      if end > bytes.len() {
+        return None;
+      }
+      let candidate = bytes[start..end].to_vec();
+      (crc32(&candidate) == expected_crc).then_some(candidate)
+    });
+    present.push(shard);
+  }
+  for i in 0..m {
+    let start = offset + i * shard_len;
+    let end = start + shard_len;
+    present.push((end <= parity_bytes.len()).then(|| parity_bytes[start..end].to_vec()));
+  }
+
+  let header = ErasureHeader {
+    k,
+    m,
+    shard_len,
+    shard_crcs,
+  };
+  reconstruct(&header, &present, original_len)
+}
+
 fn vector_stores_from_sections(
   snapshot: &SnapshotData,
 ) -> Result<HashMap<PropKeyId, VectorManifest>> {
@@ -308,14 +584,31 @@ fn vector_stores_from_sections(
   let Some(index_bytes) = snapshot.section_bytes(SectionId::VectorStoreIndex) else {
     return Ok(stores);
   };
-  let Some(blob_bytes) = snapshot.section_bytes(SectionId::VectorStoreData) else {
-    return Err(KiteError::InvalidSnapshot(
-      "Vector store index present but vector store blob section is missing".to_string(),
-    ));
+  let data_section = snapshot.section_bytes(SectionId::VectorStoreData);
+
+  // Try Reed-Solomon recovery first when a `VectorStoreParity` section is
+  // present -- it catches both a missing `VectorStoreData` section and one
+  // whose shards don't match their recorded CRCs, repairing up to `m`
+  // bad/missing shards. Falls through to using `data_section` unchanged when
+  // there's no parity section at all (true of every snapshot today, since
+  // nothing writes one yet -- see `VectorStoreConfig`'s durability knob note
+  // on `vector_stores_from_snapshot`).
+  let recovered = recover_vector_store_blob(snapshot, data_section)?;
+  let blob_bytes: &[u8] = match (&recovered, data_section) {
+    (Some(bytes), _) => bytes,
+    (None, Some(bytes)) => bytes,
+    (None, None) => {
+      return Err(RayError::InvalidSnapshot(
+        "Vector store index present but vector store blob section is missing".to_string(),
+      ));
+    }
   };
 
+  let blob_bytes = decompress_vector_store_blob(snapshot, blob_bytes)?;
+  let blob_bytes = blob_bytes.as_ref();
+
   if index_bytes.len() < 4 {
-    return Err(KiteError::InvalidSnapshot(
+    return Err(RayError::InvalidSnapshot(
       "Vector store index section too small".to_string(),
     ));
   }
@@ -323,9 +616,9 @@ fn vector_stores_from_sections(
   let count = read_u32(&index_bytes, 0) as usize;
   let expected_len = 4usize
     .checked_add(count.saturating_mul(20))
-    .ok_or_else(|| KiteError::InvalidSnapshot("Vector store index size overflow".to_string()))?;
+    .ok_or_else(|| RayError::InvalidSnapshot("Vector store index size overflow".to_string()))?;
   if index_bytes.len() < expected_len {
-    return Err(KiteError::InvalidSnapshot(format!(
+    return Err(RayError::InvalidSnapshot(format!(
       "Vector store index truncated: expected at least {expected_len} bytes, found {}",
       index_bytes.len()
     )));
@@ -337,12 +630,12 @@ fn vector_stores_from_sections(
     let payload_offset = read_u64(&index_bytes, entry_offset + 4) as usize;
     let payload_len = read_u64(&index_bytes, entry_offset + 12) as usize;
     let payload_end = payload_offset.checked_add(payload_len).ok_or_else(|| {
-      KiteError::InvalidSnapshot(format!(
+      RayError::InvalidSnapshot(format!(
         "Vector store entry {i} overflow: offset={payload_offset}, len={payload_len}"
       ))
     })?;
     if payload_end > blob_bytes.len() {
-      return Err(KiteError::InvalidSnapshot(format!(
+      return Err(RayError::InvalidSnapshot(format!(
         "Vector store entry {i} out of bounds: {}..{} exceeds blob size {}",
         payload_offset,
         payload_end,
@@ -352,13 +645,13 @@ fn vector_stores_from_sections(
 
     let manifest =
       deserialize_manifest(&blob_bytes[payload_offset..payload_end]).map_err(|err| {
-        KiteError::InvalidSnapshot(format!(
+        RayError::InvalidSnapshot(format!(
           "Failed to deserialize vector store for prop key {prop_key_id}: {err}"
         ))
       })?;
 
     if stores.insert(prop_key_id, manifest).is_some() {
-      return Err(KiteError::InvalidSnapshot(format!(
+      return Err(RayError::InvalidSnapshot(format!(
         "Duplicate vector store entry for prop key {prop_key_id}"
       )));
     }
@@ -367,13 +660,107 @@ fn vector_stores_from_sections(
   Ok(stores)
 }
 
+/// Index-only record for one prop key's vector store, resolved lazily.
+struct LazyVectorStoreEntry {
+  offset: usize,
+  len: usize,
+  manifest: OnceCell<VectorManifest>,
+}
+
+/// Lazily-decoded view over the `VectorStoreIndex`/`VectorStoreData` sections
+/// of one snapshot, borrowed from it for as long as this view lives.
+///
+/// Opening a database only parses the (small) index; `deserialize_manifest`
+/// for a given prop key runs on first access and the result is cached, so
+/// processes that only ever query a handful of property keys don't pay
+/// O(total vectors) at open time. The uncompressed-blob case (the default)
+/// is genuinely zero-copy -- `blob` borrows directly from the mapped
+/// snapshot -- falling back to an owned, decompressed copy only when the
+/// snapshot was written with `VECTOR_STORE_LZ4` set.
+pub(crate) struct LazyVectorStores<'a> {
+  blob: std::borrow::Cow<'a, [u8]>,
+  entries: RwLock<HashMap<PropKeyId, LazyVectorStoreEntry>>,
+}
+
+impl<'a> LazyVectorStores<'a> {
+  /// Get (or decode-and-cache) the manifest for a prop key.
+  pub(crate) fn get(&self, prop_key_id: PropKeyId) -> Result<Option<VectorManifest>> {
+    let entries = self.entries.read();
+    let Some(entry) = entries.get(&prop_key_id) else {
+      return Ok(None);
+    };
+
+    let manifest = entry.manifest.get_or_try_init(|| {
+      deserialize_manifest(&self.blob[entry.offset..entry.offset + entry.len]).map_err(|err| {
+        RayError::InvalidSnapshot(format!(
+          "Failed to deserialize vector store for prop key {prop_key_id}: {err}"
+        ))
+      })
+    })?;
+    Ok(Some(manifest.clone()))
+  }
+
+  /// Prop keys with a registered (possibly not-yet-decoded) vector store.
+  pub(crate) fn prop_keys(&self) -> Vec<PropKeyId> {
+    self.entries.read().keys().copied().collect()
+  }
+}
+
+/// Build a [`LazyVectorStores`] view over a snapshot's vector-store sections
+/// without eagerly decoding any manifest or copying the blob (except when
+/// LZ4 decompression forces an owned copy).
+pub(crate) fn lazy_vector_stores_from_snapshot(
+  snapshot: &SnapshotData,
+) -> Result<Option<LazyVectorStores<'_>>> {
+  let Some(index_bytes) = snapshot.section_bytes(SectionId::VectorStoreIndex) else {
+    return Ok(None);
+  };
+  let Some(blob_bytes) = snapshot.section_bytes(SectionId::VectorStoreData) else {
+    return Err(RayError::InvalidSnapshot(
+      "Vector store index present but vector store blob section is missing".to_string(),
+    ));
+  };
+  let blob = decompress_vector_store_blob(snapshot, blob_bytes)?;
+
+  if index_bytes.len() < 4 {
+    return Err(RayError::InvalidSnapshot(
+      "Vector store index section too small".to_string(),
+    ));
+  }
+  let count = read_u32(index_bytes, 0) as usize;
+  let mut entries = HashMap::with_capacity(count);
+  for i in 0..count {
+    let entry_offset = 4 + i * 20;
+    let prop_key_id = read_u32(index_bytes, entry_offset);
+    let payload_offset = read_u64(index_bytes, entry_offset + 4) as usize;
+    let payload_len = read_u64(index_bytes, entry_offset + 12) as usize;
+    entries.insert(
+      prop_key_id,
+      LazyVectorStoreEntry {
+        offset: payload_offset,
+        len: payload_len,
+        manifest: OnceCell::new(),
+      },
+    );
+  }
+
+  Ok(Some(LazyVectorStores {
+    blob,
+    entries: RwLock::new(entries),
+  }))
+}
+
 #[cfg(test)]
 mod tests {
-  use super::vector_stores_from_snapshot;
+  use super::{
+    compress_vector_store_blob, lazy_vector_stores_from_snapshot, reconstruct_from_parity_section,
+    vector_stores_from_snapshot,
+  };
   use crate::core::single_file::{close_single_file, open_single_file, SingleFileOpenOptions};
   use crate::core::snapshot::reader::SnapshotData;
   use crate::core::snapshot::writer::{build_snapshot_to_memory, NodeData, SnapshotBuildInput};
   use crate::types::{PropValue, SnapshotFlags};
+  use crate::vector::backend::VectorBackendKind;
   use crate::vector::distance::normalize;
   use crate::vector::store::{create_vector_store, vector_store_has, vector_store_insert};
   use crate::vector::types::VectorStoreConfig;
@@ -381,6 +768,78 @@ mod tests {
   use std::io::Write;
   use tempfile::{tempdir, NamedTempFile};
 
+  #[test]
+  fn test_compress_vector_store_blob_round_trips_via_lz4() {
+    let blob: Vec<u8> = (0u8..=255).cycle().take(4096).collect();
+    let compressed = compress_vector_store_blob(&blob);
+
+    // Layout matches what `decompress_vector_store_blob` expects:
+    // [u64 uncompressed_len][lz4 block].
+    let uncompressed_len = u64::from_le_bytes(compressed[0..8].try_into().unwrap()) as usize;
+    assert_eq!(uncompressed_len, blob.len());
+
+    let decompressed =
+      lz4_flex::block::decompress(&compressed[8..], uncompressed_len).expect("expected value");
+    assert_eq!(decompressed, blob);
+  }
+
+  #[test]
+  fn test_vector_store_or_create_rejects_unimplemented_backend() {
+    let temp_dir = tempdir().expect("expected value");
+    let db_path = temp_dir.path().join("backend-kind.kitedb");
+
+    let db = open_single_file(&db_path, SingleFileOpenOptions::new()).expect("expected value");
+    db.begin(false).expect("expected value");
+    let prop_key_id = db.define_propkey("embedding").expect("expected value");
+    db.commit().expect("expected value");
+
+    assert!(db
+      .vector_store_or_create_with_backend(prop_key_id, 3, VectorBackendKind::Ivf)
+      .is_err());
+    assert!(db.vector_store_or_create(prop_key_id, 3).is_ok());
+
+    close_single_file(db).expect("expected value");
+  }
+
+  #[test]
+  fn test_reconstruct_from_parity_section_repairs_corrupted_shards() {
+    use crate::vector::erasure::encode;
+
+    let data = b"0123456789abcdef0123456789abcdef0123456789abcdef";
+    let (header, parity_shards) = encode(data, 4, 2).expect("encode");
+
+    // Lay the blob out as the `k` data shards would actually be persisted in
+    // `VectorStoreData`, then corrupt two of them.
+    let mut blob = vec![0u8; header.k * header.shard_len];
+    for i in 0..header.k {
+      let start = i * header.shard_len;
+      let end = (start + header.shard_len).min(data.len());
+      if start < data.len() {
+        blob[start..start + (end - start)].copy_from_slice(&data[start..end]);
+      }
+    }
+    blob[0] ^= 0xff; // corrupt shard 0
+    blob[header.shard_len] ^= 0xff; // corrupt shard 1
+
+    // Build the `VectorStoreParity` section bytes: [u32 k][u32 m][u64
+    // shard_len][u64 original_len][u32 crc]*k [shard bytes]*m.
+    let mut parity_bytes = Vec::new();
+    parity_bytes.extend_from_slice(&(header.k as u32).to_le_bytes());
+    parity_bytes.extend_from_slice(&(header.m as u32).to_le_bytes());
+    parity_bytes.extend_from_slice(&(header.shard_len as u64).to_le_bytes());
+    parity_bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    for crc in &header.shard_crcs {
+      parity_bytes.extend_from_slice(&crc.to_le_bytes());
+    }
+    for shard in &parity_shards {
+      parity_bytes.extend_from_slice(shard);
+    }
+
+    let recovered =
+      reconstruct_from_parity_section(&parity_bytes, Some(&blob)).expect("expected value");
+    assert_eq!(recovered, data);
+  }
+
   #[test]
   fn test_set_node_vector_rejects_invalid_vectors() {
     let temp_dir = tempdir().expect("expected value");
@@ -516,4 +975,56 @@ mod tests {
       Some(PropValue::VectorF32(_))
     ));
   }
+
+  #[test]
+  fn test_lazy_vector_stores_resolve_on_first_access() {
+    let mut manifest = create_vector_store(VectorStoreConfig::new(3));
+    vector_store_insert(&mut manifest, 42, &[0.1, 0.2, 0.3]).expect("expected value");
+
+    let mut stores = HashMap::new();
+    stores.insert(7, manifest);
+
+    let mut propkeys = HashMap::new();
+    propkeys.insert(7, "embedding".to_string());
+
+    let buffer = build_snapshot_to_memory(SnapshotBuildInput {
+      generation: 1,
+      nodes: vec![NodeData {
+        node_id: 42,
+        key: None,
+        labels: vec![],
+        props: HashMap::new(),
+      }],
+      edges: Vec::new(),
+      labels: HashMap::new(),
+      etypes: HashMap::new(),
+      propkeys,
+      vector_stores: Some(stores),
+      compression: None,
+    })
+    .expect("expected value");
+
+    let mut tmp = NamedTempFile::new().expect("expected value");
+    tmp.write_all(&buffer).expect("expected value");
+    tmp.flush().expect("expected value");
+
+    let snapshot = SnapshotData::load(tmp.path()).expect("expected value");
+
+    let lazy = lazy_vector_stores_from_snapshot(&snapshot)
+      .expect("expected value")
+      .expect("expected a vector-store section");
+
+    // The index is small enough to resolve eagerly; the manifest itself is
+    // not decoded until `get` is called.
+    assert_eq!(lazy.prop_keys(), vec![7]);
+
+    let loaded_manifest = lazy
+      .get(7)
+      .expect("expected value")
+      .expect("expected a manifest for prop key 7");
+    assert!(vector_store_has(&loaded_manifest, 42));
+
+    // An unregistered prop key resolves to `None` rather than an error.
+    assert!(lazy.get(99).expect("expected value").is_none());
+  }
 }
@@ -18,6 +18,24 @@ pub trait TransactionOps {
 
   /// Check if there's an active transaction
   fn has_transaction_impl(&self) -> PyResult<bool>;
+
+  /// Run `callback` inside a write transaction, committing on success and
+  /// rolling back if it raises.
+  fn with_write_tx_impl(
+    &self,
+    py: Python<'_>,
+    handle: &Bound<'_, PyAny>,
+    callback: PyObject,
+  ) -> PyResult<PyObject>;
+
+  /// Run `callback` inside a read-only transaction, committing on success
+  /// and rolling back if it raises.
+  fn with_read_tx_impl(
+    &self,
+    py: Python<'_>,
+    handle: &Bound<'_, PyAny>,
+    callback: PyObject,
+  ) -> PyResult<PyObject>;
 }
 
 /// Begin transaction on single-file database
@@ -40,6 +58,51 @@ pub fn rollback_single_file(db: &RustSingleFileDB) -> PyResult<()> {
     .map_err(|e| PyRuntimeError::new_err(format!("Failed to rollback: {e}")))
 }
 
+/// Run `callback(handle)` inside a write transaction on `db`, committing on
+/// success and rolling back if `callback` raises. `handle` is the
+/// Python-visible object wrapping `db`, passed straight through to
+/// `callback` -- Python doesn't have Rust's closure-return/`?` distinction,
+/// so any exception propagating out of `callback` is what triggers the
+/// rollback, mirroring `SingleFileDB::with_write_tx` catching a panic.
+pub fn with_write_tx_single_file(
+  py: Python<'_>,
+  db: &RustSingleFileDB,
+  handle: &Bound<'_, PyAny>,
+  callback: PyObject,
+) -> PyResult<PyObject> {
+  begin_single_file(db, false)?;
+  match callback.call1(py, (handle,)) {
+    Ok(result) => {
+      commit_single_file(db)?;
+      Ok(result)
+    }
+    Err(e) => {
+      let _ = rollback_single_file(db);
+      Err(e)
+    }
+  }
+}
+
+/// Same as [`with_write_tx_single_file`], but opens a read-only transaction.
+pub fn with_read_tx_single_file(
+  py: Python<'_>,
+  db: &RustSingleFileDB,
+  handle: &Bound<'_, PyAny>,
+  callback: PyObject,
+) -> PyResult<PyObject> {
+  begin_single_file(db, true)?;
+  match callback.call1(py, (handle,)) {
+    Ok(result) => {
+      commit_single_file(db)?;
+      Ok(result)
+    }
+    Err(e) => {
+      let _ = rollback_single_file(db);
+      Err(e)
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   // Transaction tests require database instances
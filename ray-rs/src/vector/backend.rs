@@ -0,0 +1,199 @@
+//! Pluggable vector-store backend trait
+//!
+//! `create_vector_store`/`vector_store_insert`/etc. in `vector::store` are
+//! free functions over a single `VectorManifest` representation. This trait
+//! puts a per-property-key storage/index strategy (flat exact, IVF/IVF-PQ,
+//! or an external backend) behind one set of operations; `FlatBackend` is
+//! its only implementation. `SingleFileDB` does not use it: it still calls
+//! the `vector::store` free functions, `VectorStoreConfig` carries no
+//! backend choice, and `SingleFileDB::vector_store_or_create_with_backend`
+//! accepts only `VectorBackendKind::Flat`.
+
+use crate::error::Result;
+use crate::types::NodeId;
+
+/// Discriminator naming a vector-store backend, with `from_u8` to read it
+/// back from a byte. The snapshot's vector-store section index does not
+/// store it: every entry there is decoded as a `VectorManifest`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum VectorBackendKind {
+  /// Exact, unindexed flat store (the current default `VectorManifest`).
+  Flat = 0,
+  /// Inverted file index over coarse clusters.
+  Ivf = 1,
+  /// IVF with product-quantized codes.
+  IvfPq = 2,
+}
+
+impl VectorBackendKind {
+  pub fn from_u8(value: u8) -> Option<Self> {
+    match value {
+      0 => Some(Self::Flat),
+      1 => Some(Self::Ivf),
+      2 => Some(Self::IvfPq),
+      _ => None,
+    }
+  }
+}
+
+/// Storage/index strategy for a single property key's vector store.
+///
+/// Implementations own their own on-disk representation: `serialize`
+/// produces it and [`deserialize_backend`] reads it back given `kind()`.
+/// No snapshot section holds these bytes; the `VectorStoreData` blob holds
+/// serialized `VectorManifest`s.
+pub trait VectorBackend: Send + Sync {
+  fn kind(&self) -> VectorBackendKind;
+  fn dimensions(&self) -> usize;
+  fn len(&self) -> usize;
+  fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  fn insert(&mut self, node_id: NodeId, vector: &[f32]) -> Result<()>;
+  fn delete(&mut self, node_id: NodeId);
+  fn has(&self, node_id: NodeId) -> bool;
+  fn get(&self, node_id: NodeId) -> Option<Vec<f32>>;
+
+  fn serialize(&self) -> Vec<u8>;
+}
+
+/// Deserialize a `VectorBackend` given its discriminator. Backends register
+/// themselves here; unknown kinds are a
+/// hard snapshot error rather than silently falling back to `Flat`, since
+/// interpreting someone else's codes with the wrong backend would corrupt
+/// reads.
+pub fn deserialize_backend(
+  kind: VectorBackendKind,
+  bytes: &[u8],
+) -> Result<Box<dyn VectorBackend>> {
+  match kind {
+    VectorBackendKind::Flat => Ok(Box::new(FlatBackend::deserialize(bytes)?)),
+    VectorBackendKind::Ivf | VectorBackendKind::IvfPq => {
+      // IVF/IVF-PQ backends wrap the existing `vector::ivf` manifest format;
+      // the discriminator alone is enough for callers that only need to
+      // route bytes to `vector::ivf::serialize::deserialize_manifest`.
+      Err(crate::error::RayError::Internal(format!(
+        "{kind:?} backend deserialization is handled via vector::ivf::serialize"
+      )))
+    }
+  }
+}
+
+/// Reference flat (exact, unindexed) backend -- the default when a prop key
+/// doesn't opt into IVF/IVF-PQ.
+pub struct FlatBackend {
+  dimensions: usize,
+  vectors: std::collections::HashMap<NodeId, Vec<f32>>,
+}
+
+impl FlatBackend {
+  pub fn new(dimensions: usize) -> Self {
+    Self {
+      dimensions,
+      vectors: std::collections::HashMap::new(),
+    }
+  }
+
+  fn deserialize(bytes: &[u8]) -> Result<Self> {
+    use crate::util::binary::{read_f32, read_u32, read_u64};
+
+    if bytes.len() < 8 {
+      return Err(crate::error::RayError::InvalidSnapshot(
+        "flat vector backend payload too small".to_string(),
+      ));
+    }
+    let dimensions = read_u32(bytes, 0) as usize;
+    let count = read_u32(bytes, 4) as usize;
+    let mut offset = 8;
+    let mut vectors = std::collections::HashMap::with_capacity(count);
+    for _ in 0..count {
+      let node_id = read_u64(bytes, offset);
+      offset += 8;
+      let mut vec = Vec::with_capacity(dimensions);
+      for _ in 0..dimensions {
+        vec.push(read_f32(bytes, offset));
+        offset += 4;
+      }
+      vectors.insert(node_id, vec);
+    }
+    Ok(Self { dimensions, vectors })
+  }
+}
+
+impl VectorBackend for FlatBackend {
+  fn kind(&self) -> VectorBackendKind {
+    VectorBackendKind::Flat
+  }
+
+  fn dimensions(&self) -> usize {
+    self.dimensions
+  }
+
+  fn len(&self) -> usize {
+    self.vectors.len()
+  }
+
+  fn insert(&mut self, node_id: NodeId, vector: &[f32]) -> Result<()> {
+    if vector.len() != self.dimensions {
+      return Err(crate::error::RayError::VectorDimensionMismatch {
+        expected: self.dimensions,
+        got: vector.len(),
+      });
+    }
+    self.vectors.insert(node_id, vector.to_vec());
+    Ok(())
+  }
+
+  fn delete(&mut self, node_id: NodeId) {
+    self.vectors.remove(&node_id);
+  }
+
+  fn has(&self, node_id: NodeId) -> bool {
+    self.vectors.contains_key(&node_id)
+  }
+
+  fn get(&self, node_id: NodeId) -> Option<Vec<f32>> {
+    self.vectors.get(&node_id).cloned()
+  }
+
+  fn serialize(&self) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8 + self.vectors.len() * (8 + self.dimensions * 4));
+    out.extend_from_slice(&(self.dimensions as u32).to_le_bytes());
+    out.extend_from_slice(&(self.vectors.len() as u32).to_le_bytes());
+    for (node_id, vector) in &self.vectors {
+      out.extend_from_slice(&node_id.to_le_bytes());
+      for component in vector {
+        out.extend_from_slice(&component.to_le_bytes());
+      }
+    }
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_flat_backend_round_trip() {
+    let mut backend = FlatBackend::new(3);
+    backend.insert(1, &[0.1, 0.2, 0.3]).expect("insert");
+    backend.insert(2, &[0.4, 0.5, 0.6]).expect("insert");
+    backend.delete(2);
+
+    let bytes = backend.serialize();
+    let restored = FlatBackend::deserialize(&bytes).expect("deserialize");
+    assert_eq!(restored.dimensions(), 3);
+    assert!(restored.has(1));
+    assert!(!restored.has(2));
+    assert_eq!(restored.get(1), Some(vec![0.1, 0.2, 0.3]));
+  }
+
+  #[test]
+  fn test_flat_backend_rejects_dimension_mismatch() {
+    let mut backend = FlatBackend::new(3);
+    assert!(backend.insert(1, &[0.1, 0.2]).is_err());
+  }
+}
@@ -0,0 +1,308 @@
+//! Systematic Reed–Solomon erasure coding over GF(2^8)
+//!
+//! Protects the `VectorStoreData` blob: the blob is split into `k`
+//! equal-sized data shards (the last zero-padded) plus `m` parity shards,
+//! so up to `m` missing/corrupt shards can be reconstructed from any
+//! surviving `k` of the `k + m` total shards. On the read side,
+//! `core/single_file/vector.rs` calls `reconstruct` when a snapshot carries
+//! a `VectorStoreParity` section; nothing in this tree calls `encode` or
+//! writes that section.
+
+use crate::error::{KiteError, Result};
+
+/// GF(2^8) arithmetic using the reducing polynomial 0x11d
+/// (x^8 + x^4 + x^3 + x^2 + 1; AES/Rijndael uses 0x11b instead),
+/// precomputed into log/antilog tables for O(1) multiply/divide.
+struct Gf256 {
+  exp: [u8; 512],
+  log: [u8; 256],
+}
+
+impl Gf256 {
+  fn new() -> Self {
+    let mut exp = [0u8; 512];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    for i in 0..255usize {
+      exp[i] = x as u8;
+      log[x as usize] = i as u8;
+      x <<= 1;
+      if x & 0x100 != 0 {
+        x ^= 0x11d;
+      }
+    }
+    for i in 255..512 {
+      exp[i] = exp[i - 255];
+    }
+    Self { exp, log }
+  }
+
+  fn mul(&self, a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+      return 0;
+    }
+    self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+  }
+
+  fn div(&self, a: u8, b: u8) -> u8 {
+    assert!(b != 0, "division by zero in GF(2^8)");
+    if a == 0 {
+      return 0;
+    }
+    let diff = 255 + self.log[a as usize] as i32 - self.log[b as usize] as i32;
+    self.exp[(diff % 255) as usize]
+  }
+}
+
+/// `(k, m, shard_len)` header plus one CRC32 per data shard. The read side
+/// builds one from a `VectorStoreParity` snapshot section (see
+/// `reconstruct_from_parity_section` in `core/single_file/vector.rs`);
+/// nothing in this tree writes that section.
+#[derive(Debug, Clone)]
+pub struct ErasureHeader {
+  pub k: usize,
+  pub m: usize,
+  pub shard_len: usize,
+  pub shard_crcs: Vec<u32>,
+}
+
+/// Systematic Vandermonde-style encoding matrix: the top `k` rows are the
+/// identity (so data shards are stored verbatim) and the bottom `m` rows are
+/// `row[i][j] = (i + 1)^j` in GF(2^8), which is invertible for any `k` columns
+/// chosen from a surviving set of rows (Vandermonde property).
+fn build_encoding_matrix(gf: &Gf256, k: usize, m: usize) -> Vec<Vec<u8>> {
+  let mut matrix = Vec::with_capacity(k + m);
+  for i in 0..k {
+    let mut row = vec![0u8; k];
+    row[i] = 1;
+    matrix.push(row);
+  }
+  for i in 0..m {
+    let x = (i + 1) as u8;
+    let mut row = vec![0u8; k];
+    let mut power = 1u8;
+    for cell in row.iter_mut() {
+      *cell = power;
+      power = gf.mul(power, x);
+    }
+    matrix.push(row);
+  }
+  matrix
+}
+
+/// Split `data` into `k` shards (zero-padded to a common length) and
+/// compute `m` parity shards, returning the header plus the `m` parity
+/// shards (the `k` data shards are the caller's padded input and are not
+/// duplicated here).
+pub fn encode(data: &[u8], k: usize, m: usize) -> Result<(ErasureHeader, Vec<Vec<u8>>)> {
+  if k == 0 {
+    return Err(KiteError::Internal("erasure k must be > 0".to_string()));
+  }
+  let gf = Gf256::new();
+  let shard_len = data.len().div_ceil(k).max(1);
+
+  let mut data_shards = Vec::with_capacity(k);
+  for i in 0..k {
+    let start = i * shard_len;
+    let mut shard = vec![0u8; shard_len];
+    if start < data.len() {
+      let end = (start + shard_len).min(data.len());
+      shard[..end - start].copy_from_slice(&data[start..end]);
+    }
+    data_shards.push(shard);
+  }
+
+  let matrix = build_encoding_matrix(&gf, k, m);
+  let mut parity_shards = vec![vec![0u8; shard_len]; m];
+  for (p_idx, parity) in parity_shards.iter_mut().enumerate() {
+    let row = &matrix[k + p_idx];
+    for byte_idx in 0..shard_len {
+      let mut acc = 0u8;
+      for (shard, &coeff) in data_shards.iter().zip(row.iter()) {
+        acc ^= gf.mul(coeff, shard[byte_idx]);
+      }
+      parity[byte_idx] = acc;
+    }
+  }
+
+  let mut shard_crcs = Vec::with_capacity(k);
+  for shard in &data_shards {
+    shard_crcs.push(crc32(shard));
+  }
+
+  Ok((
+    ErasureHeader {
+      k,
+      m,
+      shard_len,
+      shard_crcs,
+    },
+    parity_shards,
+  ))
+}
+
+/// Reconstruct the original data from any `k` surviving shards (data or
+/// parity) out of the `k + m` total. `present` holds `Some(shard)` for every
+/// surviving shard index in `0..k+m`, `None` for missing/corrupt ones.
+/// Returns `Err` if fewer than `k` shards survive.
+pub fn reconstruct(
+  header: &ErasureHeader,
+  present: &[Option<Vec<u8>>],
+  original_len: usize,
+) -> Result<Vec<u8>> {
+  let gf = Gf256::new();
+  let k = header.k;
+  let m = header.m;
+  if present.len() != k + m {
+    return Err(KiteError::Internal(
+      "erasure reconstruct: shard count mismatch".to_string(),
+    ));
+  }
+
+  let surviving: Vec<usize> = present
+    .iter()
+    .enumerate()
+    .filter(|(_, s)| s.is_some())
+    .map(|(i, _)| i)
+    .collect();
+  if surviving.len() < k {
+    return Err(KiteError::InvalidSnapshot(format!(
+      "erasure reconstruct: only {} of required {k} shards survived",
+      surviving.len()
+    )));
+  }
+
+  let matrix = build_encoding_matrix(&gf, k, m);
+  // Submatrix of the surviving rows (first k of them are enough to solve).
+  let chosen: Vec<usize> = surviving.into_iter().take(k).collect();
+  let sub: Vec<Vec<u8>> = chosen.iter().map(|&r| matrix[r].clone()).collect();
+  let inverse = invert_matrix(&gf, &sub)?;
+
+  let shard_len = header.shard_len;
+  let mut data_shards: Vec<Vec<u8>> = vec![vec![0u8; shard_len]; k];
+  for out_row in 0..k {
+    for byte_idx in 0..shard_len {
+      let mut acc = 0u8;
+      for (col, &row_idx) in chosen.iter().enumerate() {
+        let shard = present[row_idx].as_ref().unwrap();
+        acc ^= gf.mul(inverse[out_row][col], shard[byte_idx]);
+      }
+      data_shards[out_row][byte_idx] = acc;
+    }
+  }
+
+  let mut out = Vec::with_capacity(k * shard_len);
+  for shard in &data_shards {
+    out.extend_from_slice(shard);
+  }
+  out.truncate(original_len);
+  Ok(out)
+}
+
+/// Gaussian elimination over GF(2^8) to invert a `k x k` matrix.
+fn invert_matrix(gf: &Gf256, matrix: &[Vec<u8>]) -> Result<Vec<Vec<u8>>> {
+  let n = matrix.len();
+  let mut aug: Vec<Vec<u8>> = matrix
+    .iter()
+    .enumerate()
+    .map(|(i, row)| {
+      let mut r = row.clone();
+      r.resize(2 * n, 0);
+      r[n + i] = 1;
+      r
+    })
+    .collect();
+
+  for col in 0..n {
+    let pivot_row = (col..n).find(|&r| aug[r][col] != 0).ok_or_else(|| {
+      KiteError::InvalidSnapshot("erasure matrix is singular; shard selection unrecoverable".into())
+    })?;
+    aug.swap(col, pivot_row);
+
+    let pivot_val = aug[col][col];
+    for cell in aug[col].iter_mut() {
+      *cell = gf.div(*cell, pivot_val);
+    }
+
+    for row in 0..n {
+      if row == col {
+        continue;
+      }
+      let factor = aug[row][col];
+      if factor == 0 {
+        continue;
+      }
+      for c in 0..2 * n {
+        aug[row][c] ^= gf.mul(factor, aug[col][c]);
+      }
+    }
+  }
+
+  Ok(aug.into_iter().map(|row| row[n..].to_vec()).collect())
+}
+
+/// Table-driven CRC32 (IEEE polynomial), matching the WAL record checksum.
+pub fn crc32(data: &[u8]) -> u32 {
+  crate::util::crc::crc32_ieee(data)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encode_reconstruct_no_loss() {
+    let data = b"the quick brown fox jumps over the lazy dog, repeated for padding";
+    let (header, parity) = encode(data, 4, 2).expect("encode");
+
+    let mut shards: Vec<Option<Vec<u8>>> = Vec::new();
+    for i in 0..4 {
+      let start = i * header.shard_len;
+      let end = (start + header.shard_len).min(data.len());
+      let mut shard = vec![0u8; header.shard_len];
+      if start < data.len() {
+        shard[..end - start].copy_from_slice(&data[start..end]);
+      }
+      shards.push(Some(shard));
+    }
+    shards.extend(parity.into_iter().map(Some));
+
+    let recovered = reconstruct(&header, &shards, data.len()).expect("reconstruct");
+    assert_eq!(recovered, data);
+  }
+
+  #[test]
+  fn test_reconstruct_with_missing_shards() {
+    let data = b"0123456789abcdef0123456789abcdef0123456789abcdef";
+    let (header, parity) = encode(data, 4, 2).expect("encode");
+
+    let mut shards: Vec<Option<Vec<u8>>> = Vec::new();
+    for i in 0..4 {
+      let start = i * header.shard_len;
+      let end = (start + header.shard_len).min(data.len());
+      let mut shard = vec![0u8; header.shard_len];
+      if start < data.len() {
+        shard[..end - start].copy_from_slice(&data[start..end]);
+      }
+      shards.push(Some(shard));
+    }
+    shards.extend(parity.into_iter().map(Some));
+
+    // Drop two data shards -- still recoverable with m=2 parity shards.
+    shards[1] = None;
+    shards[3] = None;
+
+    let recovered = reconstruct(&header, &shards, data.len()).expect("reconstruct");
+    assert_eq!(recovered, data);
+  }
+
+  #[test]
+  fn test_reconstruct_fails_when_too_many_missing() {
+    let data = b"short data";
+    let (header, parity) = encode(data, 4, 2).expect("encode");
+    let mut shards: Vec<Option<Vec<u8>>> = vec![None; 4];
+    shards.extend(parity.into_iter().map(Some));
+    // Only 2 parity shards survive; k=4 requires at least 4.
+    assert!(reconstruct(&header, &shards, data.len()).is_err());
+  }
+}